@@ -0,0 +1,105 @@
+//
+
+use crate::Point2d;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BBox {
+    pub min: Point2d,
+    pub max: Point2d,
+}
+
+impl BBox {
+    pub fn new(min: Point2d, max: Point2d) -> Self {
+        Self { min, max }
+    }
+
+    // normalizes the corners, so the points can be given in any order
+    pub fn from_points(p1: &Point2d, p2: &Point2d) -> Self {
+        Self {
+            min: Point2d::new(p1.x.min(p2.x), p1.y.min(p2.y)),
+            max: Point2d::new(p1.x.max(p2.x), p1.y.max(p2.y)),
+        }
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max.y - self.min.y
+    }
+
+    pub fn center(&self) -> Point2d {
+        Point2d::new(
+            (self.min.x + self.max.x) / 2.0,
+            (self.min.y + self.max.y) / 2.0,
+        )
+    }
+
+    pub fn contains_point(&self, pt: &Point2d) -> bool {
+        pt.x >= self.min.x && pt.x <= self.max.x && pt.y >= self.min.y && pt.y <= self.max.y
+    }
+
+    pub fn contains(&self, other: &BBox) -> bool {
+        self.contains_point(&other.min) && self.contains_point(&other.max)
+    }
+
+    pub fn intersects(&self, other: &BBox) -> bool {
+        self.min.x <= other.max.x
+            && self.max.x >= other.min.x
+            && self.min.y <= other.max.y
+            && self.max.y >= other.min.y
+    }
+
+    pub fn union(&self, other: &BBox) -> BBox {
+        BBox::new(
+            Point2d::new(self.min.x.min(other.min.x), self.min.y.min(other.min.y)),
+            Point2d::new(self.max.x.max(other.max.x), self.max.y.max(other.max.y)),
+        )
+    }
+
+    pub fn inflate(&self, d: f64) -> BBox {
+        BBox::new(
+            Point2d::new(self.min.x - d, self.min.y - d),
+            Point2d::new(self.max.x + d, self.max.y + d),
+        )
+    }
+
+    // counter clockwise, starting at min
+    pub fn corners(&self) -> [Point2d; 4] {
+        [
+            self.min,
+            Point2d::new(self.max.x, self.min.y),
+            self.max,
+            Point2d::new(self.min.x, self.max.y),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_points() {
+        let bbox = BBox::from_points(&Point2d::new(10.0, -5.0), &Point2d::new(-10.0, 5.0));
+        assert_eq!(bbox.min, Point2d::new(-10.0, -5.0));
+        assert_eq!(bbox.max, Point2d::new(10.0, 5.0));
+        assert_eq!(bbox.width(), 20.0);
+        assert_eq!(bbox.height(), 10.0);
+    }
+
+    #[test]
+    fn contains_and_intersects() {
+        let a = BBox::new(Point2d::new(0.0, 0.0), Point2d::new(10.0, 10.0));
+        let b = BBox::new(Point2d::new(2.0, 2.0), Point2d::new(4.0, 4.0));
+        let c = BBox::new(Point2d::new(8.0, 8.0), Point2d::new(20.0, 20.0));
+        let d = BBox::new(Point2d::new(11.0, 0.0), Point2d::new(20.0, 20.0));
+
+        assert!(a.contains(&b));
+        assert!(!a.contains(&c));
+        assert!(a.intersects(&c));
+        assert!(!a.intersects(&d));
+        assert_eq!(a.union(&d).max, Point2d::new(20.0, 20.0));
+    }
+}
@@ -0,0 +1,152 @@
+//
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Grid, Point2d};
+
+// grid, snap, ortho, polar tracking and object snap like F7, F9, F8, F10 and F3 in AutoCAD
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DrawingAids {
+    pub grid: Grid,
+    pub grid_on: bool,
+    pub snap_on: bool,
+    pub ortho_on: bool,
+    pub polar_on: bool,
+    // polar tracking increment in degrees
+    pub polar_angle: f64,
+    // snap to end points, mid points and centers of existing nodes
+    pub osnap_on: bool,
+}
+
+impl Default for DrawingAids {
+    fn default() -> Self {
+        Self {
+            grid: Grid::default(),
+            grid_on: false,
+            snap_on: false,
+            ortho_on: false,
+            polar_on: false,
+            polar_angle: 45.0,
+            osnap_on: false,
+        }
+    }
+}
+
+impl DrawingAids {
+    // base is the previous point of the command (e.g. the start of a line)
+    pub fn apply(&self, base: Option<&Point2d>, pt: &Point2d) -> Point2d {
+        let mut result = *pt;
+        if self.snap_on {
+            result = self.grid.snap(&result);
+        }
+
+        if let Some(base) = base {
+            if self.ortho_on {
+                result = ortho(base, &result);
+            } else if self.polar_on && self.polar_angle > 0.0 {
+                result = self.polar(base, &result);
+            }
+        }
+        result
+    }
+
+    fn polar(&self, base: &Point2d, pt: &Point2d) -> Point2d {
+        let dx = pt.x - base.x;
+        let dy = pt.y - base.y;
+        if dx == 0.0 && dy == 0.0 {
+            return *pt;
+        }
+
+        let increment = self.polar_angle.to_radians();
+        let angle = (dy.atan2(dx) / increment).round() * increment;
+        let (sin, cos) = angle.sin_cos();
+
+        // project the point onto the tracking ray
+        let mut distance = dx * cos + dy * sin;
+        if self.snap_on && self.grid.spacing > 0.0 {
+            distance = (distance / self.grid.spacing).round() * self.grid.spacing;
+        }
+        Point2d::new(base.x + distance * cos, base.y + distance * sin)
+    }
+}
+
+fn ortho(base: &Point2d, pt: &Point2d) -> Point2d {
+    if (pt.x - base.x).abs() >= (pt.y - base.y).abs() {
+        Point2d::new(pt.x, base.y)
+    } else {
+        Point2d::new(base.x, pt.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round(pt: Point2d) -> Point2d {
+        Point2d::new(crate::round(pt.x), crate::round(pt.y))
+    }
+
+    #[test]
+    fn no_aids() {
+        let aids = DrawingAids::default();
+        let pt = aids.apply(Some(&Point2d::new(0.0, 0.0)), &Point2d::new(3.0, 4.0));
+        assert_eq!(pt, Point2d::new(3.0, 4.0));
+    }
+
+    #[test]
+    fn snap() {
+        let aids = DrawingAids {
+            snap_on: true,
+            ..Default::default()
+        };
+        let pt = aids.apply(None, &Point2d::new(13.0, 27.0));
+        assert_eq!(pt, Point2d::new(10.0, 30.0));
+    }
+
+    #[test]
+    fn ortho() {
+        let aids = DrawingAids {
+            ortho_on: true,
+            ..Default::default()
+        };
+        let base = Point2d::new(10.0, 10.0);
+        assert_eq!(
+            aids.apply(Some(&base), &Point2d::new(50.0, 20.0)),
+            Point2d::new(50.0, 10.0)
+        );
+        assert_eq!(
+            aids.apply(Some(&base), &Point2d::new(0.0, -40.0)),
+            Point2d::new(10.0, -40.0)
+        );
+        // without a base point ortho has no effect
+        assert_eq!(
+            aids.apply(None, &Point2d::new(0.0, -40.0)),
+            Point2d::new(0.0, -40.0)
+        );
+    }
+
+    #[test]
+    fn polar() {
+        let aids = DrawingAids {
+            polar_on: true,
+            polar_angle: 45.0,
+            ..Default::default()
+        };
+        let base = Point2d::new(0.0, 0.0);
+        let pt = aids.apply(Some(&base), &Point2d::new(10.0, 9.0));
+        assert_eq!(round(pt), Point2d::new(9.5, 9.5));
+    }
+
+    #[test]
+    fn polar_with_snap() {
+        let aids = DrawingAids {
+            polar_on: true,
+            polar_angle: 90.0,
+            snap_on: true,
+            ..Default::default()
+        };
+        let base = Point2d::new(0.0, 0.0);
+        let pt = aids.apply(Some(&base), &Point2d::new(2.0, 33.0));
+        assert_eq!(round(pt), Point2d::new(0.0, 30.0));
+    }
+}
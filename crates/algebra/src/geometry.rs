@@ -0,0 +1,280 @@
+//
+// distance and intersection tests used for hit-testing.
+// angles are given in degrees, arcs run counter clockwise from start to end
+
+use crate::{BBox, Point2d};
+
+pub fn distance_to_segment(pt: &Point2d, a: &Point2d, b: &Point2d) -> f64 {
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+    let len2 = dx * dx + dy * dy;
+    if len2 == 0.0 {
+        return pt.distance(a);
+    }
+    let t = (((pt.x - a.x) * dx + (pt.y - a.y) * dy) / len2).clamp(0.0, 1.0);
+    pt.distance(&Point2d::new(a.x + t * dx, a.y + t * dy))
+}
+
+pub fn angle_in_arc(angle: f64, angle_start: f64, angle_end: f64) -> bool {
+    let sweep = arc_sweep(angle_start, angle_end);
+    normalize_angle(angle - angle_start) <= sweep
+}
+
+pub fn arc_point(center: &Point2d, r: f64, angle: f64) -> Point2d {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    Point2d::new(center.x + r * cos, center.y + r * sin)
+}
+
+pub fn distance_to_arc(
+    pt: &Point2d,
+    center: &Point2d,
+    r: f64,
+    angle_start: f64,
+    angle_end: f64,
+) -> f64 {
+    let angle = (pt.y - center.y).atan2(pt.x - center.x).to_degrees();
+    if angle_in_arc(angle, angle_start, angle_end) {
+        return (pt.distance(center) - r).abs();
+    }
+    let start = arc_point(center, r, angle_start);
+    let end = arc_point(center, r, angle_end);
+    pt.distance(&start).min(pt.distance(&end))
+}
+
+pub fn arc_bbox(center: &Point2d, r: f64, angle_start: f64, angle_end: f64) -> BBox {
+    let mut bbox = BBox::from_points(
+        &arc_point(center, r, angle_start),
+        &arc_point(center, r, angle_start + arc_sweep(angle_start, angle_end)),
+    );
+    for quadrant in [0.0, 90.0, 180.0, 270.0] {
+        if angle_in_arc(quadrant, angle_start, angle_end) {
+            let pt = arc_point(center, r, quadrant);
+            bbox = bbox.union(&BBox::new(pt, pt));
+        }
+    }
+    bbox
+}
+
+pub fn segments_intersect(a: &Point2d, b: &Point2d, c: &Point2d, d: &Point2d) -> bool {
+    let d1 = cross(c, d, a);
+    let d2 = cross(c, d, b);
+    let d3 = cross(a, b, c);
+    let d4 = cross(a, b, d);
+    if ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+        && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+    {
+        return true;
+    }
+    (d1 == 0.0 && on_segment(c, d, a))
+        || (d2 == 0.0 && on_segment(c, d, b))
+        || (d3 == 0.0 && on_segment(a, b, c))
+        || (d4 == 0.0 && on_segment(a, b, d))
+}
+
+pub fn segment_intersects_bbox(a: &Point2d, b: &Point2d, bbox: &BBox) -> bool {
+    if bbox.contains_point(a) || bbox.contains_point(b) {
+        return true;
+    }
+    bbox_edges(bbox)
+        .iter()
+        .any(|(c, d)| segments_intersect(a, b, c, d))
+}
+
+pub fn arc_intersects_bbox(
+    center: &Point2d,
+    r: f64,
+    angle_start: f64,
+    angle_end: f64,
+    bbox: &BBox,
+) -> bool {
+    if !arc_bbox(center, r, angle_start, angle_end).intersects(bbox) {
+        return false;
+    }
+    let start = arc_point(center, r, angle_start);
+    if bbox.contains_point(&start) {
+        return true;
+    }
+    bbox_edges(bbox).iter().any(|(a, b)| {
+        circle_segment_intersections(center, r, a, b)
+            .iter()
+            .any(|pt| {
+                let angle = (pt.y - center.y).atan2(pt.x - center.x).to_degrees();
+                angle_in_arc(angle, angle_start, angle_end)
+            })
+    })
+}
+
+fn circle_segment_intersections(
+    center: &Point2d,
+    r: f64,
+    a: &Point2d,
+    b: &Point2d,
+) -> Vec<Point2d> {
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+    let fx = a.x - center.x;
+    let fy = a.y - center.y;
+
+    let qa = dx * dx + dy * dy;
+    let qb = 2.0 * (fx * dx + fy * dy);
+    let qc = fx * fx + fy * fy - r * r;
+    let discriminant = qb * qb - 4.0 * qa * qc;
+    if qa == 0.0 || discriminant < 0.0 {
+        return vec![];
+    }
+
+    let sqrt = discriminant.sqrt();
+    [(-qb - sqrt) / (2.0 * qa), (-qb + sqrt) / (2.0 * qa)]
+        .iter()
+        .filter(|t| (0.0..=1.0).contains(*t))
+        .map(|t| Point2d::new(a.x + t * dx, a.y + t * dy))
+        .collect()
+}
+
+// shoelace formula, the last point is connected to the first
+pub fn polygon_area(points: &[Point2d]) -> f64 {
+    let n = points.len();
+    let twice: f64 = (0..n)
+        .map(|i| {
+            let (a, b) = (&points[i], &points[(i + 1) % n]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum();
+    (twice / 2.0).abs()
+}
+
+pub fn polygon_perimeter(points: &[Point2d]) -> f64 {
+    let n = points.len();
+    if n < 2 {
+        return 0.0;
+    }
+    (0..n)
+        .map(|i| points[i].distance(&points[(i + 1) % n]))
+        .sum()
+}
+
+fn bbox_edges(bbox: &BBox) -> [(Point2d, Point2d); 4] {
+    let [p0, p1, p2, p3] = bbox.corners();
+    [(p0, p1), (p1, p2), (p2, p3), (p3, p0)]
+}
+
+fn cross(a: &Point2d, b: &Point2d, c: &Point2d) -> f64 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+fn on_segment(a: &Point2d, b: &Point2d, pt: &Point2d) -> bool {
+    pt.x >= a.x.min(b.x) && pt.x <= a.x.max(b.x) && pt.y >= a.y.min(b.y) && pt.y <= a.y.max(b.y)
+}
+
+fn normalize_angle(angle: f64) -> f64 {
+    angle.rem_euclid(360.0)
+}
+
+// start == end is a full circle
+pub fn arc_sweep(angle_start: f64, angle_end: f64) -> f64 {
+    let sweep = normalize_angle(angle_end - angle_start);
+    if sweep == 0.0 {
+        360.0
+    } else {
+        sweep
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::round;
+
+    #[test]
+    fn segment_distance() {
+        let a = Point2d::new(0.0, 0.0);
+        let b = Point2d::new(10.0, 0.0);
+        assert_eq!(distance_to_segment(&Point2d::new(5.0, 3.0), &a, &b), 3.0);
+        assert_eq!(distance_to_segment(&Point2d::new(13.0, 4.0), &a, &b), 5.0);
+    }
+
+    #[test]
+    fn arc_distance() {
+        let center = Point2d::new(0.0, 0.0);
+        // quarter arc in the first quadrant
+        assert_eq!(
+            distance_to_arc(&Point2d::new(0.0, 12.0), &center, 10.0, 0.0, 90.0),
+            2.0
+        );
+        assert_eq!(
+            round(distance_to_arc(
+                &Point2d::new(-10.0, 0.0),
+                &center,
+                10.0,
+                0.0,
+                90.0
+            )),
+            14.14
+        );
+    }
+
+    #[test]
+    fn angles() {
+        assert!(angle_in_arc(45.0, 0.0, 90.0));
+        assert!(!angle_in_arc(135.0, 0.0, 90.0));
+        assert!(angle_in_arc(0.0, 270.0, 90.0));
+        assert!(angle_in_arc(180.0, 0.0, 360.0));
+    }
+
+    #[test]
+    fn polygon() {
+        let square = [
+            Point2d::new(0.0, 0.0),
+            Point2d::new(10.0, 0.0),
+            Point2d::new(10.0, 10.0),
+            Point2d::new(0.0, 10.0),
+        ];
+        assert_eq!(polygon_area(&square), 100.0);
+        assert_eq!(polygon_perimeter(&square), 40.0);
+        // clockwise gives the same area
+        let reversed: Vec<Point2d> = square.iter().rev().copied().collect();
+        assert_eq!(polygon_area(&reversed), 100.0);
+        assert_eq!(polygon_area(&square[..2]), 0.0);
+    }
+
+    #[test]
+    fn bbox_of_arc() {
+        let bbox = arc_bbox(&Point2d::new(0.0, 0.0), 10.0, 0.0, 360.0);
+        assert_eq!(round(bbox.min.x), -10.0);
+        assert_eq!(round(bbox.max.y), 10.0);
+
+        let bbox = arc_bbox(&Point2d::new(0.0, 0.0), 10.0, 0.0, 90.0);
+        assert_eq!(round(bbox.min.x), 0.0);
+        assert_eq!(round(bbox.min.y), 0.0);
+        assert_eq!(round(bbox.max.x), 10.0);
+        assert_eq!(round(bbox.max.y), 10.0);
+    }
+
+    #[test]
+    fn crossing() {
+        let bbox = BBox::new(Point2d::new(0.0, 0.0), Point2d::new(10.0, 10.0));
+        assert!(segment_intersects_bbox(
+            &Point2d::new(-5.0, 5.0),
+            &Point2d::new(15.0, 5.0),
+            &bbox
+        ));
+        assert!(!segment_intersects_bbox(
+            &Point2d::new(-5.0, 15.0),
+            &Point2d::new(15.0, 15.0),
+            &bbox
+        ));
+
+        // circle around the box does not cross it
+        let center = Point2d::new(5.0, 5.0);
+        assert!(!arc_intersects_bbox(&center, 20.0, 0.0, 360.0, &bbox));
+        assert!(arc_intersects_bbox(&center, 6.0, 0.0, 360.0, &bbox));
+        // only the upper half of the circle, which passes above the box
+        assert!(!arc_intersects_bbox(
+            &Point2d::new(5.0, 12.0),
+            3.0,
+            0.0,
+            180.0,
+            &bbox
+        ));
+    }
+}
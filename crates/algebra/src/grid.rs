@@ -0,0 +1,156 @@
+//
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Point2d, Viewport};
+
+// minor grid lines closer than this (in pixel) are not drawn
+const MIN_GRID_PIXEL: f64 = 8.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Grid {
+    pub spacing: f64,
+    // every n-th line is a major line
+    pub major_every: u32,
+    pub origin: Point2d,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridLine {
+    pub from: Point2d,
+    pub to: Point2d,
+    pub major: bool,
+}
+
+impl Default for Grid {
+    fn default() -> Self {
+        Self {
+            spacing: 10.0,
+            major_every: 5,
+            origin: Point2d::new(0.0, 0.0),
+        }
+    }
+}
+
+impl Grid {
+    pub fn snap(&self, pt: &Point2d) -> Point2d {
+        Point2d::new(
+            snap_value(pt.x, self.origin.x, self.spacing),
+            snap_value(pt.y, self.origin.y, self.spacing),
+        )
+    }
+
+    // the spacing grows with the major factor when zooming out,
+    // so the grid never gets denser than MIN_GRID_PIXEL
+    pub fn visible_spacing(&self, scale: f64) -> f64 {
+        let factor = if self.major_every > 1 {
+            self.major_every as f64
+        } else {
+            2.0
+        };
+        let mut spacing = self.spacing;
+        if spacing <= 0.0 || scale <= 0.0 {
+            return spacing;
+        }
+        while spacing * scale < MIN_GRID_PIXEL {
+            spacing *= factor;
+        }
+        spacing
+    }
+
+    pub fn lines(&self, viewport: &Viewport) -> Vec<GridLine> {
+        let mut result = vec![];
+        if self.spacing <= 0.0 {
+            return result;
+        }
+        let spacing = self.visible_spacing(viewport.scale);
+        let area = viewport.get_visible_area();
+        let major_every = self.major_every.max(1) as i64;
+
+        let first = ((area.min.x - self.origin.x) / spacing).ceil() as i64;
+        let last = ((area.max.x - self.origin.x) / spacing).floor() as i64;
+        for i in first..=last {
+            let x = self.origin.x + i as f64 * spacing;
+            result.push(GridLine {
+                from: Point2d::new(x, area.min.y),
+                to: Point2d::new(x, area.max.y),
+                major: is_major(x - self.origin.x, spacing, major_every),
+            });
+        }
+
+        let first = ((area.min.y - self.origin.y) / spacing).ceil() as i64;
+        let last = ((area.max.y - self.origin.y) / spacing).floor() as i64;
+        for i in first..=last {
+            let y = self.origin.y + i as f64 * spacing;
+            result.push(GridLine {
+                from: Point2d::new(area.min.x, y),
+                to: Point2d::new(area.max.x, y),
+                major: is_major(y - self.origin.y, spacing, major_every),
+            });
+        }
+        result
+    }
+}
+
+// counted in the spacing that is drawn, a coarser grid has its own major lines
+fn is_major(offset: f64, spacing: f64, major_every: i64) -> bool {
+    let index = (offset / spacing).round() as i64;
+    index % major_every == 0
+}
+
+fn snap_value(value: f64, origin: f64, spacing: f64) -> f64 {
+    if spacing <= 0.0 {
+        return value;
+    }
+    origin + ((value - origin) / spacing).round() * spacing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snap() {
+        let grid = Grid::default();
+        let pt = grid.snap(&Point2d::new(14.0, -16.0));
+        assert_eq!(pt, Point2d::new(10.0, -20.0));
+    }
+
+    #[test]
+    fn snap_with_origin() {
+        let grid = Grid {
+            spacing: 5.0,
+            major_every: 5,
+            origin: Point2d::new(1.0, 1.0),
+        };
+        let pt = grid.snap(&Point2d::new(3.0, 4.0));
+        assert_eq!(pt, Point2d::new(1.0, 6.0));
+    }
+
+    #[test]
+    fn visible_spacing_grows_when_zooming_out() {
+        let grid = Grid::default();
+        assert_eq!(grid.visible_spacing(1.0), 10.0);
+        assert_eq!(grid.visible_spacing(0.5), 50.0);
+        assert_eq!(grid.visible_spacing(0.01), 1250.0);
+    }
+
+    #[test]
+    fn lines() {
+        let mut viewport = Viewport::new();
+        viewport.set_canvas_size(100.0, 100.0);
+        let grid = Grid::default();
+
+        let lines = grid.lines(&viewport);
+        // -50 .. 50 in both directions
+        assert_eq!(lines.len(), 22);
+        assert_eq!(lines.iter().filter(|line| line.major).count(), 6);
+
+        // zoomed out the grid is drawn every 50, major lines every 250
+        viewport.set_canvas_size(500.0, 500.0);
+        viewport.scale = 0.5;
+        let lines = grid.lines(&viewport);
+        assert_eq!(lines.len(), 22);
+        assert_eq!(lines.iter().filter(|line| line.major).count(), 6);
+    }
+}
@@ -1,9 +1,19 @@
 //
 
+mod bbox;
+mod drawingaids;
+mod geometry;
+mod grid;
 mod matrix;
 mod point2d;
+mod ucs;
 mod viewport;
 
+pub use bbox::*;
+pub use drawingaids::*;
+pub use geometry::*;
+pub use grid::*;
 pub use matrix::*;
 pub use point2d::*;
+pub use ucs::*;
 pub use viewport::*;
@@ -2,6 +2,7 @@
 
 use crate::point2d::Point2d;
 
+#[derive(Clone, Copy, PartialEq)]
 pub struct Matrix {
     a: f64,
     b: f64,
@@ -15,6 +16,7 @@ pub struct Matrix {
 }
 
 impl Matrix {
+    #[allow(clippy::too_many_arguments)]
     fn new(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64, g: f64, h: f64, i: f64) -> Self {
         Self {
             a,
@@ -39,7 +41,7 @@ impl Matrix {
     pub fn scale(sx: f64, sy: f64) -> Self {
         Self::new(sx, 0.0, 0.0, 0.0, sy, 0.0, 0.0, 0.0, 1.0)
     }
-    fn rotate(theta: f64) -> Self {
+    pub fn rotate(theta: f64) -> Self {
         let cos_theta = theta.cos();
         let sin_theta = theta.sin();
         Self::new(
@@ -53,6 +55,42 @@ impl Matrix {
         Point2d::new(x / (self.g * point.x + self.h * point.y + self.i), y)
     }
 
+    // a direction, the translation is not applied
+    pub fn multiply_vector(&self, vector: &Point2d) -> Point2d {
+        Point2d::new(
+            self.a * vector.x + self.b * vector.y,
+            self.d * vector.x + self.e * vector.y,
+        )
+    }
+
+    // the value of an svg transform attribute
+    pub fn to_svg(&self) -> String {
+        format!(
+            "matrix({} {} {} {} {} {})",
+            self.a, self.d, self.b, self.e, self.c, self.f
+        )
+    }
+
+    // the values of an svg matrix(a b c d e f)
+    pub fn from_svg(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Self {
+        Self::new(a, c, e, b, d, f, 0.0, 0.0, 1.0)
+    }
+
+    // the scale if x and y are scaled alike and stay perpendicular,
+    // None e.g. for a skew
+    pub fn uniform_scale(&self) -> Option<f64> {
+        let sx = self.a.hypot(self.d);
+        let sy = self.b.hypot(self.e);
+        let dot = self.a * self.b + self.d * self.e;
+        let epsilon = 1e-9 * sx.max(sy);
+        ((sx - sy).abs() <= epsilon && dot.abs() <= epsilon * sx.max(sy)).then_some(sx)
+    }
+
+    // true if the orientation is reversed, e.g. by scale(1, -1)
+    pub fn is_mirrored(&self) -> bool {
+        self.a * self.e - self.b * self.d < 0.0
+    }
+
     pub fn inverse(&self) -> Option<Self> {
         let det = self.a * (self.e * self.i - self.f * self.h)
             - self.b * (self.d * self.i - self.f * self.g)
@@ -161,6 +199,28 @@ mod tests {
         assert_eq!(result.y, 100.0);
     }
 
+    #[test]
+    fn rotate_and_inverse() {
+        let m = Matrix::translate(10.0, 0.0) * Matrix::rotate(std::f64::consts::FRAC_PI_2);
+        let pt = m.multiply(&Point2d::new(1.0, 0.0));
+        assert!((pt.x - 10.0).abs() < 1e-12 && (pt.y - 1.0).abs() < 1e-12);
+        let back = m.inverse().unwrap().multiply(&pt);
+        assert!((back.x - 1.0).abs() < 1e-12 && back.y.abs() < 1e-12);
+        assert_eq!(Matrix::scale(1.0, -1.0).to_svg(), "matrix(1 0 0 -1 0 0)");
+    }
+
+    #[test]
+    fn svg_matrix() {
+        let m = Matrix::from_svg(1.0, 2.0, 3.0, 4.0, 5.0, 6.0);
+        assert_eq!(m.to_svg(), "matrix(1 2 3 4 5 6)");
+        assert_eq!(m.uniform_scale(), None);
+
+        let m = Matrix::rotate(0.5) * Matrix::scale(2.0, -2.0);
+        assert!((m.uniform_scale().unwrap() - 2.0).abs() < 1e-12);
+        assert!(m.is_mirrored());
+        assert!(!Matrix::rotate(0.5).is_mirrored());
+    }
+
     #[test]
     fn scale() {
         let pt = Point2d::new(10.0, 20.0);
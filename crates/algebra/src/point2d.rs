@@ -1,9 +1,8 @@
-use wasm_bindgen::prelude::*;
-
 use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
 
-// #[derive(Debug, Serialize, Deserialize)]
 #[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
 pub struct Point2d {
     pub x: f64,
     pub y: f64,
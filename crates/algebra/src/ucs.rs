@@ -0,0 +1,63 @@
+//
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Matrix, Point2d};
+
+// user coordinate system, points typed in commands are given in it
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Ucs {
+    pub origin: Point2d,
+    // direction of the x axis in degree, counter clockwise from the world x axis
+    pub angle: f64,
+}
+
+impl Ucs {
+    pub fn new(origin: Point2d, angle: f64) -> Self {
+        Ucs { origin, angle }
+    }
+
+    pub fn is_world(&self) -> bool {
+        *self == Ucs::default()
+    }
+
+    // ucs to world coordinates
+    pub fn matrix(&self) -> Matrix {
+        Matrix::translate(self.origin.x, self.origin.y) * Matrix::rotate(self.angle.to_radians())
+    }
+
+    pub fn to_world(&self, pt: &Point2d) -> Point2d {
+        self.matrix().multiply(pt)
+    }
+
+    pub fn to_ucs(&self, pt: &Point2d) -> Point2d {
+        match self.matrix().inverse() {
+            Some(inverse) => inverse.multiply(pt),
+            None => *pt,
+        }
+    }
+
+    // e.g. a relative coordinate
+    pub fn vector_to_world(&self, vector: &Point2d) -> Point2d {
+        self.matrix().multiply_vector(vector)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::round;
+
+    #[test]
+    fn world_and_ucs() {
+        let ucs = Ucs::new(Point2d::new(10.0, 5.0), 90.0);
+        let pt = ucs.to_world(&Point2d::new(2.0, 1.0));
+        assert_eq!((round(pt.x), round(pt.y)), (9.0, 7.0));
+        let back = ucs.to_ucs(&pt);
+        assert_eq!((round(back.x), round(back.y)), (2.0, 1.0));
+        let v = ucs.vector_to_world(&Point2d::new(1.0, 0.0));
+        assert_eq!((round(v.x), round(v.y)), (0.0, 1.0));
+        assert!(Ucs::default().is_world());
+        assert!(!ucs.is_world());
+    }
+}
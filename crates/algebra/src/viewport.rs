@@ -1,10 +1,28 @@
 //
 
-use crate::{Matrix, Point2d};
+use serde::{Deserialize, Serialize};
+
+use crate::{BBox, Matrix, Point2d};
+
+// zoom previous goes back this many views
+const MAX_HISTORY: usize = 10;
+// zoom extents leaves some space around the nodes
+const EXTENTS_MARGIN: f64 = 0.05;
 
 pub fn round(x: f64) -> f64 {
     (x * 100.0).round() / 100.0
 }
+// the visible area in view coordinates, see Viewport::get_view_matrix
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct View {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    #[serde(default)]
+    pub rotation: f64,
+}
+
 #[derive(Debug)]
 pub struct Viewport {
     canvas_width: f64,
@@ -16,6 +34,17 @@ pub struct Viewport {
     pub y: f64,
     pub width: f64,
     pub height: f64,
+    // twist angle of the view in degree, the drawing turns clockwise
+    pub rotation: f64,
+
+    // views before the last zoom or pan command
+    history: Vec<View>,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Viewport {
@@ -30,6 +59,8 @@ impl Viewport {
             y: -200.0,
             width: 400.0,
             height: 400.0,
+            rotation: 0.0,
+            history: vec![],
         }
     }
 
@@ -76,10 +107,10 @@ impl Viewport {
     pub fn zoom_viewport(&mut self, delta_y: f64, center_x: f64, center_y: f64) {
         // Zoom the viewport based on the mouse wheel event
 
-        let MAX_DELTA = 10.0;
+        const MAX_DELTA: f64 = 10.0;
         let mut delta = delta_y.abs().min(MAX_DELTA);
         let sign = -delta_y.signum();
-        delta = delta * sign;
+        delta *= sign;
 
         let old_scale = self.scale;
         let new_scale = old_scale * (1.0 + delta / 100.0);
@@ -102,18 +133,179 @@ impl Viewport {
         self.y = round(self.y + delta_y / self.scale);
     }
 
+    // model to view coordinates, the coordinates of the svg viewBox:
+    // the svg y-axis points down and the view is rotated
+    pub fn get_view_matrix(&self) -> Matrix {
+        Matrix::scale(1.0, -1.0) * Matrix::rotate(-self.rotation.to_radians())
+    }
+
+    // model to client coordinates in pixel
+    pub fn get_client_matrix(&self) -> Matrix {
+        Matrix::scale(self.scale, self.scale)
+            * Matrix::translate(-self.x, -self.y)
+            * self.get_view_matrix()
+    }
+
     pub fn client_to_canvas(&self, pt: Point2d) -> Point2d {
         // Convert client coordinates to canvas coordinates
-        let x = (pt.x + self.x * self.scale) / self.scale;
-        let y = (-pt.y - self.y * self.scale) / self.scale;
-        Point2d::new(x, y)
+        match self.get_client_matrix().inverse() {
+            Some(inverse) => inverse.multiply(&pt),
+            None => pt,
+        }
+    }
+
+    // the model area around the (maybe rotated) view
+    pub fn get_visible_area(&self) -> BBox {
+        let to_model = self.to_model();
+        let corners = [
+            Point2d::new(self.x, self.y),
+            Point2d::new(self.x + self.width, self.y),
+            Point2d::new(self.x, self.y + self.height),
+            Point2d::new(self.x + self.width, self.y + self.height),
+        ]
+        .map(|pt| to_model.multiply(&pt));
+        bbox_of(&corners)
+    }
+
+    // the center of the view is kept
+    pub fn set_rotation(&mut self, rotation: f64) {
+        let center = self.get_center();
+        self.rotation = rotation;
+        self.center_on(&center);
+    }
+
+    pub fn get_view(&self) -> View {
+        View {
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+            rotation: self.rotation,
+        }
+    }
+
+    pub fn set_view(&mut self, view: &View) {
+        self.rotation = view.rotation;
+        self.set_viewport(view.x, view.y, view.width, view.height);
+    }
+
+    // center of the visible area in model coordinates
+    pub fn get_center(&self) -> Point2d {
+        self.to_model().multiply(&Point2d::new(
+            self.x + self.width / 2.0,
+            self.y + self.height / 2.0,
+        ))
+    }
+
+    // the bounding box of all nodes, a single point is only centered
+    pub fn zoom_extents(&mut self, extents: &BBox) {
+        let margin = extents.width().max(extents.height()) * EXTENTS_MARGIN;
+        self.zoom_window(&extents.inflate(margin));
+    }
+
+    pub fn zoom_window(&mut self, window: &BBox) {
+        self.remember();
+        if window.width() <= 0.0 && window.height() <= 0.0 {
+            self.center_on(&window.center());
+            return;
+        }
+        // the window as seen in the rotated view
+        let view_matrix = self.get_view_matrix();
+        let corners = window.corners().map(|pt| view_matrix.multiply(&pt));
+        let view_window = bbox_of(&corners);
+        self.set_viewport(
+            view_window.min.x,
+            view_window.min.y,
+            view_window.width(),
+            view_window.height(),
+        );
+        self.center_on(&window.center());
+    }
+
+    // e.g. a named view
+    pub fn zoom_view(&mut self, view: &View) {
+        self.remember();
+        self.set_view(view);
+    }
+
+    // the previous views belong to another page
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    // false if there is no previous view
+    pub fn zoom_previous(&mut self) -> bool {
+        match self.history.pop() {
+            Some(view) => {
+                self.set_view(&view);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // a factor of 2 shows everything twice as large, the center is kept
+    pub fn zoom_scale(&mut self, factor: f64) {
+        if factor <= 0.0 {
+            return;
+        }
+        self.remember();
+        let center = self.get_center();
+        self.set_viewport(self.x, self.y, self.width / factor, self.height / factor);
+        self.center_on(&center);
+    }
+
+    // without a height the scale is kept
+    pub fn zoom_center(&mut self, center: &Point2d, height: Option<f64>) {
+        self.remember();
+        if let Some(height) = height.filter(|height| *height > 0.0) {
+            self.set_viewport(self.x, self.y, 0.0, height);
+        }
+        self.center_on(center);
+    }
+
+    // moves the drawing by delta, in model coordinates
+    pub fn pan(&mut self, delta: &Point2d) {
+        self.remember();
+        let delta = self.get_view_matrix().multiply_vector(delta);
+        self.x -= delta.x;
+        self.y -= delta.y;
+    }
+
+    fn remember(&mut self) {
+        if self.history.len() == MAX_HISTORY {
+            self.history.remove(0);
+        }
+        self.history.push(self.get_view());
+    }
+
+    fn center_on(&mut self, center: &Point2d) {
+        let center = self.get_view_matrix().multiply(center);
+        self.x = center.x - self.width / 2.0;
+        self.y = center.y - self.height / 2.0;
+    }
+
+    // view to model coordinates
+    fn to_model(&self) -> Matrix {
+        // a rotation with a mirror can always be inverted
+        self.get_view_matrix()
+            .inverse()
+            .unwrap_or_else(Matrix::identity)
     }
 
     // ------------------------
 }
 
-#[cfg(test)]
+fn bbox_of(points: &[Point2d]) -> BBox {
+    points
+        .iter()
+        .skip(1)
+        .fold(BBox::new(points[0], points[0]), |bbox, pt| {
+            bbox.union(&BBox::new(*pt, *pt))
+        })
+}
 
+#[cfg(test)]
 mod tests {
     use super::*;
 
@@ -169,6 +361,75 @@ mod tests {
         assert_eq!(viewport.scale, 1.0 / 1.5);
     }
 
+    #[test]
+    fn test_zoom_window_keeps_ratio() {
+        let mut viewport = Viewport::new();
+        viewport.set_canvas_size(800.0, 400.0);
+        let window = BBox::new(Point2d::new(0.0, 0.0), Point2d::new(100.0, 100.0));
+        viewport.zoom_window(&window);
+
+        assert_eq!(viewport.width, 200.0);
+        assert_eq!(viewport.height, 100.0);
+        assert_eq!(viewport.scale, 4.0);
+        assert_eq!(viewport.get_center(), Point2d::new(50.0, 50.0));
+        let visible = viewport.get_visible_area();
+        assert_eq!(visible.min, Point2d::new(-50.0, 0.0));
+        assert_eq!(visible.max, Point2d::new(150.0, 100.0));
+    }
+
+    #[test]
+    fn test_zoom_history() {
+        let mut viewport = Viewport::new();
+        viewport.set_canvas_size(800.0, 400.0);
+        let start = viewport.get_view();
+
+        viewport.zoom_scale(2.0);
+        assert_eq!(viewport.scale, 2.0);
+        assert_eq!(viewport.get_center(), Point2d::new(0.0, 0.0));
+
+        viewport.zoom_center(&Point2d::new(10.0, 20.0), Some(50.0));
+        assert_eq!(viewport.height, 50.0);
+        assert_eq!(viewport.get_center(), Point2d::new(10.0, 20.0));
+
+        viewport.pan(&Point2d::new(5.0, 5.0));
+        assert_eq!(viewport.get_center(), Point2d::new(5.0, 15.0));
+
+        // a single point is centered at the same scale
+        viewport.zoom_extents(&BBox::new(Point2d::new(1.0, 1.0), Point2d::new(1.0, 1.0)));
+        assert_eq!(viewport.height, 50.0);
+        assert_eq!(viewport.get_center(), Point2d::new(1.0, 1.0));
+
+        for _ in 0..4 {
+            assert!(viewport.zoom_previous());
+        }
+        assert_eq!(viewport.get_view(), start);
+        assert!(!viewport.zoom_previous());
+    }
+
+    #[test]
+    fn test_rotation() {
+        let mut viewport = Viewport::new();
+        viewport.set_canvas_size(800.0, 400.0);
+        viewport.zoom_center(&Point2d::new(100.0, 50.0), None);
+        viewport.set_rotation(90.0);
+        assert_eq!(round(viewport.get_center().x), 100.0);
+        assert_eq!(round(viewport.get_center().y), 50.0);
+
+        // the model y axis points to the right of the screen
+        let center = Point2d::new(400.0, 200.0);
+        let pt = viewport.client_to_canvas(center + Point2d::new(10.0, 0.0));
+        assert_eq!((round(pt.x), round(pt.y)), (100.0, 60.0));
+        let client = viewport.get_client_matrix().multiply(&pt);
+        assert_eq!((round(client.x), round(client.y)), (410.0, 200.0));
+
+        let visible = viewport.get_visible_area();
+        assert_eq!(round(visible.width()), 400.0);
+        assert_eq!(round(visible.height()), 800.0);
+
+        viewport.pan(&Point2d::new(0.0, 10.0));
+        assert_eq!(round(viewport.get_center().y), 40.0);
+    }
+
     #[test]
     fn test_zoom_viewport() {
         let mut viewport = Viewport::new();
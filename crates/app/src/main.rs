@@ -1,13 +1,86 @@
 //
 // https://github.com/snapview/tokio-tungstenite/blob/master/examples/client.rs
 //
+// app                         send command lines from stdin to the server
+//
+// without a server, e.g. in CI:
+// app --script file.scr       run a script, stop at the first error
+//     --continue              go on after errors
+//     --journal file.jsonl    append the executed commands to a journal
+//     --replay file.jsonl     rebuild the drawing from a journal first
+//
+// app --convert in.dxf|in.svg|in.json|in.dmb out.json|out.dmb|out.dxf|out.pdf|out.png [option=value ...]
+//                             options of export, e.g. version=r12 or paper=a3 scale=1:50,
+//                             an older document is written in the current format
+//     --dxf-version r12|r2000 same as version=
+//
+
+use std::fs::{File, OpenOptions};
+use std::io::BufReader as FileReader;
+use std::process::ExitCode;
 
+use command::{
+    replay, run_script, Command, CommandHandler, CommandLine, CommandSession, ExportCommand,
+    ExporterRegistry, ImportCommand, Journal, OnError, PageCommand,
+};
+use datamodel::{read_document_bytes, DataModel};
 use futures_util::{future, pin_mut, StreamExt};
-use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
 
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(i) = args.iter().position(|arg| arg == "--convert") {
+        // after the input and output paths, which may contain '='
+        let mut options: Vec<(String, String)> = vec![];
+        let mut rest = args.iter().skip(i + 3);
+        while let Some(arg) = rest.next() {
+            if arg == "--dxf-version" {
+                rest.next();
+            } else if let Some((key, value)) = arg.split_once('=') {
+                options.push((key.to_lowercase(), value.to_string()));
+            }
+        }
+        return match (
+            args.get(i + 1),
+            args.get(i + 2),
+            option(&args, "--dxf-version"),
+        ) {
+            (Some(input), Some(output), Ok(version)) => {
+                options.extend(version.map(|version| ("version".to_string(), version)));
+                convert(input, output, &options)
+            }
+            _ => {
+                eprintln!(
+                    "usage: app --convert <in.dxf|in.svg|in.json|in.dmb> <out.json|out.dmb|out.dxf|out.pdf|out.png> [option=value ...]"
+                );
+                ExitCode::FAILURE
+            }
+        };
+    }
+    let (script, journal, replay) = match (
+        option(&args, "--script"),
+        option(&args, "--journal"),
+        option(&args, "--replay"),
+    ) {
+        (Ok(script), Ok(journal), Ok(replay)) => (script, journal, replay),
+        _ => {
+            eprintln!(
+                "usage: app [--replay <file>] [--script <file> [--continue]] [--journal <file>]"
+            );
+            return ExitCode::FAILURE;
+        }
+    };
+    if script.is_some() || replay.is_some() {
+        let on_error = if args.iter().any(|arg| arg == "--continue") {
+            OnError::Continue
+        } else {
+            OnError::Stop
+        };
+        return run_batch(script, on_error, journal, replay);
+    }
+
     let url = "ws://localhost:9002";
 
     // let url = env::args()
@@ -29,7 +102,11 @@ async fn main() {
         read.for_each(|message| async {
             match message {
                 Ok(Message::Text(text)) => println!("Text message: {}", text),
-                Ok(Message::Binary(_)) => println!("Binary message"),
+                // the answer to "thumbnail"
+                Ok(Message::Binary(png)) => match std::fs::write("thumbnail.png", &png) {
+                    Ok(()) => println!("thumbnail.png written, {} bytes", png.len()),
+                    Err(err) => eprintln!("Error: {}", err),
+                },
                 Ok(Message::Ping(_)) => println!("Ping message"),
                 Ok(Message::Pong(_)) => println!("Pong message"),
                 Ok(Message::Close(_)) => println!("Close message"),
@@ -43,6 +120,159 @@ async fn main() {
 
     pin_mut!(stdin_to_ws, ws_to_stdout);
     future::select(stdin_to_ws, ws_to_stdout).await;
+    ExitCode::SUCCESS
+}
+
+// the value after the option name, Err if it is missing
+fn option(args: &[String], name: &str) -> Result<Option<String>, ()> {
+    match args.iter().position(|arg| arg == name) {
+        Some(i) => args.get(i + 1).cloned().map(Some).ok_or(()),
+        None => Ok(None),
+    }
+}
+
+// errors are reported with their line number, the exit code fails the build
+fn run_batch(
+    script: Option<String>,
+    on_error: OnError,
+    journal: Option<String>,
+    replay_from: Option<String>,
+) -> ExitCode {
+    // plain id counters, the same input gives the same ids
+    let mut data_model = DataModel::default();
+    let mut command_handler = CommandHandler::default();
+
+    if let Some(filename) = replay_from {
+        let result = File::open(&filename)
+            .map_err(|err| err.to_string())
+            .and_then(|file| {
+                replay(FileReader::new(file), &mut data_model, &mut command_handler)
+                    .map_err(|err| err.to_string())
+            });
+        match result {
+            Ok(count) => println!("{}: {} entries replayed", filename, count),
+            Err(err) => {
+                eprintln!("{}: {}", filename, err);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    // opened after the replay, so a journal can be replayed and continued
+    if let Some(filename) = journal {
+        match OpenOptions::new().create(true).append(true).open(&filename) {
+            Ok(file) => command_handler.set_journal(Journal::new(Box::new(file))),
+            Err(err) => {
+                eprintln!("Can not open '{}': {}", filename, err);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let Some(filename) = script else {
+        return ExitCode::SUCCESS;
+    };
+    let text = match std::fs::read_to_string(&filename) {
+        Ok(text) => text,
+        Err(err) => {
+            eprintln!("Can not read '{}': {}", filename, err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut session = CommandSession::default();
+    let result = run_script(&mut session, &mut data_model, &text, on_error, |dm, cmd| {
+        if let Some(inquiry) = cmd.get_inquiry() {
+            println!("{}", inquiry);
+        }
+        command_handler.execute(dm, cmd)
+    });
+
+    for error in &result.errors {
+        eprintln!("{}: {}", filename, error);
+    }
+    println!(
+        "{}: {} commands executed, {} errors",
+        filename,
+        result.executed,
+        result.errors.len()
+    );
+    if result.is_ok() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+// a json or binary document, or the nodes of a dxf or svg file on one page,
+// written in the format of the output file
+fn convert(input: &str, output: &str, options: &[(String, String)]) -> ExitCode {
+    let lowercase = input.to_ascii_lowercase();
+    let (mut data_model, node_count) =
+        if lowercase.ends_with(".json") || lowercase.ends_with(".dmb") {
+            match std::fs::read(input)
+                .map_err(|err| format!("Can not read '{}': {}", input, err))
+                .and_then(|bytes| {
+                    read_document_bytes(&bytes).map_err(|err| format!("{}: {}", input, err))
+                }) {
+                Ok(data_model) => {
+                    let count = data_model.get_nodes().len();
+                    (data_model, count)
+                }
+                Err(err) => {
+                    eprintln!("{}", err);
+                    return ExitCode::FAILURE;
+                }
+            }
+        } else {
+            match import(input) {
+                Some(import) => import,
+                None => return ExitCode::FAILURE,
+            }
+        };
+
+    let format = options
+        .iter()
+        .find(|(key, _)| key == "format")
+        .map(|(_, value)| value.as_str());
+    let export =
+        ExportCommand::from_registry(&ExporterRegistry::default(), output.to_string(), format)
+            .and_then(|mut export| {
+                for (key, value) in options.iter().filter(|(key, _)| key != "format") {
+                    export.set_option(key, value)?;
+                }
+                CommandHandler::default().execute(&mut data_model, Box::new(export))
+            });
+    if let Err(err) = export {
+        eprintln!("{}", err);
+        return ExitCode::FAILURE;
+    }
+    println!("{}: {} nodes written to {}", input, node_count, output);
+    ExitCode::SUCCESS
+}
+
+// a data model with the nodes of a dxf or svg file on one page
+fn import(input: &str) -> Option<(DataModel, usize)> {
+    let mut data_model = DataModel::default();
+    let name = std::path::Path::new(input)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let page_id = data_model.next_id();
+    PageCommand::new(page_id, name, String::new()).execute(&mut data_model);
+
+    let import = match ImportCommand::from_file(&mut data_model, input) {
+        Ok(import) => import,
+        Err(err) => {
+            eprintln!("{}", err);
+            return None;
+        }
+    };
+    for (entity, count) in import.get_skipped() {
+        eprintln!("{}: {} {} skipped", input, count, entity);
+    }
+    import.execute(&mut data_model);
+    Some((data_model, import.get_node_count()))
 }
 
 async fn read_stdin(tx: futures_channel::mpsc::UnboundedSender<Message>) {
@@ -55,6 +285,10 @@ async fn read_stdin(tx: futures_channel::mpsc::UnboundedSender<Message>) {
         if trimmed.is_empty() {
             continue;
         }
+        if let Err(err) = CommandLine::default().check_syntax(trimmed) {
+            eprintln!("{}", err.show(trimmed));
+            continue;
+        }
         tx.unbounded_send(Message::text(trimmed.to_string()))
             .unwrap();
     }
@@ -1,12 +1,13 @@
 //
 
-use datamodel::{Arc, DataModel};
+use datamodel::{Arc, DataModel, NodeId};
 
 use crate::command::Command;
+use crate::CommandRecord;
 
 #[derive(Debug)]
 pub struct ArcCommand {
-    id: String,
+    id: NodeId,
     x: f64,
     y: f64,
     r: f64,
@@ -24,14 +25,26 @@ impl Command for ArcCommand {
             self.angle_end,
         );
         dm.insert_node(Box::new(arc));
+        dm.add_node_to_current_page(&self.id);
     }
 
     fn undo(&self, dm: &mut DataModel) {
         dm.remove_node(&self.id);
     }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::Arc {
+            id: self.id.clone(),
+            x: self.x,
+            y: self.y,
+            r: self.r,
+            angle_start: self.angle_start,
+            angle_end: self.angle_end,
+        }
+    }
 }
 impl ArcCommand {
-    pub fn new(id: String, x: f64, y: f64, r: f64, angle_start: f64, angle_end: f64) -> Self {
+    pub fn new(id: NodeId, x: f64, y: f64, r: f64, angle_start: f64, angle_end: f64) -> Self {
         ArcCommand {
             id,
             x,
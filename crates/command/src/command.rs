@@ -1,6 +1,32 @@
 use datamodel::DataModel;
 
+use crate::{CommandRecord, Inquiry, OutputFile, Zoom};
+
 pub trait Command: std::fmt::Debug {
     fn execute(&self, data_model: &mut DataModel);
     fn undo(&self, _data_model: &mut DataModel) {}
+    // for the journal, called after execute
+    fn record(&self) -> CommandRecord;
+    // inquiry commands only report, the data model is not changed
+    fn get_inquiry(&self) -> Option<&Inquiry> {
+        None
+    }
+    // zoom and pan change the viewport of the client
+    fn get_zoom(&self) -> Option<&Zoom> {
+        None
+    }
+    // what went wrong in the last execute, e.g. a file that could not be
+    // written, is_undoable still decides if it is undone and journaled,
+    // e.g. for the commands a script ran before the error
+    fn get_error(&self) -> Option<String> {
+        None
+    }
+    // the files of the last execute, e.g. an export, for the output sink
+    fn take_output(&self) -> Vec<OutputFile> {
+        vec![]
+    }
+    // inquiries and zooms are neither undone nor journaled
+    fn is_undoable(&self) -> bool {
+        self.get_inquiry().is_none() && self.get_zoom().is_none()
+    }
 }
@@ -3,29 +3,84 @@
 use datamodel::DataModel;
 
 use crate::command::Command;
+use crate::{FileSink, Journal, JournalEntry, OutputSink};
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct CommandHandler {
     undo_stack: Vec<Box<dyn Command>>,
+    journal: Option<Journal>,
+    // gets the files of the commands, e.g. exports
+    sink: Box<dyn OutputSink>,
 }
+
+impl Default for CommandHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CommandHandler {
     pub fn new() -> Self {
         CommandHandler {
             undo_stack: Vec::new(),
+            journal: None,
+            sink: Box::new(FileSink),
         }
     }
 
-    pub fn execute(&mut self, dm: &mut DataModel, cmd: Box<dyn Command>) {
-        cmd.execute(dm);
+    // files are written to disk unless another sink is set
+    pub fn set_sink(&mut self, sink: Box<dyn OutputSink>) {
+        self.sink = sink;
+    }
+
+    // every executed command and undo is appended to the journal
+    pub fn set_journal(&mut self, journal: Journal) {
+        self.journal = Some(journal);
+    }
+
+    pub fn take_journal(&mut self) -> Option<Journal> {
+        self.journal.take()
+    }
+
+    // a command executed by a replay
+    pub(crate) fn push_executed(&mut self, cmd: Box<dyn Command>) {
         self.undo_stack.push(cmd);
     }
 
-    pub fn undo(&mut self, dm: &mut DataModel) {
+    // observers of the data model get all changes of a command at once
+    pub fn execute(&mut self, dm: &mut DataModel, cmd: Box<dyn Command>) -> Result<(), String> {
+        dm.begin_batch();
+        cmd.execute(dm);
+        dm.end_batch();
+        // a command with an error may have changed the model before, e.g. a
+        // script, what it did is kept and can be undone
+        let error = cmd.get_error();
+        for file in cmd.take_output() {
+            self.sink.write(file)?;
+        }
+        if cmd.is_undoable() {
+            // the command stays executed if the journal can not be written
+            let journaled = self.append_journal(JournalEntry::Execute(cmd.record()));
+            self.undo_stack.push(cmd);
+            journaled?;
+        }
+        match error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    // Err if the journal could not be written, the command is undone anyway
+    pub fn undo(&mut self, dm: &mut DataModel) -> Result<(), String> {
         if let Some(cmd) = self.undo_stack.pop() {
+            dm.begin_batch();
             cmd.undo(dm);
+            dm.end_batch();
+            self.append_journal(JournalEntry::Undo)?;
         } else {
             println!("No commands to undo");
         }
+        Ok(())
     }
 
     pub fn list_commands(&self) {
@@ -33,4 +88,13 @@ impl CommandHandler {
             println!("{:?}", cmd);
         }
     }
+
+    fn append_journal(&mut self, entry: JournalEntry) -> Result<(), String> {
+        match &mut self.journal {
+            Some(journal) => journal
+                .append(&entry)
+                .map_err(|err| format!("Can not write the journal: {}", err)),
+            None => Ok(()),
+        }
+    }
 }
@@ -1,54 +1,894 @@
-use datamodel::DataModel;
+use algebra::Point2d;
+use datamodel::{DataModel, Node, NodeId};
 
-use crate::{command::Command, ArcCommand, ExportCommand, LineCommand, PageCommand};
+use crate::coordinate::PointResolver;
+use crate::parser::{tokenize, ArgParser, ParseError, Token, TokenKind};
+use crate::registry::{ArgKind, ArgSpec, CommandInfo, CommandRegistry};
+use crate::{
+    command::Command, ArcCommand, CurrentPageCommand, DrawingAidsCommand, EraseCommand,
+    ExportCommand, ExporterRegistry, InteractiveArc, InteractiveCommand, InteractiveInquiry,
+    InteractiveLine, InteractiveMove, InteractivePan, InteractiveZoom, LineCommand, MoveCommand,
+    MovePageCommand, PageCommand,
+};
 
-pub struct CommandLine {}
+// one command line session, keeps the last point for relative input
+#[derive(Debug, Default, Clone)]
+pub struct CommandLine {
+    last_point: Option<Point2d>,
+    dynamic_input: bool,
+    registry: CommandRegistry,
+}
 
 impl CommandLine {
-    pub fn parse(data_model: &mut DataModel, line: &str) -> Result<Box<dyn Command>, String> {
-        let mut parts = line.split_whitespace();
-        let command_name = parts.next().unwrap_or("").to_string();
-
-        match command_name.to_lowercase().as_str() {
-            "line" => {
-                let x1: f64 = parts.next().unwrap_or("0").parse().unwrap_or(0.0);
-                let y1: f64 = parts.next().unwrap_or("0").parse().unwrap_or(0.0);
-                let x2: f64 = parts.next().unwrap_or("0").parse().unwrap_or(0.0);
-                let y2: f64 = parts.next().unwrap_or("0").parse().unwrap_or(0.0);
-
-                let cmd = LineCommand::new(data_model.next_id(), x1, y1, x2, y2);
-                return Ok(Box::new(cmd));
-            }
-            "arc" => {
-                let x: f64 = parts.next().unwrap_or("0").parse().unwrap_or(0.0);
-                let y: f64 = parts.next().unwrap_or("0").parse().unwrap_or(0.0);
-                let r: f64 = parts.next().unwrap_or("0").parse().unwrap_or(0.0);
-                let angle_start: f64 = parts.next().unwrap_or("0").parse().unwrap_or(0.0);
-                let angle_end: f64 = parts.next().unwrap_or("360").parse().unwrap_or(0.0);
-
-                let cmd = ArcCommand::new(data_model.next_id(), x, y, r, angle_start, angle_end);
-                return Ok(Box::new(cmd));
-            }
-            "page" => {
-                let name = parts.next().unwrap_or("new page").to_string();
-                let description = parts.next().unwrap_or("page description").to_string();
-                let cmd = PageCommand::new(data_model.next_id(), name, description);
-                return Ok(Box::new(cmd));
-            }
-            "export" => {
-                let filename = parts.next().unwrap_or("datamodel.json").to_string();
-                return Ok(Box::new(ExportCommand::new(filename)));
+    pub fn get_last_point(&self) -> Option<Point2d> {
+        self.last_point
+    }
+
+    // e.g. a point picked on the canvas
+    pub fn set_last_point(&mut self, pt: Point2d) {
+        self.last_point = Some(pt);
+    }
+
+    // in dynamic input mode x,y after the first point of a command is
+    // relative, #x,y is absolute
+    pub fn set_dynamic_input(&mut self, on: bool) {
+        self.dynamic_input = on;
+    }
+
+    pub fn is_dynamic_input(&self) -> bool {
+        self.dynamic_input
+    }
+
+    pub fn get_registry(&self) -> &CommandRegistry {
+        &self.registry
+    }
+
+    pub fn get_registry_mut(&mut self) -> &mut CommandRegistry {
+        &mut self.registry
+    }
+
+    pub fn parse(
+        &mut self,
+        data_model: &mut DataModel,
+        line: &str,
+    ) -> Result<Box<dyn Command>, ParseError> {
+        let resolver = PointResolver {
+            last_point: self.last_point,
+            dynamic_input: self.dynamic_input,
+            points_in_command: 0,
+            ucs: *data_model.get_ucs(),
+        };
+        let mut args = ArgParser::new(tokenize(line)?, line.len())
+            .with_resolver(resolver)
+            .with_command_line(self);
+        let info = self.lookup_command(args.next_token())?;
+        // ids of a command that fails to parse are given out again
+        let id_counter = data_model.get_id_counter();
+        let cmd = match (info.parse)(&mut args, data_model) {
+            Ok(cmd) => cmd,
+            Err(err) => {
+                data_model.release_ids(id_counter);
+                return Err(err);
             }
-            _ => Err("Unknown command".to_string()),
+        };
+        // only successful commands move the last point
+        if let Some(pt) = args.get_last_point() {
+            self.last_point = Some(pt);
+        }
+        Ok(cmd)
+    }
+
+    // only the syntax of the line is checked against the arguments of the
+    // command, ids, pages and files are not looked up
+    pub fn check_syntax(&self, line: &str) -> Result<(), ParseError> {
+        let tokens = tokenize(line)?;
+        let info = self.lookup_command(tokens.first().cloned())?;
+        info.check_args(&tokens[1..], line.len())
+    }
+
+    fn lookup_command(&self, token: Option<Token>) -> Result<&CommandInfo, ParseError> {
+        let Some(token) = token else {
+            return Err(ParseError::new("Missing command", 0));
+        };
+        let TokenKind::Word(name) = &token.kind else {
+            return Err(ParseError::new(
+                format!("Expected a command, found '{}'", token.text),
+                token.position,
+            ));
+        };
+        self.registry
+            .lookup(name)
+            .ok_or_else(|| ParseError::new(format!("Unknown command '{}'", name), token.position))
+    }
+}
+
+pub(crate) fn builtin_commands() -> Vec<CommandInfo> {
+    vec![
+        CommandInfo {
+            name: "line",
+            aliases: &["L"],
+            args: vec![
+                ArgSpec::required("start point", ArgKind::Point),
+                ArgSpec::required("end point", ArgKind::Point),
+            ],
+            help: "Draws a line. Without arguments it asks for points until Enter.",
+            parse: parse_line,
+            interactive: Some(|| Box::new(InteractiveLine::default())),
+        },
+        CommandInfo {
+            name: "arc",
+            aliases: &["A"],
+            args: vec![
+                ArgSpec::required("center", ArgKind::Point),
+                ArgSpec::required("radius", ArgKind::Distance),
+                ArgSpec::optional("start angle", ArgKind::Angle),
+                ArgSpec::optional("end angle", ArgKind::Angle),
+            ],
+            help: "Draws a counter-clockwise arc, angles in degree.",
+            parse: parse_arc,
+            interactive: Some(|| Box::new(InteractiveArc::default())),
+        },
+        CommandInfo {
+            name: "circle",
+            aliases: &["C"],
+            args: vec![
+                ArgSpec::required("center", ArgKind::Point),
+                ArgSpec::required("radius", ArgKind::Distance),
+            ],
+            help: "Draws a circle.",
+            parse: parse_circle,
+            interactive: Some(|| Box::new(InteractiveArc::circle())),
+        },
+        CommandInfo {
+            name: "erase",
+            aliases: &["E"],
+            args: vec![ArgSpec::repeated("node ids", ArgKind::Text)],
+            help: "Erases the given nodes or the selection of the current page.",
+            parse: parse_erase,
+            interactive: None,
+        },
+        CommandInfo {
+            name: "move",
+            aliases: &["M"],
+            args: vec![
+                ArgSpec::required("base point", ArgKind::Point),
+                ArgSpec::required("second point", ArgKind::Point),
+            ],
+            help: "Moves the selection of the current page.",
+            parse: parse_move,
+            interactive: Some(|| -> Box<dyn InteractiveCommand> {
+                Box::new(InteractiveMove::default())
+            }),
+        },
+        CommandInfo {
+            name: "page",
+            aliases: &[],
+            args: vec![
+                ArgSpec::optional("page name", ArgKind::Text),
+                ArgSpec::optional("page description", ArgKind::Text),
+            ],
+            help: "Creates a new page and makes it the current page.",
+            parse: parse_page,
+            interactive: None,
+        },
+        CommandInfo {
+            name: "switchpage",
+            aliases: &["SP"],
+            args: vec![ArgSpec::required("page id or name", ArgKind::Text)],
+            help: "Makes another page the current page, its last view is restored.",
+            parse: parse_switchpage,
+            interactive: None,
+        },
+        CommandInfo {
+            name: "movepage",
+            aliases: &[],
+            args: vec![
+                ArgSpec::required("page id or name", ArgKind::Text),
+                ArgSpec::required("position", ArgKind::Integer),
+            ],
+            help: "Moves a page to another position in the page order, 1 is the first page.",
+            parse: parse_movepage,
+            interactive: None,
+        },
+        CommandInfo {
+            name: "export",
+            aliases: &[],
+            args: vec![
+                ArgSpec::optional("filename", ArgKind::Text),
+                ArgSpec::optional("format=json|prettyjson|binary|svg|dxf|pdf|png", ArgKind::Text),
+                ArgSpec::optional("pages=current|all|<page>", ArgKind::Text),
+                ArgSpec::optional("selection=yes|no", ArgKind::Text),
+                ArgSpec::optional("version=r12|r2000", ArgKind::Text),
+                ArgSpec::optional("paper=a4 orientation=landscape scale=fit|1:50", ArgKind::Text),
+                ArgSpec::optional("margin=10 lineweight=0.25 lineweights=<layer>:<mm>,..", ArgKind::Text),
+                ArgSpec::optional("width=800 height=600 dpi=96", ArgKind::Text),
+                ArgSpec::optional("compress=yes|no", ArgKind::Text),
+                ArgSpec::optional("indent=2|tab canonical=yes|no", ArgKind::Text),
+            ],
+            help: "Exports the data model as json or binary or pages as svg, dxf, pdf or png, the format comes from the extension or format=.",
+            parse: parse_export,
+            interactive: None,
+        },
+        CommandInfo {
+            name: "import",
+            aliases: &["IMP"],
+            args: vec![ArgSpec::required("filename", ArgKind::Text)],
+            help: "Adds the nodes of a dxf or svg file or of another document to the current page.",
+            parse: crate::importcommand::parse_import,
+            interactive: None,
+        },
+        CommandInfo {
+            name: "script",
+            aliases: &["SCR"],
+            args: vec![
+                ArgSpec::required("script file", ArgKind::Text),
+                ArgSpec::optional("stop|continue", ArgKind::Keyword),
+            ],
+            help: "Runs the command lines of a script file, stops at the first error by default.",
+            parse: crate::script::parse_script,
+            interactive: None,
+        },
+        CommandInfo {
+            name: "grid",
+            aliases: &[],
+            args: vec![ArgSpec::optional(
+                "on|off|spacing [major]|origin point",
+                ArgKind::Keyword,
+            )],
+            help: "Switches the grid, sets its spacing, major line count or origin.",
+            parse: parse_grid,
+            interactive: None,
+        },
+        CommandInfo {
+            name: "snap",
+            aliases: &[],
+            args: vec![ArgSpec::optional("on|off|spacing", ArgKind::Keyword)],
+            help: "Switches snapping to the grid or sets the spacing.",
+            parse: parse_snap,
+            interactive: None,
+        },
+        CommandInfo {
+            name: "ortho",
+            aliases: &[],
+            args: vec![ArgSpec::optional("on|off", ArgKind::Keyword)],
+            help: "Restricts points to horizontal and vertical directions.",
+            parse: parse_ortho,
+            interactive: None,
+        },
+        CommandInfo {
+            name: "polar",
+            aliases: &[],
+            args: vec![ArgSpec::optional("on|off|angle", ArgKind::Keyword)],
+            help: "Restricts points to multiples of the polar angle.",
+            parse: parse_polar,
+            interactive: None,
+        },
+        CommandInfo {
+            name: "osnap",
+            aliases: &[],
+            args: vec![ArgSpec::optional("on|off", ArgKind::Keyword)],
+            help: "Snaps to end, mid and center points of nodes.",
+            parse: parse_osnap,
+            interactive: None,
+        },
+        CommandInfo {
+            name: "zoom",
+            aliases: &["Z"],
+            args: vec![ArgSpec::optional(
+                "extents|window p1 p2|previous|scale factor|center point [height]",
+                ArgKind::Keyword,
+            )],
+            help: "Zooms to all nodes, a window, a scale or a center point, or back to the previous view.",
+            parse: crate::zoom::parse_zoom,
+            interactive: Some(|| Box::new(InteractiveZoom::default())),
+        },
+        CommandInfo {
+            name: "pan",
+            aliases: &["P"],
+            args: vec![
+                ArgSpec::required("displacement|base point", ArgKind::Point),
+                ArgSpec::optional("second point", ArgKind::Point),
+            ],
+            help: "Moves the view of the drawing.",
+            parse: crate::zoom::parse_pan,
+            interactive: Some(|| Box::new(InteractivePan::default())),
+        },
+        CommandInfo {
+            name: "ucs",
+            aliases: &[],
+            args: vec![ArgSpec::optional(
+                "world|origin|z|axis",
+                ArgKind::Keyword,
+            )],
+            help: "Sets the user coordinate system in which points are entered.",
+            parse: crate::ucscommand::parse_ucs,
+            interactive: None,
+        },
+        CommandInfo {
+            name: "view",
+            aliases: &["V"],
+            args: vec![
+                ArgSpec::required("save|restore|delete", ArgKind::Keyword),
+                ArgSpec::required("view name", ArgKind::Text),
+            ],
+            help: "Saves, restores or deletes a named view of the current page.",
+            parse: crate::namedviewcommand::parse_view,
+            interactive: None,
+        },
+        CommandInfo {
+            name: "list",
+            aliases: &["LI", "LS"],
+            args: vec![ArgSpec::repeated("node ids", ArgKind::Text)],
+            help: "Lists the properties of the given nodes or the selection.",
+            parse: crate::inquiry::parse_list,
+            interactive: None,
+        },
+        CommandInfo {
+            name: "id",
+            aliases: &[],
+            args: vec![ArgSpec::required("point", ArgKind::Point)],
+            help: "Shows the coordinates of a point.",
+            parse: crate::inquiry::parse_id,
+            interactive: Some(|| Box::new(InteractiveInquiry::id())),
+        },
+        CommandInfo {
+            name: "dist",
+            aliases: &["DI"],
+            args: vec![
+                ArgSpec::required("first point", ArgKind::Point),
+                ArgSpec::required("second point", ArgKind::Point),
+            ],
+            help: "Shows distance, angle and delta between two points.",
+            parse: crate::inquiry::parse_dist,
+            interactive: Some(|| Box::new(InteractiveInquiry::dist())),
+        },
+        CommandInfo {
+            name: "area",
+            aliases: &["AA"],
+            args: vec![ArgSpec {
+                repeated: true,
+                ..ArgSpec::required("points|object [node ids]", ArgKind::Point)
+            }],
+            help: "Shows area and perimeter of a polygon or of closed nodes.",
+            parse: crate::inquiry::parse_area,
+            interactive: Some(|| Box::new(InteractiveInquiry::area())),
+        },
+        CommandInfo {
+            name: "measuregeom",
+            aliases: &["MEA"],
+            args: vec![ArgSpec::required("distance|radius|area", ArgKind::Keyword)],
+            help: "Measures distance, radius or area.",
+            parse: crate::inquiry::parse_measuregeom,
+            interactive: None,
+        },
+    ]
+}
+
+fn parse_line(args: &mut ArgParser, dm: &mut DataModel) -> Result<Box<dyn Command>, ParseError> {
+    let aids = *dm.get_drawing_aids();
+    let p1 = aids.apply(None, &args.point("start point")?);
+    args.set_last_point(p1);
+    let p2 = aids.apply(Some(&p1), &args.point("end point")?);
+    args.set_last_point(p2);
+    args.finish()?;
+
+    Ok(Box::new(LineCommand::new(
+        dm.next_id(),
+        p1.x,
+        p1.y,
+        p2.x,
+        p2.y,
+    )))
+}
+
+fn parse_arc(args: &mut ArgParser, dm: &mut DataModel) -> Result<Box<dyn Command>, ParseError> {
+    let center = dm.get_drawing_aids().apply(None, &args.point("center")?);
+    args.set_last_point(center);
+    let r = args.length("radius")?;
+    let angle_start = args.optional_angle("start angle", 0.0)?;
+    let angle_end = args.optional_angle("end angle", 360.0)?;
+    args.finish()?;
+
+    Ok(Box::new(ArcCommand::new(
+        dm.next_id(),
+        center.x,
+        center.y,
+        r,
+        angle_start,
+        angle_end,
+    )))
+}
+
+// a circle is an arc from 0 to 360 degree
+fn parse_circle(args: &mut ArgParser, dm: &mut DataModel) -> Result<Box<dyn Command>, ParseError> {
+    let center = dm.get_drawing_aids().apply(None, &args.point("center")?);
+    args.set_last_point(center);
+    let r = args.length("radius")?;
+    args.finish()?;
+
+    Ok(Box::new(ArcCommand::new(
+        dm.next_id(),
+        center.x,
+        center.y,
+        r,
+        0.0,
+        360.0,
+    )))
+}
+
+fn parse_erase(args: &mut ArgParser, dm: &mut DataModel) -> Result<Box<dyn Command>, ParseError> {
+    let mut node_ids = vec![];
+    while !args.is_empty() {
+        let position = args.position();
+        let id = args.string("node id")?;
+        if dm.get_node(&id).is_none() {
+            return Err(ParseError::new(format!("Unknown node '{}'", id), position));
         }
-        // match command_name.as_str() {
-        //     "create" => excecute_create(&mut data_model, &mut command_handler, &mut parts),
-        //     "list" => execute_list(&data_model, &command_handler, &mut parts),
-        //     "save" => save_data(&data_model),
-        //     "undo" => command_handler.undo(&mut data_model),
-        //     _ => {
-        //         eprintln!("Error: Unknown command: {}", command_name);
-        //     }
-        // }
+        node_ids.push(id.into());
+    }
+    if node_ids.is_empty() {
+        node_ids = selected_ids(args, dm)?;
+    }
+    Ok(Box::new(EraseCommand::new(dm, &node_ids)))
+}
+
+fn parse_move(args: &mut ArgParser, dm: &mut DataModel) -> Result<Box<dyn Command>, ParseError> {
+    let node_ids = selected_ids(args, dm)?;
+    let aids = *dm.get_drawing_aids();
+    let base = aids.apply(None, &args.point("base point")?);
+    args.set_last_point(base);
+    let second = aids.apply(Some(&base), &args.point("second point")?);
+    args.set_last_point(second);
+    args.finish()?;
+    Ok(Box::new(MoveCommand::new(node_ids, second - base)))
+}
+
+pub(crate) fn selected_ids(args: &ArgParser, dm: &DataModel) -> Result<Vec<NodeId>, ParseError> {
+    let selected = dm
+        .get_current_page()
+        .map(|page| page.get_selected_ids().clone())
+        .unwrap_or_default();
+    if selected.is_empty() {
+        return Err(ParseError::new("Nothing selected", args.position()));
+    }
+    Ok(selected)
+}
+
+fn parse_page(args: &mut ArgParser, dm: &mut DataModel) -> Result<Box<dyn Command>, ParseError> {
+    let name = args.optional_string("page name", "new page")?;
+    let description = args.optional_string("page description", "page description")?;
+    args.finish()?;
+    Ok(Box::new(PageCommand::new(dm.next_id(), name, description)))
+}
+
+// the page id or name
+fn parse_switchpage(
+    args: &mut ArgParser,
+    dm: &mut DataModel,
+) -> Result<Box<dyn Command>, ParseError> {
+    let page_id = page_arg(args, dm)?;
+    args.finish()?;
+    Ok(Box::new(CurrentPageCommand::new(dm, page_id)))
+}
+
+// the page id or name and the position counted from 1
+fn parse_movepage(
+    args: &mut ArgParser,
+    dm: &mut DataModel,
+) -> Result<Box<dyn Command>, ParseError> {
+    let page_id = page_arg(args, dm)?;
+    let position = args.position();
+    let number = args.integer("position")? as usize;
+    args.finish()?;
+    let count = dm.get_pages().len();
+    if number == 0 || number > count {
+        let message = format!("Invalid position {}, use 1 to {}", number, count);
+        return Err(ParseError::new(message, position));
+    }
+    Ok(Box::new(MovePageCommand::new(dm, page_id, number - 1)))
+}
+
+fn page_arg(args: &mut ArgParser, dm: &DataModel) -> Result<NodeId, ParseError> {
+    let position = args.position();
+    let name = args.string("page")?;
+    match dm.get_page(&name) {
+        Some(page) => Ok(page.get_id().clone()),
+        None => dm
+            .get_pages()
+            .iter()
+            .find(|page| page.get_name() == name)
+            .map(|page| page.get_id().clone())
+            .ok_or_else(|| ParseError::new(format!("Unknown page '{}'", name), position)),
+    }
+}
+
+// the format is chosen before the other options are set
+fn parse_export(args: &mut ArgParser, _dm: &mut DataModel) -> Result<Box<dyn Command>, ParseError> {
+    let position = args.position();
+    let filename = args.optional_string("filename", "datamodel.json")?;
+    let options = args.options()?;
+    let format = options.iter().find(|(key, ..)| key == "format");
+    let mut cmd = ExportCommand::from_registry(
+        &ExporterRegistry::default(),
+        filename,
+        format.map(|(_, value, _)| value.as_str()),
+    )
+    .map_err(|message| ParseError::new(message, format.map_or(position, |(.., at)| *at)))?;
+    for (key, value, position) in options.iter().filter(|(key, ..)| key != "format") {
+        cmd.set_option(key, value)
+            .map_err(|message| ParseError::new(message, *position))?;
+    }
+    Ok(Box::new(cmd))
+}
+
+fn parse_grid(args: &mut ArgParser, dm: &mut DataModel) -> Result<Box<dyn Command>, ParseError> {
+    let old = *dm.get_drawing_aids();
+    let mut new = old;
+    if args.peek_keyword("origin") {
+        args.next_token();
+        new.grid.origin = args.point("grid origin")?;
+    } else if args.peek_number() {
+        new.grid.spacing = args.length("grid spacing")?;
+        if !args.is_empty() {
+            new.grid.major_every = args.integer("major line count")?;
+        }
+        new.grid_on = true;
+    } else {
+        new.grid_on = args.switch(old.grid_on)?;
+    }
+    args.finish()?;
+    Ok(Box::new(DrawingAidsCommand::new(old, new)))
+}
+
+fn parse_snap(args: &mut ArgParser, dm: &mut DataModel) -> Result<Box<dyn Command>, ParseError> {
+    let old = *dm.get_drawing_aids();
+    let mut new = old;
+    if args.peek_number() {
+        new.grid.spacing = args.length("snap spacing")?;
+        new.snap_on = true;
+    } else {
+        new.snap_on = args.switch(old.snap_on)?;
+    }
+    args.finish()?;
+    Ok(Box::new(DrawingAidsCommand::new(old, new)))
+}
+
+fn parse_ortho(args: &mut ArgParser, dm: &mut DataModel) -> Result<Box<dyn Command>, ParseError> {
+    let old = *dm.get_drawing_aids();
+    let mut new = old;
+    new.ortho_on = args.switch(old.ortho_on)?;
+    args.finish()?;
+    // ortho and polar tracking exclude each other
+    if new.ortho_on {
+        new.polar_on = false;
+    }
+    Ok(Box::new(DrawingAidsCommand::new(old, new)))
+}
+
+fn parse_polar(args: &mut ArgParser, dm: &mut DataModel) -> Result<Box<dyn Command>, ParseError> {
+    let old = *dm.get_drawing_aids();
+    let mut new = old;
+    if args.peek_number() {
+        new.polar_angle = args.angle("polar angle")?;
+        new.polar_on = true;
+    } else {
+        new.polar_on = args.switch(old.polar_on)?;
+    }
+    args.finish()?;
+    if new.polar_on {
+        new.ortho_on = false;
+    }
+    Ok(Box::new(DrawingAidsCommand::new(old, new)))
+}
+
+fn parse_osnap(args: &mut ArgParser, dm: &mut DataModel) -> Result<Box<dyn Command>, ParseError> {
+    let old = *dm.get_drawing_aids();
+    let mut new = old;
+    new.osnap_on = args.switch(old.osnap_on)?;
+    args.finish()?;
+    Ok(Box::new(DrawingAidsCommand::new(old, new)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datamodel::Line;
+
+    #[test]
+    fn ortho_is_applied_to_line() {
+        let mut dm = DataModel::default();
+        let mut cl = CommandLine::default();
+        let cmd = cl.parse(&mut dm, "page p1").unwrap();
+        cmd.execute(&mut dm);
+        let cmd = cl.parse(&mut dm, "ortho on").unwrap();
+        cmd.execute(&mut dm);
+
+        let cmd = cl.parse(&mut dm, "line 0 0 100 20").unwrap();
+        cmd.execute(&mut dm);
+
+        let id = dm.get_current_page().unwrap().get_node_ids()[0].clone();
+        let line = dm
+            .get_node(&id)
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Line>()
+            .unwrap();
+        assert_eq!((line.x2, line.y2), (100.0, 0.0));
+    }
+
+    #[test]
+    fn grid_spacing_and_undo() {
+        let mut dm = DataModel::default();
+        let mut cl = CommandLine::default();
+        let cmd = cl.parse(&mut dm, "grid 25 4").unwrap();
+        cmd.execute(&mut dm);
+        let aids = dm.get_drawing_aids();
+        assert!(aids.grid_on);
+        assert_eq!(aids.grid.spacing, 25.0);
+        assert_eq!(aids.grid.major_every, 4);
+
+        cmd.undo(&mut dm);
+        assert!(!dm.get_drawing_aids().grid_on);
+        assert_eq!(dm.get_drawing_aids().grid.spacing, 10.0);
+    }
+
+    #[test]
+    fn polar_switches_ortho_off() {
+        let mut dm = DataModel::default();
+        let mut cl = CommandLine::default();
+        cl.parse(&mut dm, "ortho").unwrap().execute(&mut dm);
+        assert!(dm.get_drawing_aids().ortho_on);
+
+        cl.parse(&mut dm, "polar 30").unwrap().execute(&mut dm);
+        let aids = dm.get_drawing_aids();
+        assert!(aids.polar_on);
+        assert!(!aids.ortho_on);
+        assert_eq!(aids.polar_angle, 30.0);
+
+        assert!(cl.parse(&mut dm, "snap maybe").is_err());
+    }
+
+    #[test]
+    fn errors_instead_of_zeros() {
+        let mut dm = DataModel::default();
+        let mut cl = CommandLine::default();
+        let err = cl.parse(&mut dm, "line 10 abc").unwrap_err();
+        assert_eq!(err.position, 8);
+        let err = cl.parse(&mut dm, "line 0,0").unwrap_err();
+        assert_eq!(err, ParseError::new("Missing end point", 8));
+        let err = cl.parse(&mut dm, "line 0,0 1,1 2,2").unwrap_err();
+        assert_eq!(err.position, 13);
+        let err = cl.parse(&mut dm, "lnie 0,0").unwrap_err();
+        assert_eq!(err.message, "Unknown command 'lnie'");
+        let err = cl.parse(&mut dm, "  lnie 0,0").unwrap_err();
+        assert_eq!(err.position, 2);
+        let err = cl.parse(&mut dm, "circle 0,0 2F").unwrap_err();
+        assert_eq!(err, ParseError::new("Unknown unit 'F'", 12));
+
+        // failed commands do not use up ids
+        assert_eq!(dm.next_id(), "1");
+
+        // a name may start with a digit
+        cl.parse(&mut dm, "page 2F").unwrap().execute(&mut dm);
+        assert_eq!(dm.get_current_page().unwrap().get_name(), "2F");
+    }
+
+    #[test]
+    fn syntax_without_the_model() {
+        let cl = CommandLine::default();
+        // ids, pages and files that are not there are not an error
+        for line in [
+            "erase 7 8",
+            "move 0,0 10,0",
+            "switchpage p9",
+            "movepage p9 2",
+            "view restore v1",
+            "area object 3",
+            "area 0,0 10,0 10,10",
+            "import missing.json",
+            "script missing.txt continue",
+            "export out.svg format=svg pages=all",
+            "zoom window 0,0 10,10",
+            "grid 25 4",
+        ] {
+            assert_eq!(cl.check_syntax(line), Ok(()), "{}", line);
+        }
+
+        let err = cl.check_syntax("line 0,0").unwrap_err();
+        assert_eq!(err, ParseError::new("Missing end point", 8));
+        let err = cl.check_syntax("line 0,0 abc").unwrap_err();
+        assert_eq!(err.position, 9);
+        let err = cl.check_syntax("movepage p1 -1").unwrap_err();
+        assert_eq!(err.position, 12);
+        let err = cl.check_syntax("circle 0,0 5 10,10").unwrap_err();
+        assert_eq!(err.message, "Unexpected argument '10,10'");
+        let err = cl.check_syntax("lnie 0,0").unwrap_err();
+        assert_eq!(err.message, "Unknown command 'lnie'");
+    }
+
+    // e.g. a command that creates its nodes before all arguments are read
+    fn parse_twolines(
+        args: &mut ArgParser,
+        dm: &mut DataModel,
+    ) -> Result<Box<dyn Command>, ParseError> {
+        let (first, second) = (dm.next_id(), dm.next_id());
+        let p1 = args.point("start point")?;
+        let p2 = args.point("end point")?;
+        args.finish()?;
+        Ok(Box::new(crate::GroupCommand::new(vec![
+            Box::new(LineCommand::new(first, p1.x, p1.y, p2.x, p2.y)),
+            Box::new(LineCommand::new(second, p1.x, p2.y, p2.x, p1.y)),
+        ])))
+    }
+
+    #[test]
+    fn ids_of_failed_commands() {
+        let mut dm = DataModel::default();
+        dm.set_client_id(Some(datamodel::ClientId::new("abc").unwrap()));
+        let mut cl = CommandLine::default();
+        cl.get_registry_mut().register(CommandInfo {
+            name: "twolines",
+            aliases: &[],
+            args: vec![],
+            help: "Draws a cross.",
+            parse: parse_twolines,
+            interactive: None,
+        });
+        cl.parse(&mut dm, "page p1").unwrap().execute(&mut dm);
+        assert!(cl.parse(&mut dm, "twolines 0,0").is_err());
+        assert!(cl.parse(&mut dm, "twolines 0,0 abc").is_err());
+        cl.parse(&mut dm, "twolines 0,0 10,10")
+            .unwrap()
+            .execute(&mut dm);
+        assert_eq!(
+            dm.get_current_page().unwrap().get_node_ids(),
+            &["abc-2", "abc-3"]
+        );
+    }
+
+    #[test]
+    fn quoted_page_name_and_units() {
+        let mut dm = DataModel::default();
+        let mut cl = CommandLine::default();
+        cl.parse(&mut dm, r#"page "ground floor" 'main page'"#)
+            .unwrap()
+            .execute(&mut dm);
+        assert_eq!(dm.get_current_page().unwrap().get_name(), "ground floor");
+
+        cl.parse(&mut dm, "line 0,0 1cm,2.5cm")
+            .unwrap()
+            .execute(&mut dm);
+        let id = dm.get_current_page().unwrap().get_node_ids()[0].clone();
+        let line = dm
+            .get_node(&id)
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Line>()
+            .unwrap();
+        assert_eq!((line.x2, line.y2), (10.0, 25.0));
+    }
+
+    fn last_line(dm: &DataModel) -> (f64, f64, f64, f64) {
+        let ids = dm.get_current_page().unwrap().get_node_ids();
+        let line = dm
+            .get_node(ids.last().unwrap())
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Line>()
+            .unwrap();
+        (
+            algebra::round(line.x1),
+            algebra::round(line.y1),
+            algebra::round(line.x2),
+            algebra::round(line.y2),
+        )
+    }
+
+    #[test]
+    fn relative_and_polar_points() {
+        let mut dm = DataModel::default();
+        let mut cl = CommandLine::default();
+        cl.parse(&mut dm, "page p1").unwrap().execute(&mut dm);
+
+        cl.parse(&mut dm, "line 10,10 @20,0")
+            .unwrap()
+            .execute(&mut dm);
+        assert_eq!(last_line(&dm), (10.0, 10.0, 30.0, 10.0));
+
+        // the last point is kept between commands
+        cl.parse(&mut dm, "line @ @10<90").unwrap().execute(&mut dm);
+        assert_eq!(last_line(&dm), (30.0, 10.0, 30.0, 20.0));
+
+        cl.parse(&mut dm, "line @ <180 5").unwrap().execute(&mut dm);
+        assert_eq!(last_line(&dm), (30.0, 20.0, 25.0, 20.0));
+
+        // a failing command does not move the last point
+        assert!(cl.parse(&mut dm, "line @1,1 abc").is_err());
+        assert_eq!(cl.get_last_point(), Some(Point2d::new(25.0, 20.0)));
+    }
+
+    #[test]
+    fn dynamic_input() {
+        let mut dm = DataModel::default();
+        let mut cl = CommandLine::default();
+        cl.set_dynamic_input(true);
+        cl.parse(&mut dm, "page p1").unwrap().execute(&mut dm);
+
+        cl.parse(&mut dm, "line 10,10 5,0")
+            .unwrap()
+            .execute(&mut dm);
+        assert_eq!(last_line(&dm), (10.0, 10.0, 15.0, 10.0));
+
+        cl.parse(&mut dm, "line 0,0 #5,5").unwrap().execute(&mut dm);
+        assert_eq!(last_line(&dm), (0.0, 0.0, 5.0, 5.0));
+    }
+
+    #[test]
+    fn aliases_erase_and_move() {
+        let mut dm = DataModel::default();
+        let mut cl = CommandLine::default();
+        cl.parse(&mut dm, "page p1").unwrap().execute(&mut dm);
+        cl.parse(&mut dm, "c 5,5 2").unwrap().execute(&mut dm);
+        cl.parse(&mut dm, "L 0,0 10,0").unwrap().execute(&mut dm);
+        assert_eq!(dm.get_current_page().unwrap().get_node_ids().len(), 2);
+
+        let err = cl.parse(&mut dm, "m 0,0 1,1").unwrap_err();
+        assert_eq!(err, ParseError::new("Nothing selected", 2));
+
+        dm.set_selection(vec!["3".into()]);
+        let cmd = cl.parse(&mut dm, "move 0,0 @0,5").unwrap();
+        cmd.execute(&mut dm);
+        assert_eq!(last_line(&dm), (0.0, 5.0, 10.0, 5.0));
+        cmd.undo(&mut dm);
+        assert_eq!(last_line(&dm), (0.0, 0.0, 10.0, 0.0));
+
+        let err = cl.parse(&mut dm, "erase 3 99").unwrap_err();
+        assert_eq!(err, ParseError::new("Unknown node '99'", 8));
+        cl.parse(&mut dm, "E").unwrap().execute(&mut dm);
+        assert_eq!(dm.get_current_page().unwrap().get_node_ids(), &vec!["2"]);
+    }
+
+    #[test]
+    fn switch_page() {
+        let mut dm = DataModel::default();
+        let mut cl = CommandLine::default();
+        cl.parse(&mut dm, "page first").unwrap().execute(&mut dm);
+        cl.parse(&mut dm, "page second").unwrap().execute(&mut dm);
+        assert_eq!(dm.get_current_page_id(), "2");
+
+        let cmd = cl.parse(&mut dm, "switchpage first").unwrap();
+        cmd.execute(&mut dm);
+        assert_eq!(dm.get_current_page_id(), "1");
+        cmd.undo(&mut dm);
+        assert_eq!(dm.get_current_page_id(), "2");
+        cl.parse(&mut dm, "sp 1").unwrap().execute(&mut dm);
+        assert_eq!(dm.get_current_page_id(), "1");
+
+        let err = cl.parse(&mut dm, "switchpage third").unwrap_err();
+        assert_eq!(err, ParseError::new("Unknown page 'third'", 11));
+    }
+
+    #[test]
+    fn move_page() {
+        let mut dm = DataModel::default();
+        let mut cl = CommandLine::default();
+        for line in ["page first", "page second", "page third"] {
+            cl.parse(&mut dm, line).unwrap().execute(&mut dm);
+        }
+        let names = |dm: &DataModel| -> Vec<String> {
+            let pages = dm.get_pages();
+            pages
+                .iter()
+                .map(|page| page.get_name().to_string())
+                .collect()
+        };
+        let cmd = cl.parse(&mut dm, "movepage third 1").unwrap();
+        cmd.execute(&mut dm);
+        assert_eq!(names(&dm), ["third", "first", "second"]);
+        cmd.undo(&mut dm);
+        assert_eq!(names(&dm), ["first", "second", "third"]);
+
+        let err = cl.parse(&mut dm, "movepage 1 4").unwrap_err();
+        assert_eq!(err, ParseError::new("Invalid position 4, use 1 to 3", 11));
     }
 }
@@ -0,0 +1,446 @@
+//
+// interactive commands, the command asks for one value after the other
+//
+//   Command: line
+//   Specify first point: 0,0
+//   Specify next point or [Undo]: @10,0
+//   Specify next point or [Undo]: <click>
+//   Specify next point or [Close/Undo]: c
+//
+// the values are typed or clicked on the canvas, the command is given
+// to the command handler only when it is finished
+//
+
+use algebra::Point2d;
+use datamodel::DataModel;
+use serde::Serialize;
+
+use crate::command::Command;
+use crate::coordinate::PointResolver;
+use crate::parser::{tokenize, ArgParser, ParseError, Token, TokenKind};
+use crate::CommandLine;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum PromptKind {
+    Point,
+    Distance,
+    Angle,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Prompt {
+    pub message: String,
+    pub kind: PromptKind,
+    pub keywords: Vec<String>,
+    // shown in <>, used on Enter
+    pub default: Option<String>,
+    // reference for ortho, dynamic input, clicked distances and angles
+    // and the rubber band of the client
+    #[serde(skip)]
+    pub base: Option<Point2d>,
+}
+
+impl Prompt {
+    pub fn new(message: &str, kind: PromptKind) -> Self {
+        Prompt {
+            message: message.to_string(),
+            kind,
+            keywords: vec![],
+            default: None,
+            base: None,
+        }
+    }
+
+    pub fn keywords(mut self, keywords: &[&str]) -> Self {
+        self.keywords = keywords.iter().map(|k| k.to_string()).collect();
+        self
+    }
+
+    pub fn default_value(mut self, default: &str) -> Self {
+        self.default = Some(default.to_string());
+        self
+    }
+
+    pub fn base(mut self, base: Option<Point2d>) -> Self {
+        self.base = base;
+        self
+    }
+
+    // Specify next point or [Close/Undo]:
+    pub fn text(&self) -> String {
+        let mut text = self.message.clone();
+        if !self.keywords.is_empty() {
+            text.push_str(&format!(" or [{}]", self.keywords.join("/")));
+        }
+        if let Some(default) = &self.default {
+            text.push_str(&format!(" <{}>", default));
+        }
+        text.push(':');
+        text
+    }
+
+    // full keyword or an abbreviation of it, case insensitive
+    fn match_keyword(&self, word: &str) -> Option<&str> {
+        let word = word.to_lowercase();
+        self.keywords
+            .iter()
+            .find(|keyword| keyword.to_lowercase().starts_with(&word))
+            .map(|keyword| keyword.as_str())
+    }
+}
+
+// raw input of the user
+#[derive(Debug, Clone, PartialEq)]
+pub enum Input {
+    Text(String),
+    // clicked on the canvas, already snapped
+    Point(Point2d),
+    Enter,
+    Escape,
+}
+
+// input converted for the current prompt
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Point(Point2d),
+    // distance or angle in degree
+    Number(f64),
+    Keyword(String),
+    Enter,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Step {
+    Continue,
+    Finish,
+}
+
+pub trait InteractiveCommand: std::fmt::Debug {
+    fn prompt(&self) -> Prompt;
+    // Err with a message if the value is not accepted, the prompt is repeated
+    fn input(&mut self, value: Value) -> Result<Step, String>;
+    // the command for the command handler, None if nothing was entered
+    fn finish(&self, data_model: &mut DataModel) -> Option<Box<dyn Command>>;
+}
+
+#[derive(Debug, Default)]
+pub struct CommandSession {
+    command_line: CommandLine,
+    active: Option<Box<dyn InteractiveCommand>>,
+}
+
+impl CommandSession {
+    pub fn new(command_line: CommandLine) -> Self {
+        CommandSession {
+            command_line,
+            active: None,
+        }
+    }
+
+    pub fn get_command_line(&self) -> &CommandLine {
+        &self.command_line
+    }
+
+    pub fn get_command_line_mut(&mut self) -> &mut CommandLine {
+        &mut self.command_line
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.is_some()
+    }
+
+    pub fn get_prompt(&self) -> Option<Prompt> {
+        self.active.as_ref().map(|cmd| cmd.prompt())
+    }
+
+    // Ok(Some(cmd)) if a command is complete and has to be executed
+    pub fn input(
+        &mut self,
+        data_model: &mut DataModel,
+        input: Input,
+    ) -> Result<Option<Box<dyn Command>>, ParseError> {
+        let Some(active) = self.active.as_mut() else {
+            return match input {
+                Input::Text(line) => self.start(data_model, line.trim()),
+                _ => Ok(None),
+            };
+        };
+
+        let prompt = active.prompt();
+        let value = match input {
+            Input::Escape => {
+                self.active = None;
+                return Ok(None);
+            }
+            Input::Enter => Value::Enter,
+            Input::Text(text) if text.trim().is_empty() => Value::Enter,
+            Input::Text(text) => self.parse_value(data_model, &prompt, &text)?,
+            Input::Point(pt) => clicked_value(&prompt, pt),
+        };
+        if let Value::Point(pt) = value {
+            self.command_line.set_last_point(pt);
+        }
+
+        let Some(active) = self.active.as_mut() else {
+            return Ok(None);
+        };
+        let step = active
+            .input(value)
+            .map_err(|message| ParseError::new(message, 0))?;
+        match step {
+            Step::Continue => {
+                // e.g. after Undo the last point goes back
+                if let Some(base) = active.prompt().base {
+                    self.command_line.set_last_point(base);
+                }
+                Ok(None)
+            }
+            Step::Finish => {
+                let cmd = self.active.take().and_then(|cmd| cmd.finish(data_model));
+                Ok(cmd)
+            }
+        }
+    }
+
+    // a command name alone starts the interactive command, otherwise
+    // the whole line is parsed
+    fn start(
+        &mut self,
+        data_model: &mut DataModel,
+        line: &str,
+    ) -> Result<Option<Box<dyn Command>>, ParseError> {
+        if line.is_empty() {
+            return Ok(None);
+        }
+        let tokens = tokenize(line)?;
+        if let [Token {
+            kind: TokenKind::Word(name),
+            ..
+        }] = tokens.as_slice()
+        {
+            // commands that ask for their arguments if they are started without any
+            let interactive = self
+                .command_line
+                .get_registry()
+                .lookup(name)
+                .and_then(|info| info.interactive);
+            if let Some(create) = interactive {
+                self.active = Some(create());
+                return Ok(None);
+            }
+        }
+        self.command_line.parse(data_model, line).map(Some)
+    }
+
+    fn parse_value(
+        &self,
+        data_model: &DataModel,
+        prompt: &Prompt,
+        text: &str,
+    ) -> Result<Value, ParseError> {
+        let tokens = tokenize(text)?;
+        if let Some(Token {
+            kind: TokenKind::Word(word),
+            position,
+            ..
+        }) = tokens.first()
+        {
+            if let Some(keyword) = prompt.match_keyword(word) {
+                if tokens.len() > 1 {
+                    return Err(ParseError::new(
+                        format!("Unexpected argument '{}'", tokens[1].text),
+                        tokens[1].position,
+                    ));
+                }
+                return Ok(Value::Keyword(keyword.to_string()));
+            }
+            if !prompt.keywords.is_empty() {
+                return Err(ParseError::new(
+                    format!(
+                        "Expected {} or {}, found '{}'",
+                        kind_name(prompt.kind),
+                        prompt.keywords.join("/"),
+                        word
+                    ),
+                    *position,
+                ));
+            }
+        }
+
+        let resolver = PointResolver {
+            last_point: self.command_line.get_last_point(),
+            dynamic_input: self.command_line.is_dynamic_input(),
+            // the first point of a command is absolute in dynamic input mode
+            points_in_command: usize::from(prompt.base.is_some()),
+            ucs: *data_model.get_ucs(),
+        };
+        let mut args = ArgParser::new(tokens, text.len()).with_resolver(resolver);
+        let what = kind_name(prompt.kind);
+        let value = match prompt.kind {
+            PromptKind::Point => {
+                let pt = args.point(what)?;
+                let pt = data_model
+                    .get_drawing_aids()
+                    .apply(prompt.base.as_ref(), &pt);
+                Value::Point(pt)
+            }
+            PromptKind::Distance => Value::Number(args.length(what)?),
+            PromptKind::Angle => Value::Number(args.angle(what)?),
+        };
+        args.finish()?;
+        Ok(value)
+    }
+}
+
+// a click answers a distance or angle prompt relative to the base point
+fn clicked_value(prompt: &Prompt, pt: Point2d) -> Value {
+    let base = prompt.base.unwrap_or(Point2d::new(0.0, 0.0));
+    match prompt.kind {
+        PromptKind::Point => Value::Point(pt),
+        PromptKind::Distance => Value::Number(base.distance(&pt)),
+        PromptKind::Angle => Value::Number((pt.y - base.y).atan2(pt.x - base.x).to_degrees()),
+    }
+}
+
+fn kind_name(kind: PromptKind) -> &'static str {
+    match kind {
+        PromptKind::Point => "point",
+        PromptKind::Distance => "distance",
+        PromptKind::Angle => "angle",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datamodel::Line;
+
+    fn run(session: &mut CommandSession, dm: &mut DataModel, input: Input) {
+        if let Some(cmd) = session.input(dm, input).unwrap() {
+            cmd.execute(dm);
+        }
+    }
+
+    fn text(t: &str) -> Input {
+        Input::Text(t.to_string())
+    }
+
+    fn lines(dm: &DataModel) -> Vec<(f64, f64, f64, f64)> {
+        dm.get_current_page()
+            .unwrap()
+            .get_node_ids()
+            .iter()
+            .map(|id| {
+                let line = dm
+                    .get_node(id)
+                    .unwrap()
+                    .as_any()
+                    .downcast_ref::<Line>()
+                    .unwrap();
+                (line.x1, line.y1, line.x2, line.y2)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn line_with_close() {
+        let mut dm = DataModel::default();
+        let mut session = CommandSession::default();
+        run(&mut session, &mut dm, text("page p1"));
+
+        run(&mut session, &mut dm, text("line"));
+        assert_eq!(session.get_prompt().unwrap().text(), "Specify first point:");
+        run(&mut session, &mut dm, text("0,0"));
+        run(&mut session, &mut dm, Input::Point(Point2d::new(10.0, 0.0)));
+        run(&mut session, &mut dm, text("@0,10"));
+        assert_eq!(
+            session.get_prompt().unwrap().text(),
+            "Specify next point or [Close/Undo]:"
+        );
+        // nothing is committed before the command is finished
+        assert!(lines(&dm).is_empty());
+
+        run(&mut session, &mut dm, text("c"));
+        assert!(!session.is_active());
+        assert_eq!(
+            lines(&dm),
+            vec![
+                (0.0, 0.0, 10.0, 0.0),
+                (10.0, 0.0, 10.0, 10.0),
+                (10.0, 10.0, 0.0, 0.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn undo_and_invalid_input() {
+        let mut dm = DataModel::default();
+        let mut session = CommandSession::default();
+        run(&mut session, &mut dm, text("page p1"));
+
+        run(&mut session, &mut dm, text("LINE"));
+        run(&mut session, &mut dm, text("0,0"));
+        run(&mut session, &mut dm, text("5,5"));
+        run(&mut session, &mut dm, text("undo"));
+        // after undo @ refers to the first point again
+        run(&mut session, &mut dm, text("@20,0"));
+
+        let err = session.input(&mut dm, text("xyz")).unwrap_err();
+        assert_eq!(err.message, "Expected point or Undo, found 'xyz'");
+        assert!(session.is_active());
+
+        run(&mut session, &mut dm, Input::Enter);
+        assert_eq!(lines(&dm), vec![(0.0, 0.0, 20.0, 0.0)]);
+    }
+
+    #[test]
+    fn escape_discards() {
+        let mut dm = DataModel::default();
+        let mut session = CommandSession::default();
+        run(&mut session, &mut dm, text("page p1"));
+
+        run(&mut session, &mut dm, text("line"));
+        run(&mut session, &mut dm, text("0,0"));
+        run(&mut session, &mut dm, text("5,5"));
+        run(&mut session, &mut dm, Input::Escape);
+        assert!(!session.is_active());
+        assert!(lines(&dm).is_empty());
+
+        // complete command lines are still parsed at once
+        run(&mut session, &mut dm, text("line 0,0 1,1"));
+        assert_eq!(lines(&dm).len(), 1);
+    }
+
+    #[test]
+    fn arc_with_clicks_and_defaults() {
+        let mut dm = DataModel::default();
+        let mut session = CommandSession::default();
+        run(&mut session, &mut dm, text("page p1"));
+
+        run(&mut session, &mut dm, text("arc"));
+        run(&mut session, &mut dm, text("10,10"));
+        // the radius is the distance from the center to the click
+        run(
+            &mut session,
+            &mut dm,
+            Input::Point(Point2d::new(10.0, 15.0)),
+        );
+        assert_eq!(
+            session.get_prompt().unwrap().text(),
+            "Specify start angle <0>:"
+        );
+        run(&mut session, &mut dm, Input::Enter);
+        let cmd = session.input(&mut dm, text("90")).unwrap().unwrap();
+        cmd.execute(&mut dm);
+
+        let id = &dm.get_current_page().unwrap().get_node_ids()[0];
+        let arc = dm
+            .get_node(id)
+            .unwrap()
+            .as_any()
+            .downcast_ref::<datamodel::Arc>()
+            .unwrap();
+        assert_eq!(arc.get_r(), 5.0);
+        assert_eq!((arc.get_angle_start(), arc.get_angle_end()), (0.0, 90.0));
+    }
+}
@@ -0,0 +1,247 @@
+//
+// AutoCAD point input
+//
+//   x,y          absolute (relative to the last point in dynamic input mode)
+//   #x,y         always absolute
+//   @dx,dy       relative to the last point
+//   @            the last point
+//   dist<angle   polar from the origin
+//   @dist<angle  polar from the last point
+//   <angle dist  angle override, the distance follows as next argument
+//
+// coordinates and angles are given in the ucs, the result is in world
+// coordinates
+//
+
+use algebra::{Point2d, Ucs};
+
+use crate::parser::{parse_number, Number, ParseError};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CoordinateMode {
+    // absolute, or relative in dynamic input mode
+    Default,
+    Relative,
+    Absolute,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PointInput {
+    Cartesian {
+        x: Number,
+        y: Number,
+        mode: CoordinateMode,
+    },
+    Polar {
+        distance: Number,
+        angle: Number,
+        mode: CoordinateMode,
+    },
+    AngleOverride(Number),
+}
+
+// Ok(None) if the text is no point input
+pub fn parse_point_input(text: &str, position: usize) -> Result<Option<PointInput>, ParseError> {
+    let (mode, rest, offset) = if let Some(rest) = text.strip_prefix('@') {
+        (CoordinateMode::Relative, rest, 1)
+    } else if let Some(rest) = text.strip_prefix('#') {
+        (CoordinateMode::Absolute, rest, 1)
+    } else {
+        (CoordinateMode::Default, text, 0)
+    };
+    let position = position + offset;
+
+    if mode == CoordinateMode::Relative && rest.is_empty() {
+        return Ok(Some(PointInput::Cartesian {
+            x: zero(),
+            y: zero(),
+            mode,
+        }));
+    }
+
+    if let Some(angle) = rest.strip_prefix('<') {
+        if mode != CoordinateMode::Default {
+            return Err(ParseError::new(
+                "An angle override can not be relative or absolute",
+                position,
+            ));
+        }
+        let angle = number(angle, position + 1, "angle")?;
+        return Ok(Some(PointInput::AngleOverride(angle)));
+    }
+
+    if let Some((distance, angle)) = rest.split_once('<') {
+        let angle_position = position + distance.len() + 1;
+        return Ok(Some(PointInput::Polar {
+            distance: number(distance, position, "distance")?,
+            angle: number(angle, angle_position, "angle")?,
+            mode,
+        }));
+    }
+
+    if let Some((x, y)) = rest.split_once(',') {
+        let y_position = position + x.len() + 1;
+        return Ok(Some(PointInput::Cartesian {
+            x: number(x, position, "coordinate")?,
+            y: number(y, y_position, "coordinate")?,
+            mode,
+        }));
+    }
+
+    if mode != CoordinateMode::Default {
+        return Err(ParseError::new(
+            format!("Invalid point '{}'", text),
+            position - offset,
+        ));
+    }
+    Ok(None)
+}
+
+fn number(text: &str, position: usize, what: &str) -> Result<Number, ParseError> {
+    parse_number(text, position)?
+        .ok_or_else(|| ParseError::new(format!("Invalid {} '{}'", what, text), position))
+}
+
+fn zero() -> Number {
+    Number {
+        value: 0.0,
+        unit: None,
+    }
+}
+
+// resolves point input against the last point of the session
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PointResolver {
+    pub last_point: Option<Point2d>,
+    pub dynamic_input: bool,
+    // number of points of the current command, in dynamic input mode
+    // only the first point is absolute
+    pub points_in_command: usize,
+    pub ucs: Ucs,
+}
+
+impl PointResolver {
+    pub fn resolve(&self, input: &PointInput, position: usize) -> Result<Point2d, ParseError> {
+        match input {
+            PointInput::Cartesian { x, y, mode } => {
+                let pt = Point2d::new(x.length(position)?, y.length(position)?);
+                Ok(self.ucs_to_world(pt, *mode))
+            }
+            PointInput::Polar {
+                distance,
+                angle,
+                mode,
+            } => {
+                let pt = polar(distance.length(position)?, angle.angle(position)?);
+                Ok(self.ucs_to_world(pt, *mode))
+            }
+            PointInput::AngleOverride(_) => Err(ParseError::new(
+                "An angle override needs a distance",
+                position,
+            )),
+        }
+    }
+
+    // <angle followed by a distance
+    pub fn resolve_angle_override(&self, angle: f64, distance: f64) -> Point2d {
+        let last = self.last_point.unwrap_or(Point2d::new(0.0, 0.0));
+        last + self.ucs.vector_to_world(&polar(distance, angle))
+    }
+
+    fn ucs_to_world(&self, pt: Point2d, mode: CoordinateMode) -> Point2d {
+        if self.is_relative(mode) {
+            let last = self.last_point.unwrap_or(Point2d::new(0.0, 0.0));
+            last + self.ucs.vector_to_world(&pt)
+        } else {
+            self.ucs.to_world(&pt)
+        }
+    }
+
+    fn is_relative(&self, mode: CoordinateMode) -> bool {
+        match mode {
+            CoordinateMode::Relative => true,
+            CoordinateMode::Absolute => false,
+            CoordinateMode::Default => self.dynamic_input && self.points_in_command > 0,
+        }
+    }
+}
+
+fn polar(distance: f64, angle: f64) -> Point2d {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    Point2d::new(distance * cos, distance * sin)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(resolver: &PointResolver, text: &str) -> Point2d {
+        let input = parse_point_input(text, 0).unwrap().unwrap();
+        let pt = resolver.resolve(&input, 0).unwrap();
+        Point2d::new(algebra::round(pt.x), algebra::round(pt.y))
+    }
+
+    #[test]
+    fn inputs() {
+        let resolver = PointResolver {
+            last_point: Some(Point2d::new(10.0, 10.0)),
+            ..Default::default()
+        };
+        assert_eq!(resolve(&resolver, "5,6"), Point2d::new(5.0, 6.0));
+        assert_eq!(resolve(&resolver, "#5,6"), Point2d::new(5.0, 6.0));
+        assert_eq!(resolve(&resolver, "@5,-6"), Point2d::new(15.0, 4.0));
+        assert_eq!(resolve(&resolver, "@"), Point2d::new(10.0, 10.0));
+        assert_eq!(resolve(&resolver, "@10<90"), Point2d::new(10.0, 20.0));
+        assert_eq!(resolve(&resolver, "10<180"), Point2d::new(-10.0, 0.0));
+        assert_eq!(resolve(&resolver, "@1cm<0"), Point2d::new(20.0, 10.0));
+    }
+
+    #[test]
+    fn dynamic_input() {
+        let mut resolver = PointResolver {
+            last_point: Some(Point2d::new(10.0, 10.0)),
+            dynamic_input: true,
+            points_in_command: 0,
+            ucs: Ucs::default(),
+        };
+        // the first point of a command is absolute
+        assert_eq!(resolve(&resolver, "5,6"), Point2d::new(5.0, 6.0));
+        resolver.points_in_command = 1;
+        assert_eq!(resolve(&resolver, "5,6"), Point2d::new(15.0, 16.0));
+        assert_eq!(resolve(&resolver, "#5,6"), Point2d::new(5.0, 6.0));
+    }
+
+    #[test]
+    fn ucs() {
+        let resolver = PointResolver {
+            last_point: Some(Point2d::new(10.0, 10.0)),
+            ucs: Ucs::new(Point2d::new(100.0, 0.0), 90.0),
+            ..Default::default()
+        };
+        assert_eq!(resolve(&resolver, "5,0"), Point2d::new(100.0, 5.0));
+        assert_eq!(resolve(&resolver, "@5,0"), Point2d::new(10.0, 15.0));
+        assert_eq!(resolve(&resolver, "@5<90"), Point2d::new(5.0, 10.0));
+        assert_eq!(
+            resolver.resolve_angle_override(0.0, 2.0),
+            Point2d::new(10.0, 12.0)
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(parse_point_input("abc", 0).unwrap(), None);
+        assert_eq!(parse_point_input("10", 0).unwrap(), None);
+        assert_eq!(
+            parse_point_input("@abc", 3).unwrap_err(),
+            ParseError::new("Invalid point '@abc'", 3)
+        );
+        assert_eq!(
+            parse_point_input("@10<x", 0).unwrap_err(),
+            ParseError::new("Invalid angle 'x'", 4)
+        );
+        assert!(matches!(
+            parse_point_input("<45", 0).unwrap(),
+            Some(PointInput::AngleOverride(_))
+        ));
+    }
+}
@@ -0,0 +1,35 @@
+//
+
+use algebra::DrawingAids;
+use datamodel::DataModel;
+
+use crate::command::Command;
+use crate::CommandRecord;
+
+// grid, snap, ortho and polar settings
+#[derive(Debug)]
+pub struct DrawingAidsCommand {
+    old: DrawingAids,
+    new: DrawingAids,
+}
+impl Command for DrawingAidsCommand {
+    fn execute(&self, dm: &mut DataModel) {
+        dm.set_drawing_aids(self.new);
+    }
+
+    fn undo(&self, dm: &mut DataModel) {
+        dm.set_drawing_aids(self.old);
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::DrawingAids {
+            old: self.old,
+            new: self.new,
+        }
+    }
+}
+impl DrawingAidsCommand {
+    pub fn new(old: DrawingAids, new: DrawingAids) -> Self {
+        DrawingAidsCommand { old, new }
+    }
+}
@@ -0,0 +1,109 @@
+//
+
+use datamodel::{DataModel, Node, NodeId};
+
+use crate::command::Command;
+use crate::CommandRecord;
+
+#[derive(Debug)]
+struct ErasedNode {
+    node: Box<dyn Node>,
+    // page id and position in the node list of the page
+    pages: Vec<(NodeId, usize)>,
+}
+
+// keeps copies of the nodes, undo puts them back at their draw order
+#[derive(Debug)]
+pub struct EraseCommand {
+    erased: Vec<ErasedNode>,
+}
+impl Command for EraseCommand {
+    fn execute(&self, dm: &mut DataModel) {
+        for erased in &self.erased {
+            dm.remove_node(erased.node.get_id());
+        }
+    }
+
+    fn undo(&self, dm: &mut DataModel) {
+        let mut positions = vec![];
+        for erased in &self.erased {
+            dm.insert_node(erased.node.clone_node());
+            for (page_id, position) in &erased.pages {
+                positions.push((page_id, *position, erased.node.get_id()));
+            }
+        }
+        // lower positions first, so the later ones are still right
+        positions.sort();
+        for (page_id, position, node_id) in positions {
+            dm.insert_node_to_page(page_id, node_id, position);
+        }
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::Erase {
+            node_ids: self
+                .erased
+                .iter()
+                .map(|erased| erased.node.get_id().clone())
+                .collect(),
+        }
+    }
+}
+impl EraseCommand {
+    // the nodes are copied from the data model now
+    pub fn new(dm: &DataModel, node_ids: &[NodeId]) -> Self {
+        let erased = node_ids
+            .iter()
+            .filter_map(|id| {
+                let node = dm.get_node(id)?.clone_node();
+                let pages = dm
+                    .get_pages()
+                    .iter()
+                    .filter_map(|page| {
+                        let position = page.get_node_ids().iter().position(|n| n == id)?;
+                        Some((page.get_id().clone(), position))
+                    })
+                    .collect();
+                Some(ErasedNode { node, pages })
+            })
+            .collect();
+        EraseCommand { erased }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LineCommand, PageCommand};
+
+    #[test]
+    fn undo_restores_draw_order() {
+        let mut dm = DataModel::default();
+        PageCommand::new("p".into(), "p".into(), "".into()).execute(&mut dm);
+        for id in ["1", "2", "3"] {
+            LineCommand::new(id.into(), 0.0, 0.0, 10.0, 10.0).execute(&mut dm);
+        }
+
+        let cmd = EraseCommand::new(&dm, &["3".into(), "1".into()]);
+        cmd.execute(&mut dm);
+        assert_eq!(dm.get_current_page().unwrap().get_node_ids(), &vec!["2"]);
+        assert!(dm.get_node("1").is_none());
+
+        cmd.undo(&mut dm);
+        assert_eq!(
+            dm.get_current_page().unwrap().get_node_ids(),
+            &vec!["1", "2", "3"]
+        );
+        let page = dm.get_current_page().unwrap();
+        let area = algebra::BBox::new(
+            algebra::Point2d::new(-1.0, -1.0),
+            algebra::Point2d::new(1.0, 1.0),
+        );
+        let ids: Vec<&str> = dm
+            .query_nodes(page, &area)
+            .iter()
+            .map(|n| n.get_id().as_str())
+            .collect();
+        assert_eq!(ids, vec!["1", "2", "3"]);
+    }
+}
@@ -1,29 +1,339 @@
 //
+// export plan.pdf [format=pdf] [pages=current|all|<page>] [selection=yes] [option=value ...]
+//
+// the format comes from the extension of the file name unless it is given,
+// the file goes to the output sink of the command handler, errors of the
+// export are reported by get_error
+//
 
-use std::{fs::File, io::Write};
+use std::cell::RefCell;
 
-use datamodel::DataModel;
+use datamodel::{DataModel, Node, Page};
 
 use crate::command::Command;
+use crate::exporter::{extract, Exporter, ExporterRegistry};
+use crate::{CommandRecord, OutputFile};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PageScope {
+    Current,
+    All,
+    // id or name
+    Page(String),
+}
+
+impl std::str::FromStr for PageScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "current" => Ok(PageScope::Current),
+            "all" => Ok(PageScope::All),
+            "" => Err("Missing pages, use current, all or a page".to_string()),
+            _ => Ok(PageScope::Page(s.to_string())),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct ExportCommand {
     pub filename: String,
+    // the name of the format
+    pub format: String,
+    exporter: Box<dyn Exporter>,
+    pages: PageScope,
+    // only the selected nodes of the pages
+    selection: bool,
+    output: RefCell<Option<OutputFile>>,
+    error: RefCell<Option<String>>,
 }
 
 impl Command for ExportCommand {
     fn execute(&self, data_model: &mut DataModel) {
-        // Implement the export logic here
+        let (output, error) = match self.export(data_model) {
+            Ok(bytes) => {
+                let filename = self.filename.clone();
+                (Some(OutputFile { filename, bytes }), None)
+            }
+            Err(err) => (None, Some(err)),
+        };
+        *self.output.borrow_mut() = output;
+        *self.error.borrow_mut() = error;
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::Export {
+            filename: self.filename.clone(),
+        }
+    }
 
-        let serialized = serde_json::to_string(data_model).unwrap();
+    fn get_error(&self) -> Option<String> {
+        self.error.borrow().clone()
+    }
+
+    fn take_output(&self) -> Vec<OutputFile> {
+        self.output.borrow_mut().take().into_iter().collect()
+    }
 
-        let mut file = File::create(self.filename.as_str()).expect("Unable to create file");
-        file.write_all(serialized.as_bytes())
-            .expect("Unable to write data to file");
+    // a failed export is not journaled
+    fn is_undoable(&self) -> bool {
+        self.error.borrow().is_none()
     }
 }
+
 impl ExportCommand {
-    pub fn new(filename: String) -> Self {
-        ExportCommand { filename }
+    // the format of the extension
+    pub fn new(filename: String) -> Result<Self, String> {
+        Self::from_registry(&ExporterRegistry::default(), filename, None)
+    }
+
+    pub fn from_registry(
+        registry: &ExporterRegistry,
+        filename: String,
+        format: Option<&str>,
+    ) -> Result<Self, String> {
+        let info = match format {
+            Some(format) => registry.get(format).ok_or_else(|| {
+                format!(
+                    "Unknown format '{}', use {}",
+                    format,
+                    registry.names().join(", ")
+                )
+            })?,
+            None => registry.from_filename(&filename).ok_or_else(|| {
+                format!(
+                    "Unknown file format of '{}', use .{} or format=<format>",
+                    filename,
+                    registry.extensions().join(", .")
+                )
+            })?,
+        };
+        Ok(ExportCommand {
+            filename,
+            format: info.name.to_string(),
+            exporter: (info.create)(),
+            pages: match info.all_pages {
+                true => PageScope::All,
+                false => PageScope::Current,
+            },
+            selection: false,
+            output: RefCell::new(None),
+            error: RefCell::new(None),
+        })
+    }
+
+    // an option=value of the command line, most options depend on the format
+    pub fn set_option(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "pages" => self.pages = value.parse()?,
+            "selection" => {
+                self.selection = match value.to_ascii_lowercase().as_str() {
+                    "yes" | "true" => true,
+                    "no" | "false" => false,
+                    _ => return Err(format!("Invalid selection '{}', use yes or no", value)),
+                }
+            }
+            _ => self.exporter.set_option(key, value)?,
+        }
+        Ok(())
+    }
+
+    // the bytes of the file
+    pub fn export(&self, data_model: &DataModel) -> Result<Vec<u8>, String> {
+        let pages = self.pages(data_model)?;
+        if !self.selection {
+            return self.exporter.export(data_model, &pages);
+        }
+        let part = extract(data_model, &pages, true);
+        if part
+            .get_pages()
+            .iter()
+            .all(|page| page.get_node_ids().is_empty())
+        {
+            return Err("Nothing selected to export".to_string());
+        }
+        let pages: Vec<&Page> = pages
+            .iter()
+            .filter_map(|page| part.get_page(page.get_id()))
+            .collect();
+        self.exporter.export(&part, &pages)
+    }
+
+    fn pages<'a>(&self, data_model: &'a DataModel) -> Result<Vec<&'a Page>, String> {
+        match &self.pages {
+            PageScope::Current => Ok(data_model.get_current_page().into_iter().collect()),
+            PageScope::All => Ok(data_model.get_pages()),
+            PageScope::Page(name) => data_model
+                .get_page(name)
+                .or_else(|| {
+                    data_model
+                        .get_pages()
+                        .into_iter()
+                        .find(|page| page.get_name() == name)
+                })
+                .map(|page| vec![page])
+                .ok_or_else(|| format!("Unknown page '{}'", name)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CommandHandler, CommandLine, MemorySink};
+
+    fn sample() -> (DataModel, CommandLine) {
+        let mut dm = DataModel::default();
+        let mut cl = CommandLine::default();
+        for line in [
+            "page p1",
+            "line 0,0 100,50",
+            "line 0,0 10,10",
+            "page p2",
+            "line 0,0 50,50",
+            "switchpage p1",
+        ] {
+            cl.parse(&mut dm, line).unwrap().execute(&mut dm);
+        }
+        (dm, cl)
+    }
+
+    // the file of the export, kept in memory
+    fn export(dm: &mut DataModel, cl: &mut CommandLine, line: &str) -> Result<Vec<u8>, String> {
+        let sink = MemorySink::default();
+        let mut handler = CommandHandler::default();
+        handler.set_sink(Box::new(sink.clone()));
+        let cmd = cl.parse(dm, line).map_err(|err| err.message)?;
+        handler.execute(dm, cmd)?;
+        let mut files = sink.take();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].filename, "out");
+        Ok(files.remove(0).bytes)
+    }
+
+    #[test]
+    fn pdf_options() {
+        let (mut dm, mut cl) = sample();
+        let pdf = export(
+            &mut dm,
+            &mut cl,
+            "export out format=pdf paper=a3 orientation=portrait scale=1:1 margin=5 pages=all",
+        )
+        .unwrap();
+        assert!(pdf.starts_with(b"%PDF-1.4"));
+        // a3 portrait in points, two pages
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.contains("/MediaBox [0 0 841.89 1190.551]"));
+        assert!(text.contains("/Count 2"));
+
+        let mut cmd = ExportCommand::new("a.PDF".to_string()).unwrap();
+        assert_eq!(cmd.format, "pdf");
+        cmd.set_option("lineweights", "Walls:0.5").unwrap();
+        assert_eq!(
+            cmd.set_option("margin", "-1").unwrap_err(),
+            "Invalid margin '-1'"
+        );
+        assert!(cmd.set_option("version", "r12").is_err());
+        assert!(cl.parse(&mut dm, "export a.pdf pages=").is_err());
+    }
+
+    #[test]
+    fn png_options() {
+        let (mut dm, mut cl) = sample();
+        let png = export(
+            &mut dm,
+            &mut cl,
+            "export out format=png width=64 height=32 dpi=300",
+        )
+        .unwrap();
+        let image = render::RasterImage::from_png(&png).unwrap();
+        assert_eq!((image.width, image.height), (64, 32));
+        // black pixels of the line on white
+        assert!(image.pixels.chunks(4).any(|pixel| pixel[0] < 128));
+        assert!(image.pixels.chunks(4).any(|pixel| pixel[0] == 255));
+
+        assert!(cl.parse(&mut dm, "export a.png width=0").is_err());
+        assert!(cl.parse(&mut dm, "export a.png dpi=0").is_err());
+        assert!(cl.parse(&mut dm, "export a.png paper=a4").is_err());
+    }
+
+    #[test]
+    fn formats_and_scope() {
+        let (mut dm, mut cl) = sample();
+
+        // the complete data model
+        let json = export(&mut dm, &mut cl, "export out format=json").unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(value["pages"].as_array().unwrap().len(), 2);
+        // saved now
+        assert_eq!(value["format_version"], datamodel::FORMAT_VERSION);
+        assert_eq!(value["created"], value["modified"]);
+        let read = datamodel::read_document(std::str::from_utf8(&json).unwrap()).unwrap();
+        assert_eq!(read.get_nodes().len(), 3);
+        let view = algebra::View {
+            x: 0.0,
+            y: 0.0,
+            width: 50.0,
+            height: 50.0,
+            rotation: 0.0,
+        };
+        let p2 = dm.get_pages()[1].get_id().to_string();
+        dm.set_named_view(&p2, "all", Some(view));
+        let pretty = export(&mut dm, &mut cl, "export out format=prettyjson pages=p2").unwrap();
+        let pretty = String::from_utf8(pretty).unwrap();
+        assert!(pretty.starts_with("{\n  "));
+        let value: serde_json::Value = serde_json::from_str(&pretty).unwrap();
+        assert_eq!(value["pages"].as_array().unwrap().len(), 1);
+        assert_eq!(value["nodes"].as_array().unwrap().len(), 1);
+        // the named views of the page are kept
+        assert_eq!(value["pages"][0]["named_views"]["all"]["width"], 50.0);
+
+        // the same document in binary
+        let binary = export(&mut dm, &mut cl, "export out format=binary compress=no").unwrap();
+        let read = datamodel::read_binary(&binary).unwrap();
+        assert_eq!(read.get_nodes().len(), 3);
+        assert!(cl.parse(&mut dm, "export a.dmb compress=maybe").is_err());
+
+        let svg = export(&mut dm, &mut cl, "export out format=svg lineweight=2").unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert_eq!(svg.matches("<path ").count(), 2);
+        assert!(svg.contains("stroke-width=\"2\""));
+
+        // the selection of the current page
+        let ids = dm.get_current_page().unwrap().get_node_ids().clone();
+        dm.set_selection(vec![ids[1].clone()]);
+        let dxf = export(&mut dm, &mut cl, "export out format=dxf selection=yes").unwrap();
+        let dxf = String::from_utf8(dxf).unwrap();
+        assert_eq!(dxf.matches("\nLINE\n").count(), 1);
+
+        // errors of the export are returned
+        dm.set_selection(vec![]);
+        assert_eq!(
+            export(&mut dm, &mut cl, "export out format=dxf selection=yes").unwrap_err(),
+            "Nothing selected to export"
+        );
+        assert_eq!(
+            export(&mut dm, &mut cl, "export out format=svg pages=all").unwrap_err(),
+            "Only one page can be exported to svg, use pages=current or pages=<page>"
+        );
+        assert_eq!(
+            export(&mut dm, &mut cl, "export out format=svg pages=p3").unwrap_err(),
+            "Unknown page 'p3'"
+        );
+        // the default sink writes files
+        let cmd = cl.parse(&mut dm, "export /no/such/dir/a.json").unwrap();
+        let err = CommandHandler::default().execute(&mut dm, cmd).unwrap_err();
+        assert!(err.starts_with("Can not write '/no/such/dir/a.json'"));
+
+        assert_eq!(
+            cl.parse(&mut dm, "export plan.txt").unwrap_err().message,
+            "Unknown file format of 'plan.txt', use .dmb, .dxf, .json, .pdf, .png, .svg or format=<format>"
+        );
+        assert_eq!(
+            cl.parse(&mut dm, "export plan.txt format=bmp")
+                .unwrap_err()
+                .message,
+            "Unknown format 'bmp', use binary, dxf, json, pdf, png, prettyjson, svg"
+        );
     }
 }
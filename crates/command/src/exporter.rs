@@ -0,0 +1,530 @@
+//
+// the formats of the export command, found by their name or by the
+// extension of the file name
+//
+//   export plan.dxf version=r12
+//   export plan.txt format=prettyjson indent=tab
+//   export plan.json canonical=yes
+//   export plan.dmb compress=no
+//
+// an application adds its own formats with ExporterRegistry::register
+//
+
+use std::collections::BTreeMap;
+
+use datamodel::{write_binary, write_json, DataModel, Document, JsonStyle, Node, Page, Timestamp};
+use dxf::{write_dxf, DxfVersion};
+use render::{
+    page_viewport, render_png, write_pdf, write_svg, PlotSettings, RasterOptions, MAX_IMAGE_SIZE,
+};
+
+pub trait Exporter: std::fmt::Debug {
+    // an option=value of the command line
+    fn set_option(&mut self, key: &str, _value: &str) -> Result<(), String> {
+        Err(format!("Unknown option '{}'", key))
+    }
+
+    // the pages in order, the data model may have more pages
+    fn export(&self, data_model: &DataModel, pages: &[&Page]) -> Result<Vec<u8>, String>;
+}
+
+pub type CreateExporterFn = fn() -> Box<dyn Exporter>;
+
+#[derive(Clone)]
+pub struct ExporterInfo {
+    pub name: &'static str,
+    // lower case without the dot
+    pub extensions: &'static [&'static str],
+    // for the help, e.g. "version=r12|r2000"
+    pub options: &'static str,
+    // the complete data model if no pages are given, else the current page
+    pub all_pages: bool,
+    pub create: CreateExporterFn,
+}
+
+impl std::fmt::Debug for ExporterInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExporterInfo")
+            .field("name", &self.name)
+            .field("extensions", &self.extensions)
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+pub struct ExporterRegistry {
+    // key is the lower case name
+    formats: BTreeMap<String, ExporterInfo>,
+    // extension -> lower case name
+    extensions: BTreeMap<String, String>,
+}
+
+impl Default for ExporterRegistry {
+    fn default() -> Self {
+        let mut registry = ExporterRegistry::new();
+        for info in builtin_exporters() {
+            registry.register(info);
+        }
+        registry
+    }
+}
+
+impl ExporterRegistry {
+    // an empty registry, default() has the builtin formats
+    pub fn new() -> Self {
+        ExporterRegistry {
+            formats: BTreeMap::new(),
+            extensions: BTreeMap::new(),
+        }
+    }
+
+    // replaces a format with the same name, the extensions are taken over
+    pub fn register(&mut self, info: ExporterInfo) {
+        let name = info.name.to_lowercase();
+        for extension in info.extensions {
+            self.extensions
+                .insert(extension.to_lowercase(), name.clone());
+        }
+        self.formats.insert(name, info);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ExporterInfo> {
+        self.formats.get(&name.to_lowercase())
+    }
+
+    pub fn from_filename(&self, filename: &str) -> Option<&ExporterInfo> {
+        let (_, extension) = filename.rsplit_once('.')?;
+        self.extensions
+            .get(&extension.to_lowercase())
+            .and_then(|name| self.formats.get(name))
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.formats.values().map(|info| info.name).collect()
+    }
+
+    pub fn extensions(&self) -> Vec<&str> {
+        self.extensions
+            .keys()
+            .map(|extension| extension.as_str())
+            .collect()
+    }
+}
+
+pub(crate) fn builtin_exporters() -> Vec<ExporterInfo> {
+    vec![
+        ExporterInfo {
+            name: "json",
+            extensions: &["json"],
+            options: "indent=2|tab canonical=yes|no",
+            all_pages: true,
+            create: || Box::new(JsonExporter::default()),
+        },
+        ExporterInfo {
+            name: "prettyjson",
+            extensions: &[],
+            options: "indent=2|tab",
+            all_pages: true,
+            create: || {
+                Box::new(JsonExporter {
+                    style: JsonStyle::Pretty("  ".to_string()),
+                })
+            },
+        },
+        ExporterInfo {
+            name: "binary",
+            extensions: &["dmb"],
+            options: "compress=yes|no",
+            all_pages: true,
+            create: || Box::new(BinaryExporter { compress: true }),
+        },
+        ExporterInfo {
+            name: "svg",
+            extensions: &["svg"],
+            options: "lineweight=1",
+            all_pages: false,
+            create: || Box::new(SvgExporter { line_width: 1.0 }),
+        },
+        ExporterInfo {
+            name: "dxf",
+            extensions: &["dxf"],
+            options: "version=r12|r2000",
+            all_pages: false,
+            create: || Box::new(DxfExporter::default()),
+        },
+        ExporterInfo {
+            name: "pdf",
+            extensions: &["pdf"],
+            options: "paper=a4 orientation=landscape scale=fit|1:50 margin=10 lineweight=0.25 \
+                      lineweights=<layer>:<mm>,..",
+            all_pages: false,
+            create: || Box::new(PdfExporter::default()),
+        },
+        ExporterInfo {
+            name: "png",
+            extensions: &["png"],
+            options: "width=800 height=600 dpi=96 lineweight=0.25",
+            all_pages: false,
+            create: || Box::new(PngExporter::default()),
+        },
+    ]
+}
+
+// copies of the pages and their nodes, only the selected nodes if
+// selected_only, e.g. to export a part of the data model as json
+pub fn extract(data_model: &DataModel, pages: &[&Page], selected_only: bool) -> DataModel {
+    let mut result = DataModel::default();
+    result.set_info(data_model.get_info().clone());
+    result.set_drawing_aids(*data_model.get_drawing_aids());
+    result.set_ucs(*data_model.get_ucs());
+    for page in pages {
+        let page_id = page.get_id();
+        let copy = Page::new(
+            page_id.to_string(),
+            page.get_name().to_string(),
+            page.get_description().to_string(),
+        );
+        result.insert_page(copy);
+        if let Some(view) = page.get_view() {
+            result.set_page_view(page_id, *view);
+        }
+        for (name, view) in page.get_named_views() {
+            result.set_named_view(page_id, name, Some(*view));
+        }
+        // in draw order
+        for node in page
+            .get_node_ids()
+            .iter()
+            .filter(|id| !selected_only || page.get_selected_ids().contains(id))
+            .filter_map(|id| data_model.get_node(id))
+        {
+            result.insert_node(node.clone_node());
+            result.add_node_to_page(page_id, node.get_id());
+        }
+    }
+    let current = data_model.get_current_page_id();
+    if pages.iter().any(|page| page.get_id() == current) {
+        result.set_current_page(current);
+    }
+    result
+}
+
+// formats with one page per file
+fn single_page<'a>(format: &str, pages: &[&'a Page]) -> Result<&'a Page, String> {
+    match pages {
+        [page] => Ok(page),
+        [] => Err("No page to export".to_string()),
+        _ => Err(format!(
+            "Only one page can be exported to {}, use pages=current or pages=<page>",
+            format
+        )),
+    }
+}
+
+fn length(key: &str, value: &str) -> Result<f64, String> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|length| *length >= 0.0)
+        .ok_or_else(|| format!("Invalid {} '{}'", key, value))
+}
+
+fn positive<T: std::str::FromStr + PartialOrd + Default>(
+    key: &str,
+    value: &str,
+) -> Result<T, String> {
+    value
+        .parse::<T>()
+        .ok()
+        .filter(|number| *number > T::default())
+        .ok_or_else(|| format!("Invalid {} '{}'", key, value))
+}
+
+fn image_size(key: &str, value: &str) -> Result<u32, String> {
+    positive(key, value)
+        .ok()
+        .filter(|size| *size <= MAX_IMAGE_SIZE)
+        .ok_or_else(|| format!("Invalid {} '{}', use 1 to {}", key, value, MAX_IMAGE_SIZE))
+}
+
+fn yes_no(key: &str, value: &str) -> Result<bool, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "yes" | "true" => Ok(true),
+        "no" | "false" => Ok(false),
+        _ => Err(format!("Invalid {} '{}', use yes or no", key, value)),
+    }
+}
+
+#[derive(Debug, Default)]
+struct JsonExporter {
+    style: JsonStyle,
+}
+
+impl Exporter for JsonExporter {
+    fn set_option(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "indent" => {
+                let indent = match value.to_ascii_lowercase().as_str() {
+                    "tab" => "\t".to_string(),
+                    _ => match value.parse::<usize>() {
+                        Ok(n) if n <= 8 => " ".repeat(n),
+                        _ => return Err(format!("Invalid indent '{}', use 0..8 or tab", value)),
+                    },
+                };
+                self.style = JsonStyle::Pretty(indent);
+            }
+            "canonical" => {
+                self.style = match yes_no(key, value)? {
+                    true => JsonStyle::Canonical,
+                    false => JsonStyle::Compact,
+                }
+            }
+            _ => return Err(format!("Unknown option '{}'", key)),
+        }
+        Ok(())
+    }
+
+    // the canonical form keeps the times of the data model, the same
+    // drawing is written to the same bytes, e.g. for version control
+    fn export(&self, data_model: &DataModel, pages: &[&Page]) -> Result<Vec<u8>, String> {
+        let now = Timestamp::now();
+        with_pages(data_model, pages, |data_model| match self.style {
+            JsonStyle::Canonical => {
+                let document = Document::new(data_model, data_model.get_info().clone());
+                write_json(&document, &self.style)
+            }
+            _ => write_json(&data_model.saved(now), &self.style),
+        })
+    }
+}
+
+// the json document in a compact binary form
+#[derive(Debug)]
+struct BinaryExporter {
+    compress: bool,
+}
+
+impl Exporter for BinaryExporter {
+    fn set_option(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "compress" => self.compress = yes_no(key, value)?,
+            _ => return Err(format!("Unknown option '{}'", key)),
+        }
+        Ok(())
+    }
+
+    fn export(&self, data_model: &DataModel, pages: &[&Page]) -> Result<Vec<u8>, String> {
+        let now = Timestamp::now();
+        with_pages(data_model, pages, |data_model| {
+            write_binary(&data_model.saved(now), self.compress)
+        })
+    }
+}
+
+// the data model, or a copy with only the pages, the drawing aids and all
+// views are kept
+fn with_pages<T>(data_model: &DataModel, pages: &[&Page], write: impl Fn(&DataModel) -> T) -> T {
+    if pages.len() == data_model.get_pages().len() {
+        write(data_model)
+    } else {
+        write(&extract(data_model, pages, false))
+    }
+}
+
+#[derive(Debug)]
+struct SvgExporter {
+    // in drawing units
+    line_width: f64,
+}
+
+impl Exporter for SvgExporter {
+    fn set_option(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "lineweight" => self.line_width = length(key, value)?,
+            _ => return Err(format!("Unknown option '{}'", key)),
+        }
+        Ok(())
+    }
+
+    fn export(&self, data_model: &DataModel, pages: &[&Page]) -> Result<Vec<u8>, String> {
+        let page = single_page("svg", pages)?;
+        Ok(write_svg(data_model, page, self.line_width).into_bytes())
+    }
+}
+
+#[derive(Debug, Default)]
+struct DxfExporter {
+    version: DxfVersion,
+}
+
+impl Exporter for DxfExporter {
+    fn set_option(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "version" => self.version = value.parse()?,
+            _ => return Err(format!("Unknown option '{}'", key)),
+        }
+        Ok(())
+    }
+
+    fn export(&self, data_model: &DataModel, pages: &[&Page]) -> Result<Vec<u8>, String> {
+        let page = single_page("dxf", pages)?;
+        let nodes: Vec<&dyn Node> = page
+            .get_node_ids()
+            .iter()
+            .filter_map(|id| data_model.get_node(id))
+            .collect();
+        Ok(write_dxf(&nodes, self.version).into_bytes())
+    }
+}
+
+#[derive(Debug, Default)]
+struct PdfExporter {
+    settings: PlotSettings,
+}
+
+impl Exporter for PdfExporter {
+    fn set_option(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let settings = &mut self.settings;
+        match key {
+            "paper" => settings.paper = value.parse()?,
+            "orientation" => settings.orientation = value.parse()?,
+            "scale" => settings.scale = value.parse()?,
+            "margin" => settings.margin = length(key, value)?,
+            "lineweight" => settings.line_width = length(key, value)?,
+            "lineweights" => settings.parse_layer_widths(value)?,
+            _ => return Err(format!("Unknown option '{}'", key)),
+        }
+        Ok(())
+    }
+
+    // each page on its own sheet
+    fn export(&self, data_model: &DataModel, pages: &[&Page]) -> Result<Vec<u8>, String> {
+        if pages.is_empty() {
+            return Err("No page to export".to_string());
+        }
+        Ok(write_pdf(data_model, pages, &self.settings))
+    }
+}
+
+#[derive(Debug, Default)]
+struct PngExporter {
+    options: RasterOptions,
+}
+
+impl Exporter for PngExporter {
+    fn set_option(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let options = &mut self.options;
+        match key {
+            "width" => options.width = image_size(key, value)?,
+            "height" => options.height = image_size(key, value)?,
+            "dpi" => options.dpi = positive(key, value)?,
+            "lineweight" => options.line_width = length(key, value)?,
+            _ => return Err(format!("Unknown option '{}'", key)),
+        }
+        Ok(())
+    }
+
+    // as in the last view of the page
+    fn export(&self, data_model: &DataModel, pages: &[&Page]) -> Result<Vec<u8>, String> {
+        let page = single_page("png", pages)?;
+        let options = &self.options;
+        let viewport = page_viewport(data_model, page, options.width, options.height);
+        render_png(data_model, page, &viewport, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry() {
+        let mut registry = ExporterRegistry::default();
+        assert_eq!(
+            registry.names(),
+            vec!["binary", "dxf", "json", "pdf", "png", "prettyjson", "svg"]
+        );
+        assert_eq!(
+            registry.from_filename("a/b.c/Plan.DXF").unwrap().name,
+            "dxf"
+        );
+        assert!(registry.from_filename("plan.txt").is_none());
+        assert!(registry.from_filename("plan").is_none());
+        assert_eq!(registry.get("PrettyJson").unwrap().name, "prettyjson");
+
+        // another format for .json
+        registry.register(ExporterInfo {
+            name: "nodes",
+            extensions: &["json", "nodes"],
+            options: "",
+            all_pages: false,
+            create: || Box::new(JsonExporter::default()),
+        });
+        assert_eq!(registry.from_filename("plan.json").unwrap().name, "nodes");
+
+        let mut png = (registry.get("png").unwrap().create)();
+        assert!(png.set_option("width", "0").is_err());
+        assert!(png.set_option("height", "4097").is_err());
+        assert!(png.set_option("dpi", "72.5").is_ok());
+        assert_eq!(
+            png.set_option("paper", "a4").unwrap_err(),
+            "Unknown option 'paper'"
+        );
+    }
+
+    #[test]
+    fn json_options() {
+        let mut dm = DataModel::default();
+        dm.insert_page(Page::new("1", "plan".into(), String::new()));
+        let pages = dm.get_pages();
+
+        let registry = ExporterRegistry::default();
+        let mut json = (registry.get("json").unwrap().create)();
+        json.set_option("canonical", "yes").unwrap();
+        let first = json.export(&dm, &pages).unwrap();
+        // no new modified time, the same data model gives the same file
+        assert_eq!(json.export(&dm, &pages).unwrap(), first);
+        assert!(first.starts_with(b"{\n  \"format_version\": 2,\n"));
+        assert!(first.ends_with(b"\n}\n"));
+
+        let mut pretty = (registry.get("prettyjson").unwrap().create)();
+        pretty.set_option("indent", "tab").unwrap();
+        assert!(pretty
+            .export(&dm, &pages)
+            .unwrap()
+            .starts_with(b"{\n\t\"format_version\""));
+        assert_eq!(
+            pretty.set_option("indent", "-1").unwrap_err(),
+            "Invalid indent '-1', use 0..8 or tab"
+        );
+        assert!(json.set_option("canonical", "maybe").is_err());
+    }
+
+    #[test]
+    fn extract_selection() {
+        let mut dm = DataModel::default();
+        dm.insert_page(Page::new("p1", "first".into(), String::new()));
+        for id in ["1", "2", "3"] {
+            let line = datamodel::Line::new(id.to_string());
+            dm.insert_node(Box::new(line));
+            dm.add_node_to_page("p1", id);
+        }
+        dm.insert_page(Page::new("p2", "second".into(), String::new()));
+        dm.set_current_page("p1");
+        dm.set_selection(vec!["3".into(), "1".into()]);
+
+        let page = dm.get_page("p1").unwrap();
+        let part = extract(&dm, &[page], true);
+        assert_eq!(part.get_pages().len(), 1);
+        assert_eq!(part.get_current_page_id(), "p1");
+        // the draw order and not the order of the selection
+        assert_eq!(part.get_page("p1").unwrap().get_node_ids(), &["1", "3"]);
+        assert!(part.get_node("2").is_none());
+
+        let pages = dm.get_pages();
+        assert_eq!(
+            single_page("dxf", &pages).unwrap_err(),
+            "Only one page can be exported to dxf, use pages=current or pages=<page>"
+        );
+    }
+}
@@ -0,0 +1,37 @@
+//
+
+use datamodel::DataModel;
+
+use crate::command::Command;
+use crate::CommandRecord;
+
+// several commands that are executed and undone as one step,
+// e.g. the segments of an interactive line command
+#[derive(Debug)]
+pub struct GroupCommand {
+    commands: Vec<Box<dyn Command>>,
+}
+impl Command for GroupCommand {
+    fn execute(&self, dm: &mut DataModel) {
+        for cmd in &self.commands {
+            cmd.execute(dm);
+        }
+    }
+
+    fn undo(&self, dm: &mut DataModel) {
+        for cmd in self.commands.iter().rev() {
+            cmd.undo(dm);
+        }
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::Group {
+            commands: self.commands.iter().map(|cmd| cmd.record()).collect(),
+        }
+    }
+}
+impl GroupCommand {
+    pub fn new(commands: Vec<Box<dyn Command>>) -> Self {
+        GroupCommand { commands }
+    }
+}
@@ -0,0 +1,282 @@
+//
+// import drawing.dxf
+// import icon.svg
+// import other.json
+//
+// the nodes of the file are added to the current page, read when the
+// command is parsed so the journal holds the nodes and not the file name.
+// the nodes of another document (.json, .dmb) get new ids, see paste
+//
+
+use std::collections::BTreeMap;
+
+use datamodel::{read_document_bytes, DataModel, Node, NodeId, NodeType};
+use dxf::{decode_dxf, read_dxf};
+use svgimport::read_svg;
+
+use crate::command::Command;
+use crate::parser::{ArgParser, ParseError};
+use crate::CommandRecord;
+
+#[derive(Debug)]
+pub struct ImportCommand {
+    page_id: NodeId,
+    nodes: Vec<Box<dyn Node>>,
+    // dxf entity type or svg element -> number of those that were left out
+    skipped: BTreeMap<String, usize>,
+}
+
+impl Command for ImportCommand {
+    fn execute(&self, dm: &mut DataModel) {
+        for node in &self.nodes {
+            dm.insert_node(node.clone_node());
+            dm.add_node_to_page(&self.page_id, node.get_id());
+        }
+    }
+
+    fn undo(&self, dm: &mut DataModel) {
+        for node in &self.nodes {
+            dm.remove_node(node.get_id());
+        }
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::Import {
+            page_id: self.page_id.clone(),
+            nodes: self
+                .nodes
+                .iter()
+                .map(|node| serde_json::to_value(node.as_ref()).unwrap_or_default())
+                .collect(),
+        }
+    }
+}
+
+impl ImportCommand {
+    pub fn new(page_id: NodeId, nodes: Vec<Box<dyn Node>>) -> Self {
+        ImportCommand {
+            page_id,
+            nodes,
+            skipped: BTreeMap::new(),
+        }
+    }
+
+    // nodes of another document for the current page, they get new ids so
+    // they do not collide with the nodes of the data model. pages are left out
+    pub fn paste(dm: &mut DataModel, mut nodes: Vec<Box<dyn Node>>) -> Result<Self, String> {
+        if dm.get_current_page().is_none() {
+            return Err("No current page".to_string());
+        }
+        nodes.retain(|node| !matches!(node.get_node_type(), NodeType::Page));
+        dm.reidentify(&mut nodes);
+        Ok(ImportCommand::new(dm.get_current_page_id().clone(), nodes))
+    }
+
+    // the nodes of a dxf or svg file or of the first page of a document
+    // for the current page
+    pub fn from_file(dm: &mut DataModel, filename: &str) -> Result<Self, String> {
+        if dm.get_current_page().is_none() {
+            return Err("No current page".to_string());
+        }
+        let lowercase = filename.to_ascii_lowercase();
+        let dxf = lowercase.ends_with(".dxf");
+        let document = lowercase.ends_with(".json") || lowercase.ends_with(".dmb");
+        if !dxf && !document && !lowercase.ends_with(".svg") {
+            return Err(format!(
+                "Unknown file format of '{}', use .dxf, .svg, .json or .dmb",
+                filename
+            ));
+        }
+        let bytes = std::fs::read(filename)
+            .map_err(|err| format!("Can not read '{}': {}", filename, err))?;
+        if document {
+            let other =
+                read_document_bytes(&bytes).map_err(|err| format!("{}: {}", filename, err))?;
+            let nodes = other
+                .get_current_page()
+                .map(|page| page.get_node_ids().as_slice())
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|id| other.get_node(id))
+                .map(|node| node.clone_node())
+                .collect();
+            return Self::paste(dm, nodes);
+        }
+        let (nodes, skipped) = if dxf {
+            let import = read_dxf(&decode_dxf(&bytes), &mut || dm.next_id())
+                .map_err(|err| format!("{}: {}", filename, err))?;
+            (import.nodes, import.skipped)
+        } else {
+            let text = String::from_utf8_lossy(&bytes);
+            let import = read_svg(&text, &mut || dm.next_id())
+                .map_err(|err| format!("{}: {}", filename, err))?;
+            (import.nodes, import.skipped)
+        };
+        Ok(ImportCommand {
+            page_id: dm.get_current_page_id().clone(),
+            nodes,
+            skipped,
+        })
+    }
+
+    pub fn get_node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn get_node_ids(&self) -> Vec<NodeId> {
+        self.nodes
+            .iter()
+            .map(|node| node.get_id().clone())
+            .collect()
+    }
+
+    pub fn get_skipped(&self) -> &BTreeMap<String, usize> {
+        &self.skipped
+    }
+}
+
+pub(crate) fn parse_import(
+    args: &mut ArgParser,
+    dm: &mut DataModel,
+) -> Result<Box<dyn Command>, ParseError> {
+    let position = args.position();
+    let filename = args.string("filename")?;
+    args.finish()?;
+    let cmd = ImportCommand::from_file(dm, &filename)
+        .map_err(|message| ParseError::new(message, position))?;
+    Ok(Box::new(cmd))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{replay, CommandHandler, CommandLine, JournalEntry};
+
+    use datamodel::Line;
+
+    const SAMPLE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../dxf/samples/r12.dxf");
+    const SVG_SAMPLE: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../svgimport/samples/shapes.svg"
+    );
+
+    #[test]
+    fn import_and_replay() {
+        let mut dm = DataModel::default();
+        let mut cl = CommandLine::default();
+        let mut handler = CommandHandler::default();
+        let err = cl
+            .parse(&mut dm, &format!("import {}", SAMPLE))
+            .unwrap_err();
+        assert_eq!(err.message, "No current page");
+
+        let cmd = cl.parse(&mut dm, "page p1").unwrap();
+        let page = serde_json::to_string(&JournalEntry::Execute(cmd.record())).unwrap();
+        handler.execute(&mut dm, cmd).unwrap();
+        let cmd = cl.parse(&mut dm, &format!("import {}", SAMPLE)).unwrap();
+        let import = serde_json::to_string(&JournalEntry::Execute(cmd.record())).unwrap();
+        handler.execute(&mut dm, cmd).unwrap();
+        let page_ids = dm.get_current_page().unwrap().get_node_ids().clone();
+        assert_eq!(page_ids.len(), 7);
+        assert_eq!(
+            dm.get_node("2").unwrap().get_node_type().to_string(),
+            "Line"
+        );
+
+        // the journal holds the nodes, the file is not read again
+        let mut replayed = DataModel::default();
+        let text = format!("{}\n{}\n", page, import);
+        replay(
+            text.as_bytes(),
+            &mut replayed,
+            &mut CommandHandler::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            replayed.get_current_page().unwrap().get_node_ids(),
+            &page_ids
+        );
+
+        // exported as r12 and imported again
+        let filename = std::env::temp_dir().join("import_and_replay.dxf");
+        let filename = filename.to_str().unwrap();
+        let cmd = cl
+            .parse(&mut dm, &format!("export {} version=r12", filename))
+            .unwrap();
+        handler.execute(&mut dm, cmd).unwrap();
+        let cmd = cl.parse(&mut dm, &format!("import {}", filename)).unwrap();
+        handler.execute(&mut dm, cmd).unwrap();
+        assert_eq!(dm.get_current_page().unwrap().get_node_ids().len(), 14);
+        std::fs::remove_file(filename).unwrap();
+        assert!(cl.parse(&mut dm, "export a.dxf version=r14").is_err());
+
+        // import, export, import
+        for _ in 0..3 {
+            handler.undo(&mut dm).unwrap();
+        }
+        assert!(dm.get_current_page().unwrap().get_node_ids().is_empty());
+        let err = cl.parse(&mut dm, "import drawing.png").unwrap_err();
+        assert_eq!(
+            err.message,
+            "Unknown file format of 'drawing.png', use .dxf, .svg, .json or .dmb"
+        );
+
+        // the y axis of the svg is flipped
+        let cmd = cl
+            .parse(&mut dm, &format!("import {}", SVG_SAMPLE))
+            .unwrap();
+        handler.execute(&mut dm, cmd).unwrap();
+        assert_eq!(dm.get_current_page().unwrap().get_node_ids().len(), 11);
+        let line = dm.get_current_page().unwrap().get_node_ids()[0].clone();
+        let line = dm
+            .get_node(&line)
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Line>()
+            .unwrap();
+        assert_eq!((line.y1, line.y2), (100.0, 0.0));
+    }
+
+    #[test]
+    fn import_document() {
+        // two sessions give out the same ids
+        let mut other = DataModel::default();
+        let mut dm = DataModel::default();
+        let mut cl = CommandLine::default();
+        for line in ["page p1", "line 0,0 10,0", "line 0,0 0,10"] {
+            cl.parse(&mut other, line).unwrap().execute(&mut other);
+            cl.parse(&mut dm, line).unwrap().execute(&mut dm);
+        }
+        let filename = std::env::temp_dir().join("import_document.dmb");
+        let bytes =
+            datamodel::write_binary(&other.saved(datamodel::Timestamp::now()), true).unwrap();
+        std::fs::write(&filename, bytes).unwrap();
+
+        dm.set_client_id(Some(datamodel::ClientId::new("abc").unwrap()));
+        let cmd = cl
+            .parse(&mut dm, &format!("import {}", filename.to_str().unwrap()))
+            .unwrap();
+        CommandHandler::default().execute(&mut dm, cmd).unwrap();
+        std::fs::remove_file(&filename).unwrap();
+        assert_eq!(
+            dm.get_current_page().unwrap().get_node_ids(),
+            &["2", "3", "abc-1", "abc-2"]
+        );
+        let line = dm
+            .get_node("abc-2")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Line>();
+        assert_eq!(line.unwrap().get_p2(), algebra::Point2d::new(0.0, 10.0));
+
+        // pasted pages are left out
+        let nodes = vec![
+            other.get_node("2").unwrap().clone_node(),
+            Box::new(other.get_page("1").unwrap().clone()),
+        ];
+        let cmd = ImportCommand::paste(&mut dm, nodes).unwrap();
+        assert_eq!(cmd.get_node_count(), 1);
+        cmd.execute(&mut dm);
+        assert!(dm.get_node("abc-3").is_some());
+    }
+}
@@ -0,0 +1,556 @@
+//
+// inquiry commands, they report on the drawing and do not change it
+//
+//   list [ids]                  properties of the nodes or the selection
+//   id point                    coordinates of a point
+//   dist p1 p2                  distance, angle and delta
+//   area p1 p2 p3 ...           area and perimeter of the closed polygon
+//   area object [ids]           area and perimeter of circles and closed polylines
+//   measuregeom distance|radius|area ...
+//
+
+use std::fmt;
+
+use algebra::{arc_sweep, polygon_area, polygon_perimeter, Point2d};
+use datamodel::{Arc, DataModel, Line, Node, NodeId, Page, Polyline, Text};
+use serde::Serialize;
+
+use crate::command::Command;
+use crate::commandline::selected_ids;
+use crate::parser::{ArgParser, ParseError};
+use crate::CommandRecord;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum PropertyValue {
+    Number(f64),
+    Point(Point2d),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Property {
+    pub name: String,
+    pub value: PropertyValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NodeInfo {
+    pub id: NodeId,
+    // e.g. Circle for an arc of 360 degree
+    pub node_type: String,
+    pub properties: Vec<Property>,
+}
+
+// the result of an inquiry, as text for the command line or as json
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "inquiry", rename_all = "snake_case")]
+pub enum Inquiry {
+    List {
+        nodes: Vec<NodeInfo>,
+    },
+    Id {
+        point: Point2d,
+    },
+    Dist {
+        distance: f64,
+        // degree, counter clockwise from the x axis
+        angle: f64,
+        delta: Point2d,
+    },
+    Radius {
+        radius: f64,
+        diameter: f64,
+    },
+    Area {
+        area: f64,
+        perimeter: f64,
+    },
+}
+
+impl Inquiry {
+    pub fn list(data_model: &DataModel, node_ids: &[NodeId]) -> Self {
+        let nodes = node_ids
+            .iter()
+            .filter_map(|id| data_model.get_node(id))
+            .map(node_info)
+            .collect();
+        Inquiry::List { nodes }
+    }
+
+    pub fn dist(p1: &Point2d, p2: &Point2d) -> Self {
+        let delta = *p2 - *p1;
+        Inquiry::Dist {
+            distance: p1.distance(p2),
+            angle: angle(&delta),
+            delta,
+        }
+    }
+
+    // the polygon is closed, the last point is connected to the first
+    pub fn area(points: &[Point2d]) -> Self {
+        Inquiry::Area {
+            area: polygon_area(points),
+            perimeter: polygon_perimeter(points),
+        }
+    }
+
+    // the sum over all nodes, every node must be closed
+    pub fn node_area(data_model: &DataModel, node_ids: &[NodeId]) -> Result<Self, String> {
+        let mut area = 0.0;
+        let mut perimeter = 0.0;
+        for id in node_ids {
+            let node = data_model.get_node(id);
+            let circle = node
+                .and_then(|node| node.as_any().downcast_ref::<Arc>())
+                .filter(|arc| is_circle(arc));
+            let polygon = node
+                .and_then(|node| node.as_any().downcast_ref::<Polyline>())
+                .filter(|polyline| polyline.closed);
+            if let Some(circle) = circle {
+                let r = circle.get_r();
+                area += std::f64::consts::PI * r * r;
+                perimeter += std::f64::consts::TAU * r;
+            } else if let Some(polygon) = polygon {
+                area += polygon_area(&polygon.points);
+                perimeter += polygon_perimeter(&polygon.points);
+            } else {
+                return Err(format!("Node '{}' is not a closed shape", id));
+            }
+        }
+        Ok(Inquiry::Area { area, perimeter })
+    }
+
+    pub fn radius(data_model: &DataModel, node_id: &str) -> Result<Self, String> {
+        let arc = data_model
+            .get_node(node_id)
+            .and_then(|node| node.as_any().downcast_ref::<Arc>())
+            .ok_or_else(|| format!("Node '{}' is not an arc or circle", node_id))?;
+        Ok(Inquiry::Radius {
+            radius: arc.get_r(),
+            diameter: 2.0 * arc.get_r(),
+        })
+    }
+}
+
+impl fmt::Display for Inquiry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Inquiry::List { nodes } => {
+                let text: Vec<String> = nodes.iter().map(|node| node.to_string()).collect();
+                write!(f, "{}", text.join("\n"))
+            }
+            Inquiry::Id { point } => write!(f, "{}", PropertyValue::Point(*point)),
+            Inquiry::Dist {
+                distance,
+                angle,
+                delta,
+            } => write!(
+                f,
+                "Distance = {},  Angle = {},  Delta X = {},  Delta Y = {}",
+                number(*distance),
+                number(*angle),
+                number(delta.x),
+                number(delta.y)
+            ),
+            Inquiry::Radius { radius, diameter } => write!(
+                f,
+                "Radius = {},  Diameter = {}",
+                number(*radius),
+                number(*diameter)
+            ),
+            Inquiry::Area { area, perimeter } => write!(
+                f,
+                "Area = {},  Perimeter = {}",
+                number(*area),
+                number(*perimeter)
+            ),
+        }
+    }
+}
+
+impl fmt::Display for NodeInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}  id: {}", self.node_type, self.id)?;
+        for property in &self.properties {
+            write!(f, "\n  {}: {}", property.name, property.value)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for PropertyValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PropertyValue::Number(value) => write!(f, "{}", number(*value)),
+            PropertyValue::Point(pt) => write!(f, "X = {}  Y = {}", number(pt.x), number(pt.y)),
+            PropertyValue::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+fn number(value: f64) -> String {
+    // no -0.0000
+    format!("{:.4}", value + 0.0)
+}
+
+fn angle(delta: &Point2d) -> f64 {
+    delta.y.atan2(delta.x).to_degrees().rem_euclid(360.0)
+}
+
+fn is_circle(arc: &Arc) -> bool {
+    arc_sweep(arc.get_angle_start(), arc.get_angle_end()) == 360.0
+}
+
+fn property(name: &str, value: PropertyValue) -> Property {
+    Property {
+        name: name.to_string(),
+        value,
+    }
+}
+
+fn node_info(node: &dyn Node) -> NodeInfo {
+    let mut node_type = node.get_node_type().to_string();
+    let mut properties = vec![];
+    if let Some(line) = node.as_any().downcast_ref::<Line>() {
+        let delta = line.get_p2() - line.get_p1();
+        properties = vec![
+            property("from", PropertyValue::Point(line.get_p1())),
+            property("to", PropertyValue::Point(line.get_p2())),
+            property(
+                "length",
+                PropertyValue::Number(line.get_p1().distance(&line.get_p2())),
+            ),
+            property("angle", PropertyValue::Number(angle(&delta))),
+            property("delta", PropertyValue::Point(delta)),
+        ];
+    } else if let Some(arc) = node.as_any().downcast_ref::<Arc>() {
+        let r = arc.get_r();
+        properties = vec![
+            property("center", PropertyValue::Point(arc.get_center())),
+            property("radius", PropertyValue::Number(r)),
+        ];
+        if is_circle(arc) {
+            node_type = "Circle".to_string();
+            properties.extend([
+                property(
+                    "circumference",
+                    PropertyValue::Number(std::f64::consts::TAU * r),
+                ),
+                property("area", PropertyValue::Number(std::f64::consts::PI * r * r)),
+            ]);
+        } else {
+            let sweep = arc_sweep(arc.get_angle_start(), arc.get_angle_end());
+            properties.extend([
+                property("start angle", PropertyValue::Number(arc.get_angle_start())),
+                property("end angle", PropertyValue::Number(arc.get_angle_end())),
+                property("length", PropertyValue::Number(sweep.to_radians() * r)),
+            ]);
+        }
+    } else if let Some(polyline) = node.as_any().downcast_ref::<Polyline>() {
+        let length: f64 = polyline.segments().map(|(a, b)| a.distance(b)).sum();
+        properties = vec![
+            property(
+                "vertices",
+                PropertyValue::Number(polyline.points.len() as f64),
+            ),
+            property(
+                "closed",
+                PropertyValue::Text(if polyline.closed { "yes" } else { "no" }.to_string()),
+            ),
+            property("length", PropertyValue::Number(length)),
+        ];
+        if polyline.closed {
+            properties.push(property(
+                "area",
+                PropertyValue::Number(polygon_area(&polyline.points)),
+            ));
+        }
+    } else if let Some(text) = node.as_any().downcast_ref::<Text>() {
+        properties = vec![
+            property("text", PropertyValue::Text(text.text.clone())),
+            property("position", PropertyValue::Point(text.get_position())),
+            property("height", PropertyValue::Number(text.height)),
+            property("rotation", PropertyValue::Number(text.rotation)),
+        ];
+    } else if let Some(page) = node.as_any().downcast_ref::<Page>() {
+        properties = vec![
+            property("name", PropertyValue::Text(page.get_name().to_string())),
+            property(
+                "description",
+                PropertyValue::Text(page.get_description().to_string()),
+            ),
+            property(
+                "nodes",
+                PropertyValue::Number(page.get_node_ids().len() as f64),
+            ),
+        ];
+    }
+    NodeInfo {
+        id: node.get_id().clone(),
+        node_type,
+        properties,
+    }
+}
+
+// executing an inquiry changes nothing, it is neither undone nor journaled
+#[derive(Debug)]
+pub struct InquiryCommand {
+    inquiry: Inquiry,
+}
+
+impl Command for InquiryCommand {
+    fn execute(&self, _data_model: &mut DataModel) {}
+
+    // nothing to replay
+    fn record(&self) -> CommandRecord {
+        CommandRecord::Group { commands: vec![] }
+    }
+
+    fn get_inquiry(&self) -> Option<&Inquiry> {
+        Some(&self.inquiry)
+    }
+}
+
+impl InquiryCommand {
+    pub fn new(inquiry: Inquiry) -> Self {
+        InquiryCommand { inquiry }
+    }
+}
+
+fn inquiry(inquiry: Inquiry) -> Result<Box<dyn Command>, ParseError> {
+    Ok(Box::new(InquiryCommand::new(inquiry)))
+}
+
+// the given node ids or the selection of the current page
+fn node_ids(args: &mut ArgParser, dm: &DataModel) -> Result<Vec<NodeId>, ParseError> {
+    let mut node_ids = vec![];
+    while !args.is_empty() {
+        let position = args.position();
+        let id = args.string("node id")?;
+        if dm.get_node(&id).is_none() {
+            return Err(ParseError::new(format!("Unknown node '{}'", id), position));
+        }
+        node_ids.push(id.into());
+    }
+    if node_ids.is_empty() {
+        node_ids = selected_ids(args, dm)?;
+    }
+    Ok(node_ids)
+}
+
+pub(crate) fn parse_list(
+    args: &mut ArgParser,
+    dm: &mut DataModel,
+) -> Result<Box<dyn Command>, ParseError> {
+    let node_ids = node_ids(args, dm)?;
+    inquiry(Inquiry::list(dm, &node_ids))
+}
+
+pub(crate) fn parse_id(
+    args: &mut ArgParser,
+    dm: &mut DataModel,
+) -> Result<Box<dyn Command>, ParseError> {
+    let point = dm.get_drawing_aids().apply(None, &args.point("point")?);
+    args.set_last_point(point);
+    args.finish()?;
+    inquiry(Inquiry::Id {
+        point: dm.get_ucs().to_ucs(&point),
+    })
+}
+
+pub(crate) fn parse_dist(
+    args: &mut ArgParser,
+    dm: &mut DataModel,
+) -> Result<Box<dyn Command>, ParseError> {
+    let aids = *dm.get_drawing_aids();
+    let p1 = aids.apply(None, &args.point("first point")?);
+    args.set_last_point(p1);
+    let p2 = aids.apply(Some(&p1), &args.point("second point")?);
+    args.set_last_point(p2);
+    args.finish()?;
+    inquiry(Inquiry::dist(&p1, &p2))
+}
+
+pub(crate) fn parse_area(
+    args: &mut ArgParser,
+    dm: &mut DataModel,
+) -> Result<Box<dyn Command>, ParseError> {
+    if args.peek_keyword("object") {
+        args.next_token();
+        let position = args.position();
+        let node_ids = node_ids(args, dm)?;
+        let area = Inquiry::node_area(dm, &node_ids)
+            .map_err(|message| ParseError::new(message, position))?;
+        return inquiry(area);
+    }
+
+    let aids = *dm.get_drawing_aids();
+    let mut points: Vec<Point2d> = vec![];
+    while points.len() < 3 || !args.is_empty() {
+        let pt = aids.apply(points.last(), &args.point("point")?);
+        args.set_last_point(pt);
+        points.push(pt);
+    }
+    inquiry(Inquiry::area(&points))
+}
+
+pub(crate) fn parse_measuregeom(
+    args: &mut ArgParser,
+    dm: &mut DataModel,
+) -> Result<Box<dyn Command>, ParseError> {
+    match args.keyword(&["distance", "radius", "area"])?.as_str() {
+        "distance" => parse_dist(args, dm),
+        "area" => parse_area(args, dm),
+        _ => {
+            let position = args.position();
+            let id = args.string("node id")?;
+            args.finish()?;
+            let radius =
+                Inquiry::radius(dm, &id).map_err(|message| ParseError::new(message, position))?;
+            inquiry(radius)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CommandHandler, CommandLine, CommandSession, Input};
+
+    fn setup() -> (DataModel, CommandLine) {
+        let mut dm = DataModel::default();
+        let mut cl = CommandLine::default();
+        for line in [
+            "page p1",
+            "line 0,0 30,40",
+            "circle 10,10 2",
+            "arc 0,0 5 0 90",
+        ] {
+            cl.parse(&mut dm, line).unwrap().execute(&mut dm);
+        }
+        (dm, cl)
+    }
+
+    fn inquire(cl: &mut CommandLine, dm: &mut DataModel, line: &str) -> Inquiry {
+        let cmd = cl.parse(dm, line).unwrap();
+        cmd.get_inquiry().unwrap().clone()
+    }
+
+    #[test]
+    fn id_and_dist() {
+        let (mut dm, mut cl) = setup();
+        let id = inquire(&mut cl, &mut dm, "id 3,4");
+        assert_eq!(id.to_string(), "X = 3.0000  Y = 4.0000");
+
+        // the id point is the last point
+        let dist = inquire(&mut cl, &mut dm, "dist @ @-3,-4");
+        assert_eq!(
+            dist.to_string(),
+            "Distance = 5.0000,  Angle = 233.1301,  Delta X = -3.0000,  Delta Y = -4.0000"
+        );
+        assert_eq!(
+            serde_json::to_value(&dist).unwrap()["delta"],
+            serde_json::json!({"x": -3.0, "y": -4.0})
+        );
+    }
+
+    #[test]
+    fn interactive_dist() {
+        let mut dm = DataModel::default();
+        let mut session = CommandSession::default();
+        assert!(session
+            .input(&mut dm, Input::Text("DI".into()))
+            .unwrap()
+            .is_none());
+        assert_eq!(session.get_prompt().unwrap().text(), "Specify first point:");
+        session
+            .input(&mut dm, Input::Point(Point2d::new(1.0, 1.0)))
+            .unwrap();
+        let cmd = session
+            .input(&mut dm, Input::Text("@0,2".into()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            cmd.get_inquiry(),
+            Some(&Inquiry::dist(
+                &Point2d::new(1.0, 1.0),
+                &Point2d::new(1.0, 3.0)
+            ))
+        );
+        assert!(!session.is_active());
+    }
+
+    #[test]
+    fn area_of_points_and_objects() {
+        let (mut dm, mut cl) = setup();
+        let area = inquire(&mut cl, &mut dm, "area 0,0 10,0 10,10 0,10");
+        assert_eq!(
+            area,
+            Inquiry::Area {
+                area: 100.0,
+                perimeter: 40.0
+            }
+        );
+        assert_eq!(
+            cl.parse(&mut dm, "area 0,0 10,0").unwrap_err(),
+            ParseError::new("Missing point", 13)
+        );
+
+        let area = inquire(&mut cl, &mut dm, "measuregeom area object 3");
+        assert_eq!(area.to_string(), "Area = 12.5664,  Perimeter = 12.5664");
+        let err = cl.parse(&mut dm, "area object 3 4").unwrap_err();
+        assert_eq!(err, ParseError::new("Node '4' is not a closed shape", 12));
+
+        // e.g. an imported closed polyline
+        let points = vec![
+            Point2d::new(0.0, 0.0),
+            Point2d::new(4.0, 0.0),
+            Point2d::new(4.0, 3.0),
+        ];
+        dm.insert_node(Box::new(Polyline::new("p".to_string(), points, true)));
+        let area = inquire(&mut cl, &mut dm, "area object p");
+        assert_eq!(area.to_string(), "Area = 6.0000,  Perimeter = 12.0000");
+        let list = inquire(&mut cl, &mut dm, "list p");
+        assert!(list
+            .to_string()
+            .ends_with("length: 12.0000\n  area: 6.0000"));
+    }
+
+    #[test]
+    fn list_selection() {
+        let (mut dm, mut cl) = setup();
+        assert!(cl.parse(&mut dm, "list").is_err());
+        dm.set_selection(vec!["2".into(), "3".into()]);
+        let list = inquire(&mut cl, &mut dm, "list");
+        assert_eq!(
+            list.to_string(),
+            "Line  id: 2
+  from: X = 0.0000  Y = 0.0000
+  to: X = 30.0000  Y = 40.0000
+  length: 50.0000
+  angle: 53.1301
+  delta: X = 30.0000  Y = 40.0000
+Circle  id: 3
+  center: X = 10.0000  Y = 10.0000
+  radius: 2.0000
+  circumference: 12.5664
+  area: 12.5664"
+        );
+        let json = serde_json::to_value(&list).unwrap();
+        assert_eq!(json["inquiry"], "list");
+        assert_eq!(json["nodes"][1]["properties"][1]["value"], 2.0);
+    }
+
+    #[test]
+    fn inquiries_are_not_undone() {
+        let (mut dm, mut cl) = setup();
+        let mut handler = CommandHandler::default();
+        for line in ["line 0,0 1,1", "dist 0,0 1,1"] {
+            let cmd = cl.parse(&mut dm, line).unwrap();
+            handler.execute(&mut dm, cmd).unwrap();
+        }
+        handler.undo(&mut dm).unwrap();
+        assert_eq!(dm.get_current_page().unwrap().get_node_ids().len(), 3);
+    }
+}
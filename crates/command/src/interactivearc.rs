@@ -0,0 +1,86 @@
+//
+
+use algebra::Point2d;
+use datamodel::DataModel;
+
+use crate::command::Command;
+use crate::{ArcCommand, InteractiveCommand, Prompt, PromptKind, Step, Value};
+
+// center, radius, start and end angle
+#[derive(Debug, Default)]
+pub struct InteractiveArc {
+    // circle: no angles are asked
+    full_circle: bool,
+    center: Option<Point2d>,
+    r: Option<f64>,
+    angle_start: Option<f64>,
+    angle_end: Option<f64>,
+}
+
+impl InteractiveArc {
+    pub fn circle() -> Self {
+        InteractiveArc {
+            full_circle: true,
+            ..Default::default()
+        }
+    }
+}
+
+impl InteractiveCommand for InteractiveArc {
+    fn prompt(&self) -> Prompt {
+        if self.center.is_none() {
+            Prompt::new("Specify center point", PromptKind::Point)
+        } else if self.r.is_none() {
+            Prompt::new("Specify radius", PromptKind::Distance).base(self.center)
+        } else if self.angle_start.is_none() {
+            Prompt::new("Specify start angle", PromptKind::Angle)
+                .default_value("0")
+                .base(self.center)
+        } else {
+            Prompt::new("Specify end angle", PromptKind::Angle)
+                .default_value("360")
+                .base(self.center)
+        }
+    }
+
+    fn input(&mut self, value: Value) -> Result<Step, String> {
+        match (value, self.center, self.r, self.angle_start) {
+            (Value::Point(pt), None, _, _) => self.center = Some(pt),
+            (Value::Number(r), Some(_), None, _) if r > 0.0 => {
+                self.r = Some(r);
+                if self.full_circle {
+                    self.angle_start = Some(0.0);
+                    self.angle_end = Some(360.0);
+                    return Ok(Step::Finish);
+                }
+            }
+            (Value::Number(_), Some(_), None, _) => {
+                return Err("The radius has to be greater than zero".to_string())
+            }
+            (Value::Number(angle), Some(_), Some(_), None) => self.angle_start = Some(angle),
+            (Value::Enter, Some(_), Some(_), None) => self.angle_start = Some(0.0),
+            (Value::Number(angle), Some(_), Some(_), Some(_)) => {
+                self.angle_end = Some(angle);
+                return Ok(Step::Finish);
+            }
+            (Value::Enter, Some(_), Some(_), Some(_)) => {
+                self.angle_end = Some(360.0);
+                return Ok(Step::Finish);
+            }
+            _ => return Err("Invalid input".to_string()),
+        }
+        Ok(Step::Continue)
+    }
+
+    fn finish(&self, data_model: &mut DataModel) -> Option<Box<dyn Command>> {
+        let center = self.center?;
+        Some(Box::new(ArcCommand::new(
+            data_model.next_id(),
+            center.x,
+            center.y,
+            self.r?,
+            self.angle_start?,
+            self.angle_end?,
+        )))
+    }
+}
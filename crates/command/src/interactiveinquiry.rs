@@ -0,0 +1,111 @@
+//
+
+use algebra::Point2d;
+use datamodel::DataModel;
+
+use crate::command::Command;
+use crate::{Inquiry, InquiryCommand, InteractiveCommand, Prompt, PromptKind, Step, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum InquiryKind {
+    Id,
+    Dist,
+    Area,
+}
+
+// id, dist and area with points entered one after the other
+#[derive(Debug)]
+pub struct InteractiveInquiry {
+    kind: InquiryKind,
+    points: Vec<Point2d>,
+    // area of the selected nodes instead of points
+    object: bool,
+}
+
+impl InteractiveInquiry {
+    fn new(kind: InquiryKind) -> Self {
+        InteractiveInquiry {
+            kind,
+            points: vec![],
+            object: false,
+        }
+    }
+
+    pub fn id() -> Self {
+        Self::new(InquiryKind::Id)
+    }
+
+    pub fn dist() -> Self {
+        Self::new(InquiryKind::Dist)
+    }
+
+    pub fn area() -> Self {
+        Self::new(InquiryKind::Area)
+    }
+}
+
+impl InteractiveCommand for InteractiveInquiry {
+    fn prompt(&self) -> Prompt {
+        let base = self.points.last().copied();
+        match (self.kind, self.points.len()) {
+            (InquiryKind::Id, _) => Prompt::new("Specify point", PromptKind::Point),
+            (InquiryKind::Dist, 0) => Prompt::new("Specify first point", PromptKind::Point),
+            (InquiryKind::Dist, _) => {
+                Prompt::new("Specify second point", PromptKind::Point).base(base)
+            }
+            (InquiryKind::Area, 0) => {
+                Prompt::new("Specify first corner point", PromptKind::Point).keywords(&["Object"])
+            }
+            (InquiryKind::Area, _) => Prompt::new("Specify next corner point", PromptKind::Point)
+                .keywords(&["Undo"])
+                .base(base),
+        }
+    }
+
+    fn input(&mut self, value: Value) -> Result<Step, String> {
+        match (self.kind, value) {
+            (InquiryKind::Area, Value::Point(pt)) => {
+                self.points.push(pt);
+                Ok(Step::Continue)
+            }
+            (InquiryKind::Area, Value::Enter) if self.points.len() > 2 => Ok(Step::Finish),
+            (InquiryKind::Area, Value::Keyword(keyword)) if keyword == "Undo" => {
+                self.points.pop();
+                Ok(Step::Continue)
+            }
+            (InquiryKind::Area, Value::Keyword(keyword)) if keyword == "Object" => {
+                self.object = true;
+                Ok(Step::Finish)
+            }
+            (InquiryKind::Area, _) => Err("At least 3 points are needed".to_string()),
+            (kind, Value::Point(pt)) => {
+                self.points.push(pt);
+                let needed = if kind == InquiryKind::Id { 1 } else { 2 };
+                if self.points.len() == needed {
+                    Ok(Step::Finish)
+                } else {
+                    Ok(Step::Continue)
+                }
+            }
+            _ => Err("Invalid input".to_string()),
+        }
+    }
+
+    fn finish(&self, data_model: &mut DataModel) -> Option<Box<dyn Command>> {
+        let inquiry = match self.kind {
+            InquiryKind::Area if self.object => {
+                let node_ids = data_model.get_current_page()?.get_selected_ids();
+                if node_ids.is_empty() {
+                    return None;
+                }
+                Inquiry::node_area(data_model, node_ids).ok()?
+            }
+            InquiryKind::Area => Inquiry::area(&self.points),
+            InquiryKind::Id => Inquiry::Id {
+                point: data_model.get_ucs().to_ucs(self.points.first()?),
+            },
+            InquiryKind::Dist => Inquiry::dist(self.points.first()?, self.points.get(1)?),
+        };
+        Some(Box::new(InquiryCommand::new(inquiry)))
+    }
+}
@@ -0,0 +1,68 @@
+//
+
+use algebra::Point2d;
+use datamodel::DataModel;
+
+use crate::command::Command;
+use crate::{GroupCommand, InteractiveCommand, LineCommand, Prompt, PromptKind, Step, Value};
+
+// line segments until Enter, Close or Escape
+#[derive(Debug, Default)]
+pub struct InteractiveLine {
+    points: Vec<Point2d>,
+}
+
+impl InteractiveCommand for InteractiveLine {
+    fn prompt(&self) -> Prompt {
+        match self.points.len() {
+            0 => Prompt::new("Specify first point", PromptKind::Point),
+            1 | 2 => Prompt::new("Specify next point", PromptKind::Point)
+                .keywords(&["Undo"])
+                .base(self.points.last().copied()),
+            _ => Prompt::new("Specify next point", PromptKind::Point)
+                .keywords(&["Close", "Undo"])
+                .base(self.points.last().copied()),
+        }
+    }
+
+    fn input(&mut self, value: Value) -> Result<Step, String> {
+        match value {
+            Value::Point(pt) => {
+                self.points.push(pt);
+                Ok(Step::Continue)
+            }
+            Value::Enter => Ok(Step::Finish),
+            Value::Keyword(keyword) if keyword == "Undo" => {
+                self.points.pop();
+                Ok(Step::Continue)
+            }
+            Value::Keyword(keyword) if keyword == "Close" && self.points.len() > 2 => {
+                self.points.push(self.points[0]);
+                Ok(Step::Finish)
+            }
+            _ => Err("Invalid input".to_string()),
+        }
+    }
+
+    fn finish(&self, data_model: &mut DataModel) -> Option<Box<dyn Command>> {
+        let mut segments: Vec<Box<dyn Command>> = self
+            .points
+            .windows(2)
+            .map(|p| -> Box<dyn Command> {
+                Box::new(LineCommand::new(
+                    data_model.next_id(),
+                    p[0].x,
+                    p[0].y,
+                    p[1].x,
+                    p[1].y,
+                ))
+            })
+            .collect();
+        // all segments are undone at once
+        match segments.len() {
+            0 => None,
+            1 => segments.pop(),
+            _ => Some(Box::new(GroupCommand::new(segments))),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+//
+
+use algebra::Point2d;
+use datamodel::DataModel;
+
+use crate::command::Command;
+use crate::{InteractiveCommand, MoveCommand, Prompt, PromptKind, Step, Value};
+
+// moves the selection of the current page from a base point to a second point
+#[derive(Debug, Default)]
+pub struct InteractiveMove {
+    base: Option<Point2d>,
+    second: Option<Point2d>,
+}
+
+impl InteractiveCommand for InteractiveMove {
+    fn prompt(&self) -> Prompt {
+        match self.base {
+            None => Prompt::new("Specify base point", PromptKind::Point),
+            Some(base) => Prompt::new("Specify second point", PromptKind::Point).base(Some(base)),
+        }
+    }
+
+    fn input(&mut self, value: Value) -> Result<Step, String> {
+        match (value, self.base) {
+            (Value::Point(pt), None) => {
+                self.base = Some(pt);
+                Ok(Step::Continue)
+            }
+            (Value::Point(pt), Some(_)) => {
+                self.second = Some(pt);
+                Ok(Step::Finish)
+            }
+            _ => Err("Invalid input".to_string()),
+        }
+    }
+
+    fn finish(&self, data_model: &mut DataModel) -> Option<Box<dyn Command>> {
+        let node_ids = data_model.get_current_page()?.get_selected_ids().clone();
+        if node_ids.is_empty() {
+            return None;
+        }
+        Some(Box::new(MoveCommand::new(
+            node_ids,
+            self.second? - self.base?,
+        )))
+    }
+}
@@ -0,0 +1,87 @@
+//
+
+use algebra::Point2d;
+use datamodel::DataModel;
+
+use crate::command::Command;
+use crate::{InteractiveCommand, Prompt, PromptKind, Step, Value, Zoom, ZoomCommand};
+
+// zoom window by two corners, or extents and previous as keywords
+#[derive(Debug, Default)]
+pub struct InteractiveZoom {
+    corner: Option<Point2d>,
+    zoom: Option<Zoom>,
+}
+
+impl InteractiveCommand for InteractiveZoom {
+    fn prompt(&self) -> Prompt {
+        match self.corner {
+            None => Prompt::new("Specify corner of window", PromptKind::Point)
+                .keywords(&["Extents", "Previous"]),
+            Some(corner) => {
+                Prompt::new("Specify opposite corner", PromptKind::Point).base(Some(corner))
+            }
+        }
+    }
+
+    fn input(&mut self, value: Value) -> Result<Step, String> {
+        match (value, self.corner) {
+            (Value::Point(pt), None) => {
+                self.corner = Some(pt);
+                Ok(Step::Continue)
+            }
+            (Value::Point(pt), Some(corner)) => {
+                self.zoom = Some(Zoom::Window(corner, pt));
+                Ok(Step::Finish)
+            }
+            (Value::Keyword(keyword), None) => {
+                self.zoom = Some(match keyword.as_str() {
+                    "Extents" => Zoom::Extents,
+                    _ => Zoom::Previous,
+                });
+                Ok(Step::Finish)
+            }
+            _ => Err("Invalid input".to_string()),
+        }
+    }
+
+    fn finish(&self, _data_model: &mut DataModel) -> Option<Box<dyn Command>> {
+        Some(Box::new(ZoomCommand::new(self.zoom.clone()?)))
+    }
+}
+
+// moves the drawing from a base point to a second point
+#[derive(Debug, Default)]
+pub struct InteractivePan {
+    base: Option<Point2d>,
+    second: Option<Point2d>,
+}
+
+impl InteractiveCommand for InteractivePan {
+    fn prompt(&self) -> Prompt {
+        match self.base {
+            None => Prompt::new("Specify base point", PromptKind::Point),
+            Some(base) => Prompt::new("Specify second point", PromptKind::Point).base(Some(base)),
+        }
+    }
+
+    fn input(&mut self, value: Value) -> Result<Step, String> {
+        match (value, self.base) {
+            (Value::Point(pt), None) => {
+                self.base = Some(pt);
+                Ok(Step::Continue)
+            }
+            (Value::Point(pt), Some(_)) => {
+                self.second = Some(pt);
+                Ok(Step::Finish)
+            }
+            _ => Err("Invalid input".to_string()),
+        }
+    }
+
+    fn finish(&self, _data_model: &mut DataModel) -> Option<Box<dyn Command>> {
+        Some(Box::new(ZoomCommand::new(Zoom::Pan(
+            self.second? - self.base?,
+        ))))
+    }
+}
@@ -0,0 +1,351 @@
+//
+// append-only journal of the executed commands, one json object per line
+//
+//   {"execute":{"command":"Page","id":"1","name":"p1","description":""}}
+//   {"execute":{"command":"Line","id":"2","x1":0.0,"y1":0.0,"x2":10.0,"y2":0.0}}
+//   "undo"
+//
+// replaying a journal rebuilds the same data model, ids included
+//
+
+use std::io::{BufRead, Write};
+
+use algebra::{DrawingAids, Point2d, Ucs, View};
+use datamodel::{node_from_json, DataModel, NodeId};
+use serde::{Deserialize, Serialize};
+
+use crate::command::Command;
+use crate::{
+    ArcCommand, CommandHandler, CurrentPageCommand, DrawingAidsCommand, EraseCommand, GroupCommand,
+    ImportCommand, LineCommand, MoveCommand, MovePageCommand, NamedViewCommand, PageCommand,
+    ReplaceNodeCommand, UcsCommand,
+};
+
+// the data needed to create a command again
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command")]
+pub enum CommandRecord {
+    Line {
+        id: NodeId,
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+    },
+    Arc {
+        id: NodeId,
+        x: f64,
+        y: f64,
+        r: f64,
+        angle_start: f64,
+        angle_end: f64,
+    },
+    Page {
+        id: NodeId,
+        name: String,
+        description: String,
+    },
+    DrawingAids {
+        old: DrawingAids,
+        new: DrawingAids,
+    },
+    Ucs {
+        old: Ucs,
+        new: Ucs,
+    },
+    Erase {
+        node_ids: Vec<NodeId>,
+    },
+    Move {
+        node_ids: Vec<NodeId>,
+        delta: Point2d,
+    },
+    ReplaceNode {
+        node: serde_json::Value,
+    },
+    // nodes read from a file, added to the page
+    Import {
+        page_id: NodeId,
+        nodes: Vec<serde_json::Value>,
+    },
+    Group {
+        commands: Vec<CommandRecord>,
+    },
+    CurrentPage {
+        page_id: NodeId,
+    },
+    MovePage {
+        page_id: NodeId,
+        position: usize,
+    },
+    // None deletes the view
+    NamedView {
+        page_id: NodeId,
+        name: String,
+        view: Option<View>,
+    },
+    // not repeated by a replay, but kept for the undo steps
+    Export {
+        filename: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalEntry {
+    Execute(CommandRecord),
+    Undo,
+}
+
+pub struct Journal {
+    writer: Box<dyn Write>,
+}
+
+impl std::fmt::Debug for Journal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Journal").finish()
+    }
+}
+
+impl Journal {
+    pub fn new(writer: Box<dyn Write>) -> Self {
+        Journal { writer }
+    }
+
+    // one line per entry, flushed at once so a crash loses nothing
+    pub fn append(&mut self, entry: &JournalEntry) -> std::io::Result<()> {
+        let line = serde_json::to_string(entry)?;
+        writeln!(self.writer, "{}", line)?;
+        self.writer.flush()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayError {
+    // 1 based
+    pub line_nr: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line_nr, self.message)
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+// executes the journal through the command handler, so undo works
+// afterwards, returns the number of entries
+pub fn replay<R: BufRead>(
+    reader: R,
+    dm: &mut DataModel,
+    handler: &mut CommandHandler,
+) -> Result<usize, ReplayError> {
+    let mut count = 0;
+    for (nr, line) in reader.lines().enumerate() {
+        let error = |message: String| ReplayError {
+            line_nr: nr + 1,
+            message,
+        };
+        let line = line.map_err(|e| error(e.to_string()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: JournalEntry = serde_json::from_str(&line).map_err(|e| error(e.to_string()))?;
+        match entry {
+            JournalEntry::Execute(record) => {
+                let cmd = execute_record(record, dm).map_err(error)?;
+                handler.push_executed(cmd);
+            }
+            JournalEntry::Undo => handler.undo(dm).map_err(error)?,
+        }
+        count += 1;
+    }
+    Ok(count)
+}
+
+// erase and replace take the old state from the data model, like the
+// command did when it was created
+fn execute_record(record: CommandRecord, dm: &mut DataModel) -> Result<Box<dyn Command>, String> {
+    let cmd: Box<dyn Command> = match record {
+        CommandRecord::Line { id, x1, y1, x2, y2 } => {
+            Box::new(LineCommand::new(id, x1, y1, x2, y2))
+        }
+        CommandRecord::Arc {
+            id,
+            x,
+            y,
+            r,
+            angle_start,
+            angle_end,
+        } => Box::new(ArcCommand::new(id, x, y, r, angle_start, angle_end)),
+        CommandRecord::Page {
+            id,
+            name,
+            description,
+        } => Box::new(PageCommand::new(id, name, description)),
+        CommandRecord::DrawingAids { old, new } => Box::new(DrawingAidsCommand::new(old, new)),
+        CommandRecord::Ucs { old, new } => Box::new(UcsCommand::new(old, new)),
+        CommandRecord::Erase { node_ids } => Box::new(EraseCommand::new(dm, &node_ids)),
+        CommandRecord::Move { node_ids, delta } => Box::new(MoveCommand::new(node_ids, delta)),
+        CommandRecord::ReplaceNode { node } => {
+            Box::new(ReplaceNodeCommand::new(dm, node_from_json(node)?)?)
+        }
+        CommandRecord::Import { page_id, nodes } => Box::new(ImportCommand::new(
+            page_id,
+            nodes
+                .into_iter()
+                .map(node_from_json)
+                .collect::<Result<_, _>>()?,
+        )),
+        // the later commands of a group may depend on the earlier ones
+        CommandRecord::Group { commands } => {
+            let mut executed = vec![];
+            for record in commands {
+                executed.push(execute_record(record, dm)?);
+            }
+            return Ok(Box::new(GroupCommand::new(executed)));
+        }
+        CommandRecord::CurrentPage { page_id } => Box::new(CurrentPageCommand::new(dm, page_id)),
+        CommandRecord::MovePage { page_id, position } => {
+            Box::new(MovePageCommand::new(dm, page_id, position))
+        }
+        CommandRecord::NamedView {
+            page_id,
+            name,
+            view,
+        } => Box::new(NamedViewCommand::new(dm, page_id, name, view)),
+        CommandRecord::Export { .. } => return Ok(Box::new(GroupCommand::new(vec![]))),
+    };
+    dm.begin_batch();
+    cmd.execute(dm);
+    dm.end_batch();
+    Ok(cmd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CommandSession, Input};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    // a writer the test can read afterwards
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    // e.g. a full disk
+    struct FailingWriter;
+
+    impl Write for FailingWriter {
+        fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("disk full"))
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn snapshot(dm: &DataModel) -> serde_json::Value {
+        serde_json::to_value(dm).unwrap()
+    }
+
+    #[test]
+    fn replay_rebuilds_the_data_model() {
+        let buffer = SharedBuffer::default();
+        let mut dm = DataModel::default();
+        let mut handler = CommandHandler::default();
+        handler.set_journal(Journal::new(Box::new(buffer.clone())));
+        let mut session = CommandSession::default();
+
+        for line in [
+            "page p1",
+            "grid 20",
+            "line",
+            "0,0",
+            "@10,0",
+            "@0,10",
+            "",
+            "circle 5,5 2",
+            "line 1,1 2,2",
+        ] {
+            let input = if line.is_empty() {
+                Input::Enter
+            } else {
+                Input::Text(line.to_string())
+            };
+            if let Some(cmd) = session.input(&mut dm, input).unwrap() {
+                handler.execute(&mut dm, cmd).unwrap();
+            }
+        }
+        handler.undo(&mut dm).unwrap();
+        dm.set_selection(vec!["4".into()]);
+        let cmd = session
+            .input(&mut dm, Input::Text("move 0,0 3,3".to_string()))
+            .unwrap()
+            .unwrap();
+        handler.execute(&mut dm, cmd).unwrap();
+        let cmd = session
+            .input(&mut dm, Input::Text("erase 3".to_string()))
+            .unwrap()
+            .unwrap();
+        handler.execute(&mut dm, cmd).unwrap();
+
+        let journal = String::from_utf8(buffer.0.borrow().clone()).unwrap();
+        assert_eq!(journal.lines().count(), 8);
+        assert_eq!(journal.lines().nth(5).unwrap(), "\"undo\"");
+
+        let mut replayed = DataModel::default();
+        let mut replay_handler = CommandHandler::default();
+        let count = replay(journal.as_bytes(), &mut replayed, &mut replay_handler).unwrap();
+        assert_eq!(count, 8);
+        // the selection is no command
+        dm.set_selection(vec![]);
+        assert_eq!(snapshot(&replayed), snapshot(&dm));
+        assert_eq!(replayed.get_drawing_aids(), dm.get_drawing_aids());
+        // new ids continue after the replayed ones
+        assert_eq!(replayed.next_id(), dm.next_id());
+
+        // the replayed commands can be undone
+        replay_handler.undo(&mut replayed).unwrap();
+        assert!(replayed.get_node("3").is_some());
+    }
+
+    #[test]
+    fn journal_errors_are_returned() {
+        let mut dm = DataModel::default();
+        let mut handler = CommandHandler::default();
+        handler.set_journal(Journal::new(Box::new(FailingWriter)));
+        let mut cl = crate::CommandLine::default();
+        let cmd = cl.parse(&mut dm, "page p1").unwrap();
+        let err = handler.execute(&mut dm, cmd).unwrap_err();
+        assert_eq!(err, "Can not write the journal: disk full");
+        // executed and undone anyway
+        assert_eq!(dm.get_pages().len(), 1);
+        let err = handler.undo(&mut dm).unwrap_err();
+        assert_eq!(err, "Can not write the journal: disk full");
+        assert!(dm.get_pages().is_empty());
+    }
+
+    #[test]
+    fn broken_line() {
+        let journal = "\"undo\"\n{\"execute\":{\"command\":\"Line\",\"id\":";
+        let err = replay(
+            journal.as_bytes(),
+            &mut DataModel::default(),
+            &mut CommandHandler::default(),
+        )
+        .unwrap_err();
+        assert_eq!(err.line_nr, 2);
+    }
+}
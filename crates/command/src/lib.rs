@@ -6,16 +6,63 @@ mod arccommand;
 mod command;
 mod commandhandler;
 mod commandline;
+mod commandsession;
+mod coordinate;
+mod drawingaidscommand;
+mod erasecommand;
 mod exportcommand;
+mod exporter;
+mod groupcommand;
+mod importcommand;
+mod inquiry;
+mod interactivearc;
+mod interactiveinquiry;
+mod interactiveline;
+mod interactivemove;
+mod interactivezoom;
+mod journal;
 mod linecommand;
+mod movecommand;
+mod namedviewcommand;
+mod output;
 mod pagecommand;
+mod parser;
+mod registry;
+mod replacenodecommand;
+mod script;
+mod ucscommand;
+mod zoom;
 
 pub use arccommand::*;
+pub use command::Command;
 pub use commandhandler::*;
 pub use commandline::*;
+pub use commandsession::*;
+pub use coordinate::*;
+pub use drawingaidscommand::*;
+pub use erasecommand::*;
 pub use exportcommand::*;
+pub use exporter::*;
+pub use groupcommand::*;
+pub use importcommand::*;
+pub use inquiry::*;
+pub use interactivearc::*;
+pub use interactiveinquiry::*;
+pub use interactiveline::*;
+pub use interactivemove::*;
+pub use interactivezoom::*;
+pub use journal::*;
 pub use linecommand::*;
+pub use movecommand::*;
+pub use namedviewcommand::*;
+pub use output::*;
 pub use pagecommand::*;
+pub use parser::*;
+pub use registry::*;
+pub use replacenodecommand::*;
+pub use script::*;
+pub use ucscommand::*;
+pub use zoom::*;
 
 #[cfg(test)]
 mod tests {
@@ -1,12 +1,13 @@
 //
 
-use datamodel::{DataModel, Line};
+use datamodel::{DataModel, Line, NodeId};
 
 use crate::command::Command;
+use crate::CommandRecord;
 
 #[derive(Debug)]
 pub struct LineCommand {
-    id: String,
+    id: NodeId,
     x1: f64,
     y1: f64,
     x2: f64,
@@ -20,18 +21,25 @@ impl Command for LineCommand {
         line.x2 = self.x2;
         line.y2 = self.y2;
         dm.insert_node(Box::new(line));
-        if let Some(page) = dm.get_current_page_mut() {
-            let id = self.id.clone();
-            page.add_node_id(id);
-        }
+        dm.add_node_to_current_page(&self.id);
     }
 
     fn undo(&self, dm: &mut DataModel) {
         dm.remove_node(&self.id);
     }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::Line {
+            id: self.id.clone(),
+            x1: self.x1,
+            y1: self.y1,
+            x2: self.x2,
+            y2: self.y2,
+        }
+    }
 }
 impl LineCommand {
-    pub fn new(id: String, x1: f64, y1: f64, x2: f64, y2: f64) -> Self {
+    pub fn new(id: NodeId, x1: f64, y1: f64, x2: f64, y2: f64) -> Self {
         LineCommand { id, x1, y1, x2, y2 }
     }
 }
@@ -0,0 +1,39 @@
+//
+
+use algebra::Point2d;
+use datamodel::{DataModel, NodeId};
+
+use crate::command::Command;
+use crate::CommandRecord;
+
+#[derive(Debug)]
+pub struct MoveCommand {
+    node_ids: Vec<NodeId>,
+    delta: Point2d,
+}
+impl Command for MoveCommand {
+    fn execute(&self, dm: &mut DataModel) {
+        for id in &self.node_ids {
+            dm.update_node(id, |node| node.translate(&self.delta));
+        }
+    }
+
+    fn undo(&self, dm: &mut DataModel) {
+        let back = Point2d::new(-self.delta.x, -self.delta.y);
+        for id in &self.node_ids {
+            dm.update_node(id, |node| node.translate(&back));
+        }
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::Move {
+            node_ids: self.node_ids.clone(),
+            delta: self.delta,
+        }
+    }
+}
+impl MoveCommand {
+    pub fn new(node_ids: Vec<NodeId>, delta: Point2d) -> Self {
+        MoveCommand { node_ids, delta }
+    }
+}
@@ -0,0 +1,143 @@
+//
+// named views of a page
+//
+//   view save name
+//   view restore name
+//   view delete name
+//
+
+use algebra::View;
+use datamodel::{DataModel, Node, NodeId};
+
+use crate::command::Command;
+use crate::parser::{ArgParser, ParseError};
+use crate::{CommandRecord, Zoom, ZoomCommand};
+
+// saves or deletes a named view, the old view is restored by undo
+#[derive(Debug)]
+pub struct NamedViewCommand {
+    page_id: NodeId,
+    name: String,
+    old: Option<View>,
+    new: Option<View>,
+}
+
+impl Command for NamedViewCommand {
+    fn execute(&self, dm: &mut DataModel) {
+        dm.set_named_view(&self.page_id, &self.name, self.new);
+    }
+
+    fn undo(&self, dm: &mut DataModel) {
+        dm.set_named_view(&self.page_id, &self.name, self.old);
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::NamedView {
+            page_id: self.page_id.clone(),
+            name: self.name.clone(),
+            view: self.new,
+        }
+    }
+}
+
+impl NamedViewCommand {
+    // None deletes the view, the old view is copied from the data model now
+    pub fn new(dm: &DataModel, page_id: NodeId, name: String, new: Option<View>) -> Self {
+        let old = dm
+            .get_page(&page_id)
+            .and_then(|page| page.get_named_view(&name))
+            .copied();
+        NamedViewCommand {
+            page_id,
+            name,
+            old,
+            new,
+        }
+    }
+}
+
+pub(crate) fn parse_view(
+    args: &mut ArgParser,
+    dm: &mut DataModel,
+) -> Result<Box<dyn Command>, ParseError> {
+    let action = args.keyword(&["save", "restore", "delete"])?;
+    let position = args.position();
+    let name = args.string("view name")?;
+    args.finish()?;
+
+    let Some(page) = dm.get_current_page() else {
+        return Err(ParseError::new("No current page", position));
+    };
+    let page_id = page.get_id().clone();
+    let named_view = page.get_named_view(&name).copied();
+    let unknown = || ParseError::new(format!("Unknown view '{}'", name), position);
+    match action.as_str() {
+        "save" => {
+            let view = page
+                .get_view()
+                .copied()
+                .ok_or_else(|| ParseError::new("The page has not been viewed yet", position))?;
+            Ok(Box::new(NamedViewCommand::new(
+                dm,
+                page_id,
+                name,
+                Some(view),
+            )))
+        }
+        "restore" => {
+            let view = named_view.ok_or_else(unknown)?;
+            Ok(Box::new(ZoomCommand::new(Zoom::View(view))))
+        }
+        _ => {
+            named_view.ok_or_else(unknown)?;
+            Ok(Box::new(NamedViewCommand::new(dm, page_id, name, None)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CommandHandler, CommandLine};
+
+    #[test]
+    fn save_restore_delete() {
+        let mut dm = DataModel::default();
+        let mut cl = CommandLine::default();
+        let mut handler = CommandHandler::default();
+        let cmd = cl.parse(&mut dm, "page p1").unwrap();
+        handler.execute(&mut dm, cmd).unwrap();
+
+        let err = cl.parse(&mut dm, "view save door").unwrap_err();
+        assert_eq!(err.message, "The page has not been viewed yet");
+
+        let view = View {
+            x: 0.0,
+            y: -50.0,
+            width: 100.0,
+            height: 50.0,
+            rotation: 0.0,
+        };
+        dm.set_page_view("1", view);
+        let cmd = cl.parse(&mut dm, "view save door").unwrap();
+        handler.execute(&mut dm, cmd).unwrap();
+        let page = dm.get_current_page().unwrap();
+        assert_eq!(page.get_named_view("door"), Some(&view));
+
+        let cmd = cl.parse(&mut dm, "view restore door").unwrap();
+        assert_eq!(cmd.get_zoom(), Some(&Zoom::View(view)));
+        assert_eq!(
+            cl.parse(&mut dm, "view restore window").unwrap_err(),
+            ParseError::new("Unknown view 'window'", 13)
+        );
+
+        let cmd = cl.parse(&mut dm, "view delete door").unwrap();
+        handler.execute(&mut dm, cmd).unwrap();
+        assert!(dm.get_current_page().unwrap().get_named_views().is_empty());
+        handler.undo(&mut dm).unwrap();
+        assert_eq!(
+            dm.get_current_page().unwrap().get_named_view("door"),
+            Some(&view)
+        );
+    }
+}
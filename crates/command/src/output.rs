@@ -0,0 +1,46 @@
+//
+// where the files of commands go, e.g. an export: native builds write them
+// to disk, in the browser the bytes are handed to javascript
+//
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputFile {
+    // as given on the command line
+    pub filename: String,
+    pub bytes: Vec<u8>,
+}
+
+pub trait OutputSink: std::fmt::Debug {
+    fn write(&mut self, file: OutputFile) -> Result<(), String>;
+}
+
+// relative to the working directory
+#[derive(Debug, Default)]
+pub struct FileSink;
+
+impl OutputSink for FileSink {
+    fn write(&mut self, file: OutputFile) -> Result<(), String> {
+        std::fs::write(&file.filename, file.bytes)
+            .map_err(|err| format!("Can not write '{}': {}", file.filename, err))
+    }
+}
+
+// the files are kept until they are taken, clones share them
+#[derive(Debug, Clone, Default)]
+pub struct MemorySink(Rc<RefCell<Vec<OutputFile>>>);
+
+impl MemorySink {
+    pub fn take(&self) -> Vec<OutputFile> {
+        self.0.borrow_mut().drain(..).collect()
+    }
+}
+
+impl OutputSink for MemorySink {
+    fn write(&mut self, file: OutputFile) -> Result<(), String> {
+        self.0.borrow_mut().push(file);
+        Ok(())
+    }
+}
@@ -1,12 +1,13 @@
 //
 
-use datamodel::{DataModel, Page};
+use datamodel::{DataModel, Node, NodeId, Page};
 
 use crate::command::Command;
+use crate::CommandRecord;
 
 #[derive(Debug)]
 pub struct PageCommand {
-    id: String,
+    id: NodeId,
     name: String,
     description: String,
 }
@@ -19,9 +20,17 @@ impl Command for PageCommand {
     fn undo(&self, dm: &mut DataModel) {
         dm.remove_page(&self.id);
     }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::Page {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            description: self.description.clone(),
+        }
+    }
 }
 impl PageCommand {
-    pub fn new(id: String, name: String, description: String) -> Self {
+    pub fn new(id: NodeId, name: String, description: String) -> Self {
         PageCommand {
             id,
             name,
@@ -29,3 +38,71 @@ impl PageCommand {
         }
     }
 }
+
+// makes another page the current page
+#[derive(Debug)]
+pub struct CurrentPageCommand {
+    old: NodeId,
+    new: NodeId,
+}
+impl Command for CurrentPageCommand {
+    fn execute(&self, dm: &mut DataModel) {
+        dm.set_current_page(&self.new);
+    }
+
+    fn undo(&self, dm: &mut DataModel) {
+        dm.set_current_page(&self.old);
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::CurrentPage {
+            page_id: self.new.clone(),
+        }
+    }
+}
+impl CurrentPageCommand {
+    pub fn new(dm: &DataModel, page_id: NodeId) -> Self {
+        CurrentPageCommand {
+            old: dm.get_current_page_id().clone(),
+            new: page_id,
+        }
+    }
+}
+
+// another position in the page order, 0 is the first page
+#[derive(Debug)]
+pub struct MovePageCommand {
+    page_id: NodeId,
+    old: usize,
+    new: usize,
+}
+impl Command for MovePageCommand {
+    fn execute(&self, dm: &mut DataModel) {
+        dm.move_page(&self.page_id, self.new);
+    }
+
+    fn undo(&self, dm: &mut DataModel) {
+        dm.move_page(&self.page_id, self.old);
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::MovePage {
+            page_id: self.page_id.clone(),
+            position: self.new,
+        }
+    }
+}
+impl MovePageCommand {
+    pub fn new(dm: &DataModel, page_id: NodeId, position: usize) -> Self {
+        let old = dm
+            .get_pages()
+            .iter()
+            .position(|page| page.get_id() == &page_id)
+            .unwrap_or(position);
+        MovePageCommand {
+            page_id,
+            old,
+            new: position,
+        }
+    }
+}
@@ -0,0 +1,566 @@
+//
+// tokenizer and argument parser for the command language
+//
+//   line 0,0 100mm,2cm
+//   line @ @50<30
+//   page "ground floor" "first page"
+//   export drawing.json format=json
+//
+
+use algebra::Point2d;
+use serde::Serialize;
+
+use crate::coordinate::{parse_point_input, PointInput, PointResolver};
+use crate::CommandLine;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Unit {
+    // factor to millimeter, the drawing unit
+    Length(f64),
+    // factor to degree
+    Angle(f64),
+}
+
+const UNITS: [(&str, Unit); 8] = [
+    ("mm", Unit::Length(1.0)),
+    ("cm", Unit::Length(10.0)),
+    ("m", Unit::Length(1000.0)),
+    ("in", Unit::Length(25.4)),
+    ("ft", Unit::Length(304.8)),
+    ("deg", Unit::Angle(1.0)),
+    ("°", Unit::Angle(1.0)),
+    ("rad", Unit::Angle(180.0 / std::f64::consts::PI)),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Number {
+    pub value: f64,
+    pub unit: Option<Unit>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    Word(String),
+    Str(String),
+    Number(Number),
+    // x,y @dx,dy #x,y dist<angle <angle
+    Point(PointInput),
+    // key=value
+    Option(String, String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    // byte offset in the command line
+    pub position: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParseError {
+    pub message: String,
+    // byte offset in the command line
+    pub position: usize,
+}
+
+impl ParseError {
+    pub fn new(message: impl Into<String>, position: usize) -> Self {
+        ParseError {
+            message: message.into(),
+            position,
+        }
+    }
+
+    // the command line with a marker below the error position
+    pub fn show(&self, line: &str) -> String {
+        let column = line
+            .get(..self.position.min(line.len()))
+            .map(|prefix| prefix.chars().count())
+            .unwrap_or(0);
+        format!("{}\n{}^ {}", line, " ".repeat(column), self.message)
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (position {})", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub fn tokenize(line: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = vec![];
+    let mut chars = line.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        if c == '"' || c == '\'' {
+            chars.next();
+            let mut value = String::new();
+            let mut closed = false;
+            while let Some((_, ch)) = chars.next() {
+                if ch == c {
+                    closed = true;
+                    break;
+                }
+                if ch == '\\' {
+                    if let Some((_, escaped)) = chars.next() {
+                        value.push(escaped);
+                    }
+                    continue;
+                }
+                value.push(ch);
+            }
+            if !closed {
+                return Err(ParseError::new("Unterminated string", start));
+            }
+            let end = chars.peek().map(|(i, _)| *i).unwrap_or(line.len());
+            tokens.push(Token {
+                kind: TokenKind::Str(value),
+                position: start,
+                text: line[start..end].to_string(),
+            });
+            continue;
+        }
+
+        let mut end = line.len();
+        while let Some(&(i, ch)) = chars.peek() {
+            if ch.is_whitespace() {
+                end = i;
+                break;
+            }
+            chars.next();
+        }
+        let text = &line[start..end];
+        tokens.push(Token {
+            kind: classify(text, start),
+            position: start,
+            text: text.to_string(),
+        });
+    }
+    Ok(tokens)
+}
+
+// a name like 2F that does not read as a number is a word, the error is
+// given where a number is expected
+fn classify(text: &str, position: usize) -> TokenKind {
+    classify_strict(text, position).unwrap_or_else(|_| TokenKind::Word(text.to_string()))
+}
+
+fn classify_strict(text: &str, position: usize) -> Result<TokenKind, ParseError> {
+    if let Some((key, value)) = text.split_once('=') {
+        if !key.is_empty() {
+            return Ok(TokenKind::Option(key.to_lowercase(), value.to_string()));
+        }
+    }
+    if let Some(point) = parse_point_input(text, position)? {
+        return Ok(TokenKind::Point(point));
+    }
+    match parse_number(text, position)? {
+        Some(number) => Ok(TokenKind::Number(number)),
+        None => Ok(TokenKind::Word(text.to_string())),
+    }
+}
+
+// Ok(None) if the text does not start like a number
+pub fn parse_number(text: &str, position: usize) -> Result<Option<Number>, ParseError> {
+    let bytes = text.as_bytes();
+    let mut end = 0;
+    if end < bytes.len() && (bytes[end] == b'+' || bytes[end] == b'-') {
+        end += 1;
+    }
+    let digits_start = end;
+    while end < bytes.len() && (bytes[end].is_ascii_digit() || bytes[end] == b'.') {
+        end += 1;
+    }
+    if end == digits_start {
+        return Ok(None);
+    }
+    // exponent, but not the start of a unit
+    if end + 1 < bytes.len() && (bytes[end] == b'e' || bytes[end] == b'E') {
+        let mut exp_end = end + 1;
+        if bytes[exp_end] == b'+' || bytes[exp_end] == b'-' {
+            exp_end += 1;
+        }
+        if exp_end < bytes.len() && bytes[exp_end].is_ascii_digit() {
+            while exp_end < bytes.len() && bytes[exp_end].is_ascii_digit() {
+                exp_end += 1;
+            }
+            end = exp_end;
+        }
+    }
+
+    let value: f64 = text[..end]
+        .parse()
+        .map_err(|_| ParseError::new(format!("Invalid number '{}'", text), position))?;
+
+    let suffix = &text[end..];
+    if suffix.is_empty() {
+        return Ok(Some(Number { value, unit: None }));
+    }
+    match UNITS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(suffix))
+    {
+        Some((_, unit)) => Ok(Some(Number {
+            value,
+            unit: Some(*unit),
+        })),
+        None => Err(ParseError::new(
+            format!("Unknown unit '{}'", suffix),
+            position + end,
+        )),
+    }
+}
+
+impl Number {
+    pub fn length(&self, position: usize) -> Result<f64, ParseError> {
+        match self.unit {
+            None => Ok(self.value),
+            Some(Unit::Length(factor)) => Ok(self.value * factor),
+            Some(Unit::Angle(_)) => {
+                Err(ParseError::new("Expected a length, not an angle", position))
+            }
+        }
+    }
+
+    pub fn angle(&self, position: usize) -> Result<f64, ParseError> {
+        match self.unit {
+            None => Ok(self.value),
+            Some(Unit::Angle(factor)) => Ok(self.value * factor),
+            Some(Unit::Length(_)) => {
+                Err(ParseError::new("Expected an angle, not a length", position))
+            }
+        }
+    }
+}
+
+// reads the arguments of one command line
+pub struct ArgParser<'a> {
+    tokens: Vec<Token>,
+    index: usize,
+    line_len: usize,
+    resolver: PointResolver,
+    command_line: Option<&'a CommandLine>,
+}
+
+impl<'a> ArgParser<'a> {
+    pub fn new(tokens: Vec<Token>, line_len: usize) -> Self {
+        ArgParser {
+            tokens,
+            index: 0,
+            line_len,
+            resolver: PointResolver::default(),
+            command_line: None,
+        }
+    }
+
+    // last point and dynamic input mode of the session
+    pub fn with_resolver(mut self, resolver: PointResolver) -> Self {
+        self.resolver = resolver;
+        self
+    }
+
+    // the command line that is parsing, e.g. for the aliases of a script
+    pub fn with_command_line(mut self, command_line: &'a CommandLine) -> Self {
+        self.command_line = Some(command_line);
+        self
+    }
+
+    pub fn get_command_line(&self) -> Option<&'a CommandLine> {
+        self.command_line
+    }
+
+    pub fn get_last_point(&self) -> Option<Point2d> {
+        self.resolver.last_point
+    }
+
+    // the point after drawing aids were applied, following relative input
+    // refers to it
+    pub fn set_last_point(&mut self, pt: Point2d) {
+        self.resolver.last_point = Some(pt);
+    }
+
+    pub fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    pub fn next_token(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.index).cloned();
+        if token.is_some() {
+            self.index += 1;
+        }
+        token
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index >= self.tokens.len()
+    }
+
+    // position for errors about missing arguments
+    pub fn position(&self) -> usize {
+        self.peek().map(|t| t.position).unwrap_or(self.line_len)
+    }
+
+    pub fn missing(&self, what: &str) -> ParseError {
+        ParseError::new(format!("Missing {}", what), self.position())
+    }
+
+    pub fn length(&mut self, what: &str) -> Result<f64, ParseError> {
+        let token = self.next_token().ok_or_else(|| self.missing(what))?;
+        match &token.kind {
+            TokenKind::Number(number) => number.length(token.position),
+            _ => Err(expected(what, &token)),
+        }
+    }
+
+    pub fn angle(&mut self, what: &str) -> Result<f64, ParseError> {
+        let token = self.next_token().ok_or_else(|| self.missing(what))?;
+        match &token.kind {
+            TokenKind::Number(number) => number.angle(token.position),
+            _ => Err(expected(what, &token)),
+        }
+    }
+
+    pub fn optional_angle(&mut self, what: &str, default: f64) -> Result<f64, ParseError> {
+        if self.is_empty() {
+            return Ok(default);
+        }
+        self.angle(what)
+    }
+
+    pub fn integer(&mut self, what: &str) -> Result<u32, ParseError> {
+        let token = self.next_token().ok_or_else(|| self.missing(what))?;
+        match &token.kind {
+            TokenKind::Number(Number { value, unit: None })
+                if *value >= 0.0 && value.fract() == 0.0 =>
+            {
+                Ok(*value as u32)
+            }
+            _ => Err(expected(what, &token)),
+        }
+    }
+
+    // point input (see coordinate.rs) or two numbers x y
+    pub fn point(&mut self, what: &str) -> Result<Point2d, ParseError> {
+        let token = self.next_token().ok_or_else(|| self.missing(what))?;
+        let pt = match &token.kind {
+            TokenKind::Point(PointInput::AngleOverride(angle)) => {
+                let angle = angle.angle(token.position)?;
+                let distance = self.length("distance")?;
+                self.resolver.resolve_angle_override(angle, distance)
+            }
+            TokenKind::Point(input) => self.resolver.resolve(input, token.position)?,
+            TokenKind::Number(x) => {
+                let x = x.length(token.position)?;
+                let y = self.length(what)?;
+                Point2d::new(x, y)
+            }
+            _ => return Err(expected(what, &token)),
+        };
+        self.resolver.points_in_command += 1;
+        self.set_last_point(pt);
+        Ok(pt)
+    }
+
+    // quoted string or a single word
+    pub fn string(&mut self, what: &str) -> Result<String, ParseError> {
+        let token = self.next_token().ok_or_else(|| self.missing(what))?;
+        match token.kind {
+            TokenKind::Str(value) => Ok(value),
+            TokenKind::Option(..) => Err(expected(what, &token)),
+            _ => Ok(token.text),
+        }
+    }
+
+    pub fn optional_string(&mut self, what: &str, default: &str) -> Result<String, ParseError> {
+        if self.is_empty() {
+            return Ok(default.to_string());
+        }
+        self.string(what)
+    }
+
+    // one of the given keywords (case insensitive), returned in lower case
+    pub fn keyword(&mut self, keywords: &[&str]) -> Result<String, ParseError> {
+        let what = keywords.join("/");
+        let token = self.next_token().ok_or_else(|| self.missing(&what))?;
+        match &token.kind {
+            TokenKind::Word(word) => {
+                let word = word.to_lowercase();
+                if keywords.contains(&word.as_str()) {
+                    Ok(word)
+                } else {
+                    Err(expected(&what, &token))
+                }
+            }
+            _ => Err(expected(&what, &token)),
+        }
+    }
+
+    pub fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token { kind: TokenKind::Word(word), .. }) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    pub fn peek_number(&self) -> bool {
+        matches!(
+            self.peek(),
+            Some(Token {
+                kind: TokenKind::Number(_),
+                ..
+            })
+        )
+    }
+
+    // on/off, no argument toggles the current value
+    pub fn switch(&mut self, current: bool) -> Result<bool, ParseError> {
+        if self.is_empty() {
+            return Ok(!current);
+        }
+        Ok(self.keyword(&["on", "off"])? == "on")
+    }
+
+    // all remaining key=value options
+    pub fn options(&mut self) -> Result<Vec<(String, String, usize)>, ParseError> {
+        let mut result = vec![];
+        while let Some(token) = self.next_token() {
+            match token.kind {
+                TokenKind::Option(key, value) => result.push((key, value, token.position)),
+                _ => return Err(expected("option name=value", &token)),
+            }
+        }
+        Ok(result)
+    }
+
+    pub fn finish(&self) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(token) => Err(ParseError::new(
+                format!("Unexpected argument '{}'", token.text),
+                token.position,
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+pub(crate) fn expected(what: &str, token: &Token) -> ParseError {
+    if let TokenKind::Word(text) = &token.kind {
+        if let Err(err) = classify_strict(text, token.position) {
+            return err;
+        }
+    }
+    ParseError::new(
+        format!("Expected {}, found '{}'", what, token.text),
+        token.position,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens() {
+        let tokens = tokenize(r#"page "ground floor" 10,20 5cm x=y"#).unwrap();
+        let kinds: Vec<_> = tokens.iter().map(|t| t.kind.clone()).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Word("page".to_string()),
+                TokenKind::Str("ground floor".to_string()),
+                TokenKind::Point(PointInput::Cartesian {
+                    x: Number {
+                        value: 10.0,
+                        unit: None
+                    },
+                    y: Number {
+                        value: 20.0,
+                        unit: None
+                    },
+                    mode: crate::coordinate::CoordinateMode::Default,
+                }),
+                TokenKind::Number(Number {
+                    value: 5.0,
+                    unit: Some(Unit::Length(10.0))
+                }),
+                TokenKind::Option("x".to_string(), "y".to_string()),
+            ]
+        );
+        assert_eq!(tokens[1].position, 5);
+        assert_eq!(tokens[2].position, 20);
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(parse_number("-1.5e2", 0).unwrap().unwrap().value, -150.0);
+        let number = parse_number("2m", 0).unwrap().unwrap();
+        assert_eq!(number.length(0).unwrap(), 2000.0);
+        assert!(number.angle(0).is_err());
+        assert_eq!(parse_number("abc", 0).unwrap(), None);
+        assert_eq!(
+            parse_number("10xy", 4).unwrap_err(),
+            ParseError::new("Unknown unit 'xy'", 6)
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            tokenize("page \"abc").unwrap_err(),
+            ParseError::new("Unterminated string", 5)
+        );
+        // the error of a point or number that does not read is given
+        // where it is expected, elsewhere it may be a name
+        let line = "line 1,x 2F";
+        let mut args = ArgParser::new(tokenize(line).unwrap(), line.len());
+        args.next_token();
+        assert_eq!(
+            args.point("start point").unwrap_err(),
+            ParseError::new("Invalid coordinate 'x'", 7)
+        );
+        assert_eq!(
+            args.length("radius").unwrap_err(),
+            ParseError::new("Unknown unit 'F'", 10)
+        );
+        let mut args = ArgParser::new(tokenize(line).unwrap(), line.len());
+        args.next_token();
+        args.next_token();
+        assert_eq!(args.string("page name"), Ok("2F".to_string()));
+
+        let line = "line 10 abc";
+        let mut args = ArgParser::new(tokenize(line).unwrap(), line.len());
+        args.next_token();
+        let err = args.point("start point").unwrap_err();
+        assert_eq!(err.position, 8);
+        assert_eq!(
+            err.show(line),
+            "line 10 abc\n        ^ Expected start point, found 'abc'"
+        );
+
+        let line = "line 10";
+        let mut args = ArgParser::new(tokenize(line).unwrap(), line.len());
+        args.next_token();
+        assert_eq!(
+            args.point("start point").unwrap_err(),
+            ParseError::new("Missing start point", 7)
+        );
+    }
+
+    #[test]
+    fn points_refer_to_last_point() {
+        let line = "line 10,10 @5,0 <90 10";
+        let mut args = ArgParser::new(tokenize(line).unwrap(), line.len());
+        args.next_token();
+        assert_eq!(args.point("p1").unwrap(), Point2d::new(10.0, 10.0));
+        assert_eq!(args.point("p2").unwrap(), Point2d::new(15.0, 10.0));
+        let pt = args.point("p3").unwrap();
+        assert_eq!(algebra::round(pt.x), 15.0);
+        assert_eq!(algebra::round(pt.y), 20.0);
+        args.finish().unwrap();
+    }
+}
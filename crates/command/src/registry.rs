@@ -0,0 +1,372 @@
+//
+// all commands of the command line with their aliases, arguments and help
+//
+// user aliases are read from a file like AutoCAD's acad.pgp
+//
+//   ; comment
+//   L,   *LINE
+//   CI,  *CIRCLE
+//
+
+use std::collections::BTreeMap;
+
+use datamodel::DataModel;
+use serde::Serialize;
+
+use crate::command::Command;
+use crate::coordinate::PointInput;
+use crate::parser::{expected, ArgParser, Number, ParseError, Token, TokenKind};
+use crate::InteractiveCommand;
+
+pub type ParseFn = fn(&mut ArgParser, &mut DataModel) -> Result<Box<dyn Command>, ParseError>;
+pub type InteractiveFn = fn() -> Box<dyn InteractiveCommand>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum ArgKind {
+    Point,
+    Distance,
+    Angle,
+    Integer,
+    Text,
+    Keyword,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ArgSpec {
+    pub name: &'static str,
+    pub kind: ArgKind,
+    pub optional: bool,
+    // a list at the end, e.g. node ids
+    pub repeated: bool,
+}
+
+impl ArgSpec {
+    pub const fn required(name: &'static str, kind: ArgKind) -> Self {
+        ArgSpec {
+            name,
+            kind,
+            optional: false,
+            repeated: false,
+        }
+    }
+
+    pub const fn optional(name: &'static str, kind: ArgKind) -> Self {
+        ArgSpec {
+            name,
+            kind,
+            optional: true,
+            repeated: false,
+        }
+    }
+
+    pub const fn repeated(name: &'static str, kind: ArgKind) -> Self {
+        ArgSpec {
+            name,
+            kind,
+            optional: true,
+            repeated: true,
+        }
+    }
+
+    // e.g. "format=json|prettyjson", any key=value option is taken
+    fn is_option(&self) -> bool {
+        self.name.contains('=')
+    }
+
+    // the keywords of "on|off|spacing [major]|origin point". an argument
+    // that takes a value has keywords only where they are followed by their
+    // own arguments, e.g. "object [node ids]" of area
+    fn keywords(&self) -> impl Iterator<Item = &str> + '_ {
+        let keyword_arg = self.kind == ArgKind::Keyword;
+        self.name
+            .split('|')
+            .filter(move |alternative| {
+                self.name.contains('|')
+                    && !self.is_option()
+                    && (keyword_arg || alternative.contains('['))
+            })
+            .filter_map(|alternative| alternative.split_whitespace().next())
+    }
+
+    // the number of tokens of one argument, None if they do not fit
+    fn token_count(&self, tokens: &[Token]) -> Option<usize> {
+        let kind = |i: usize| tokens.get(i).map(|token| &token.kind);
+        match (self.kind, kind(0)?) {
+            (_, TokenKind::Option(..)) => self.is_option().then_some(1),
+            (ArgKind::Point, TokenKind::Point(PointInput::AngleOverride(_))) => {
+                matches!(kind(1), Some(TokenKind::Number(_))).then_some(2)
+            }
+            (ArgKind::Point, TokenKind::Point(_)) => Some(1),
+            (ArgKind::Point, TokenKind::Number(_)) => {
+                matches!(kind(1), Some(TokenKind::Number(_))).then_some(2)
+            }
+            (ArgKind::Distance | ArgKind::Angle, TokenKind::Number(_)) => Some(1),
+            (ArgKind::Integer, TokenKind::Number(Number { value, unit: None })) => {
+                (*value >= 0.0 && value.fract() == 0.0).then_some(1)
+            }
+            (ArgKind::Text, _) => (!self.is_option()).then_some(1),
+            // a number for a keyword, e.g. the spacing of the grid, may be
+            // followed by more arguments
+            (ArgKind::Keyword, TokenKind::Number(_)) => Some(tokens.len()),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CommandInfo {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub args: Vec<ArgSpec>,
+    pub help: &'static str,
+    pub parse: ParseFn,
+    // used when the command is entered without arguments
+    pub interactive: Option<InteractiveFn>,
+}
+
+impl std::fmt::Debug for CommandInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CommandInfo")
+            .field("name", &self.name)
+            .field("aliases", &self.aliases)
+            .finish()
+    }
+}
+
+impl CommandInfo {
+    // line <start point> <end point>, optional arguments in []
+    pub fn usage(&self) -> String {
+        let mut usage = self.name.to_string();
+        for arg in &self.args {
+            if arg.repeated {
+                usage.push_str(&format!(" [{}...]", arg.name));
+            } else if arg.optional {
+                usage.push_str(&format!(" [{}]", arg.name));
+            } else {
+                usage.push_str(&format!(" <{}>", arg.name));
+            }
+        }
+        usage
+    }
+
+    // the tokens after the command name against the arguments. only the
+    // syntax is checked, ids, pages and files are not looked up. the
+    // arguments after a keyword, e.g. "zoom window p1 p2", are not described
+    // by the arguments and are taken as they are
+    pub fn check_args(&self, tokens: &[Token], line_len: usize) -> Result<(), ParseError> {
+        let mut rest = tokens;
+        for (i, arg) in self.args.iter().enumerate() {
+            let Some(token) = rest.first() else {
+                if arg.optional {
+                    continue;
+                }
+                return Err(ParseError::new(format!("Missing {}", arg.name), line_len));
+            };
+            if let TokenKind::Word(word) = &token.kind {
+                if arg
+                    .keywords()
+                    .any(|keyword| keyword.eq_ignore_ascii_case(word))
+                {
+                    if i + 1 == self.args.len() {
+                        return Ok(());
+                    }
+                    rest = &rest[1..];
+                    continue;
+                }
+            }
+            match arg.token_count(rest) {
+                Some(count) => rest = &rest[count..],
+                None if arg.optional => continue,
+                None => return Err(expected(arg.name, token)),
+            }
+            if arg.repeated {
+                while let Some(count) = arg.token_count(rest) {
+                    rest = &rest[count..];
+                }
+            }
+            if arg.is_option() {
+                // the other options are taken by this one
+                while let Some(TokenKind::Option(..)) = rest.first().map(|token| &token.kind) {
+                    rest = &rest[1..];
+                }
+            }
+        }
+        match rest.first() {
+            Some(token) => Err(ParseError::new(
+                format!("Unexpected argument '{}'", token.text),
+                token.position,
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CommandRegistry {
+    // key is the lower case name
+    commands: BTreeMap<String, CommandInfo>,
+    // lower case alias -> lower case command name
+    aliases: BTreeMap<String, String>,
+}
+
+impl Default for CommandRegistry {
+    fn default() -> Self {
+        let mut registry = CommandRegistry::new();
+        for info in crate::commandline::builtin_commands() {
+            registry.register(info);
+        }
+        registry
+    }
+}
+
+impl CommandRegistry {
+    // an empty registry, default() has the builtin commands
+    pub fn new() -> Self {
+        CommandRegistry {
+            commands: BTreeMap::new(),
+            aliases: BTreeMap::new(),
+        }
+    }
+
+    // replaces a command with the same name
+    pub fn register(&mut self, info: CommandInfo) {
+        let name = info.name.to_lowercase();
+        for alias in info.aliases {
+            self.aliases.insert(alias.to_lowercase(), name.clone());
+        }
+        self.commands.insert(name, info);
+    }
+
+    pub fn add_alias(&mut self, alias: &str, command: &str) -> Result<(), String> {
+        let name = self.alias_command(alias, command)?;
+        self.aliases.insert(alias.to_lowercase(), name);
+        Ok(())
+    }
+
+    // the lower case command name for a new alias
+    fn alias_command(&self, alias: &str, command: &str) -> Result<String, String> {
+        let Some(info) = self.lookup(command) else {
+            return Err(format!("Unknown command '{}'", command));
+        };
+        if self.commands.contains_key(&alias.to_lowercase()) {
+            return Err(format!("'{}' is a command name", alias));
+        }
+        Ok(info.name.to_lowercase())
+    }
+
+    // name or alias, case insensitive
+    pub fn lookup(&self, name: &str) -> Option<&CommandInfo> {
+        let name = name.to_lowercase();
+        let name = self.aliases.get(&name).unwrap_or(&name);
+        self.commands.get(name)
+    }
+
+    // aliases of a PGP file, returns the number of aliases. none of them
+    // are added if a line has an error
+    pub fn load_aliases(&mut self, text: &str) -> Result<usize, String> {
+        let mut aliases = BTreeMap::new();
+        let mut count = 0;
+        for (nr, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+            let Some((alias, command)) = line.split_once(',') else {
+                return Err(format!("line {}: Expected 'alias, *command'", nr + 1));
+            };
+            let alias = alias.trim();
+            let command = command.trim().trim_start_matches('*').trim();
+            if alias.is_empty() || alias.contains(char::is_whitespace) {
+                return Err(format!("line {}: Invalid alias '{}'", nr + 1, alias));
+            }
+            let name = self
+                .alias_command(alias, command)
+                .map_err(|err| format!("line {}: {}", nr + 1, err))?;
+            aliases.insert(alias.to_lowercase(), name);
+            count += 1;
+        }
+        self.aliases.extend(aliases);
+        Ok(count)
+    }
+
+    // command names starting with the prefix, sorted
+    pub fn complete(&self, prefix: &str) -> Vec<String> {
+        let prefix = prefix.to_lowercase();
+        self.commands
+            .keys()
+            .filter(|name| name.starts_with(&prefix))
+            .cloned()
+            .collect()
+    }
+
+    pub fn get_aliases(&self, command: &str) -> Vec<String> {
+        let command = command.to_lowercase();
+        self.aliases
+            .iter()
+            .filter(|(_, name)| **name == command)
+            .map(|(alias, _)| alias.to_uppercase())
+            .collect()
+    }
+
+    // usage, aliases and help text
+    pub fn help(&self, name: &str) -> Option<String> {
+        let info = self.lookup(name)?;
+        let mut help = info.usage();
+        let aliases = self.get_aliases(info.name);
+        if !aliases.is_empty() {
+            help.push_str(&format!(" (alias {})", aliases.join(", ")));
+        }
+        help.push('\n');
+        help.push_str(info.help);
+        Some(help)
+    }
+
+    pub fn get_commands(&self) -> Vec<&CommandInfo> {
+        self.commands.values().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_aliases() {
+        let registry = CommandRegistry::default();
+        for (alias, name) in [
+            ("L", "line"),
+            ("a", "arc"),
+            ("C", "circle"),
+            ("e", "erase"),
+            ("M", "move"),
+        ] {
+            assert_eq!(registry.lookup(alias).unwrap().name, name);
+        }
+        assert!(registry.lookup("xyz").is_none());
+        assert_eq!(registry.complete("p"), vec!["page", "pan", "polar"]);
+        assert_eq!(
+            registry.help("l").unwrap().lines().next().unwrap(),
+            "line <start point> <end point> (alias L)"
+        );
+    }
+
+    #[test]
+    fn pgp_file() {
+        let mut registry = CommandRegistry::default();
+        let text = "; my aliases\n\nLN,   *LINE\nci, *circle\n";
+        assert_eq!(registry.load_aliases(text), Ok(2));
+        assert_eq!(registry.lookup("ln").unwrap().name, "line");
+        assert_eq!(registry.lookup("CI").unwrap().name, "circle");
+
+        assert_eq!(
+            registry.load_aliases("X, *LINE\nY *ARC"),
+            Err("line 2: Expected 'alias, *command'".to_string())
+        );
+        // a file with an error adds no aliases
+        assert!(registry.lookup("X").is_none());
+        assert_eq!(
+            registry.load_aliases("Z, *NOPE"),
+            Err("line 1: Unknown command 'NOPE'".to_string())
+        );
+    }
+}
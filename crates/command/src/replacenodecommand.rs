@@ -0,0 +1,38 @@
+//
+
+use datamodel::{DataModel, Node};
+
+use crate::command::Command;
+use crate::CommandRecord;
+
+// exchanges a node with a changed copy, e.g. a node patched by the client
+#[derive(Debug)]
+pub struct ReplaceNodeCommand {
+    old: Box<dyn Node>,
+    new: Box<dyn Node>,
+}
+impl Command for ReplaceNodeCommand {
+    fn execute(&self, dm: &mut DataModel) {
+        dm.replace_node(self.new.clone_node());
+    }
+
+    fn undo(&self, dm: &mut DataModel) {
+        dm.replace_node(self.old.clone_node());
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::ReplaceNode {
+            node: serde_json::to_value(self.new.as_ref()).unwrap_or_default(),
+        }
+    }
+}
+impl ReplaceNodeCommand {
+    // the old node is copied from the data model now
+    pub fn new(dm: &DataModel, new: Box<dyn Node>) -> Result<Self, String> {
+        let old = dm
+            .get_node(new.get_id())
+            .ok_or_else(|| format!("Node '{}' not found", new.get_id()))?
+            .clone_node();
+        Ok(ReplaceNodeCommand { old, new })
+    }
+}
@@ -0,0 +1,400 @@
+//
+// script files (.scr), one command line or prompt answer per line
+//
+//   ; comment
+//   page "ground floor"
+//   line
+//   0,0
+//   @100,0
+//
+//   circle 50,50 10
+//
+// an empty line is Enter while a command is prompting, otherwise it is
+// ignored, a command that is still prompting at the end gets an Enter
+//
+
+use std::cell::RefCell;
+
+use datamodel::DataModel;
+use serde::Serialize;
+
+use crate::command::Command;
+use crate::parser::{ArgParser, ParseError};
+use crate::{CommandLine, CommandRecord, CommandSession, Input, OutputFile};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OnError {
+    #[default]
+    Stop,
+    Continue,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScriptError {
+    // 1 based
+    pub line_nr: usize,
+    pub line: String,
+    pub error: ParseError,
+}
+
+impl std::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}:\n{}", self.line_nr, self.error.show(&self.line))
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ScriptResult {
+    // number of commands given to execute
+    pub executed: usize,
+    pub errors: Vec<ScriptError>,
+}
+
+impl ScriptResult {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+// runs the lines through the session, finished commands are given to
+// execute, e.g. CommandHandler::execute, its errors are script errors too
+pub fn run_script<F>(
+    session: &mut CommandSession,
+    data_model: &mut DataModel,
+    text: &str,
+    on_error: OnError,
+    mut execute: F,
+) -> ScriptResult
+where
+    F: FnMut(&mut DataModel, Box<dyn Command>) -> Result<(), String>,
+{
+    let mut result = ScriptResult::default();
+    for (nr, line) in text.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with(';') {
+            continue;
+        }
+        let input = match (trimmed.is_empty(), session.is_active()) {
+            (true, false) => continue,
+            (true, true) => Input::Enter,
+            (false, _) => Input::Text(trimmed.to_string()),
+        };
+        match input_line(session, data_model, input, &mut execute) {
+            Ok(count) => result.executed += count,
+            Err(error) => {
+                result.errors.push(ScriptError {
+                    line_nr: nr + 1,
+                    line: trimmed.to_string(),
+                    error,
+                });
+                if on_error == OnError::Stop {
+                    // do not leave a half entered command behind
+                    let _ = session.input(data_model, Input::Escape);
+                    return result;
+                }
+            }
+        }
+    }
+    if session.is_active() {
+        // a command that gives nothing to execute was not finished
+        let executed = input_line(session, data_model, Input::Enter, &mut execute).and_then(
+            |count| match count {
+                0 => Err(ParseError::new("The command is not finished", 0)),
+                count => Ok(count),
+            },
+        );
+        match executed {
+            Ok(count) => result.executed += count,
+            Err(error) => result.errors.push(ScriptError {
+                line_nr: text.lines().count(),
+                line: text.lines().last().unwrap_or_default().trim().to_string(),
+                error,
+            }),
+        }
+        let _ = session.input(data_model, Input::Escape);
+    }
+    result
+}
+
+// the number of commands given to execute
+fn input_line<F>(
+    session: &mut CommandSession,
+    data_model: &mut DataModel,
+    input: Input,
+    execute: &mut F,
+) -> Result<usize, ParseError>
+where
+    F: FnMut(&mut DataModel, Box<dyn Command>) -> Result<(), String>,
+{
+    match session.input(data_model, input)? {
+        Some(cmd) => execute(data_model, cmd)
+            .map(|_| 1)
+            .map_err(|message| ParseError::new(message, 0)),
+        None => Ok(0),
+    }
+}
+
+// script <file> [stop|continue]
+// the commands of the script are undone as one step, the commands before an
+// error stay executed
+#[derive(Debug)]
+pub struct ScriptCommand {
+    text: String,
+    on_error: OnError,
+    // aliases and last point of the command line that started the script
+    command_line: CommandLine,
+    executed: RefCell<Vec<Box<dyn Command>>>,
+    // the files of the commands, e.g. exports
+    output: RefCell<Vec<OutputFile>>,
+    // the script errors with their line numbers
+    error: RefCell<Option<String>>,
+}
+
+impl Command for ScriptCommand {
+    fn execute(&self, data_model: &mut DataModel) {
+        let mut session = CommandSession::new(self.command_line.clone());
+        let mut executed = vec![];
+        let mut output = vec![];
+        let result = run_script(
+            &mut session,
+            data_model,
+            &self.text,
+            self.on_error,
+            |dm, cmd| {
+                cmd.execute(dm);
+                if let Some(error) = cmd.get_error() {
+                    return Err(error);
+                }
+                output.extend(cmd.take_output());
+                if cmd.is_undoable() {
+                    executed.push(cmd);
+                }
+                Ok(())
+            },
+        );
+        let errors: Vec<String> = result
+            .errors
+            .iter()
+            .map(|error| error.to_string())
+            .collect();
+        *self.executed.borrow_mut() = executed;
+        *self.output.borrow_mut() = output;
+        *self.error.borrow_mut() = (!errors.is_empty()).then(|| errors.join("\n"));
+    }
+
+    fn get_error(&self) -> Option<String> {
+        self.error.borrow().clone()
+    }
+
+    fn take_output(&self) -> Vec<OutputFile> {
+        self.output.borrow_mut().drain(..).collect()
+    }
+
+    // nothing to undo if the first command failed
+    fn is_undoable(&self) -> bool {
+        !self.executed.borrow().is_empty()
+    }
+
+    fn undo(&self, data_model: &mut DataModel) {
+        for cmd in self.executed.borrow().iter().rev() {
+            cmd.undo(data_model);
+        }
+    }
+
+    // the commands that were executed, the script file may be gone
+    fn record(&self) -> CommandRecord {
+        CommandRecord::Group {
+            commands: self
+                .executed
+                .borrow()
+                .iter()
+                .map(|cmd| cmd.record())
+                .collect(),
+        }
+    }
+}
+
+impl ScriptCommand {
+    pub fn new(text: String, on_error: OnError) -> Self {
+        ScriptCommand {
+            text,
+            on_error,
+            command_line: CommandLine::default(),
+            executed: RefCell::new(vec![]),
+            output: RefCell::new(vec![]),
+            error: RefCell::new(None),
+        }
+    }
+
+    pub fn with_command_line(mut self, command_line: CommandLine) -> Self {
+        self.command_line = command_line;
+        self
+    }
+}
+
+pub(crate) fn parse_script(
+    args: &mut ArgParser,
+    _dm: &mut DataModel,
+) -> Result<Box<dyn Command>, ParseError> {
+    let position = args.position();
+    let filename = args.string("script file")?;
+    let on_error = if args.is_empty() {
+        OnError::Stop
+    } else if args.keyword(&["stop", "continue"])? == "continue" {
+        OnError::Continue
+    } else {
+        OnError::Stop
+    };
+    args.finish()?;
+    let text = std::fs::read_to_string(&filename).map_err(|err| {
+        ParseError::new(format!("Can not read '{}': {}", filename, err), position)
+    })?;
+    let mut cmd = ScriptCommand::new(text, on_error);
+    if let Some(command_line) = args.get_command_line() {
+        cmd = cmd.with_command_line(command_line.clone());
+    }
+    Ok(Box::new(cmd))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CommandHandler, MemorySink};
+    use algebra::Point2d;
+
+    const SCRIPT: &str = "; test drawing
+page p1
+line
+0,0
+@10,0
+@0,10
+
+circle 5,5 abc
+circle 5,5 2
+line 0,0 1,1
+";
+
+    fn run(on_error: OnError) -> (DataModel, ScriptResult) {
+        let mut dm = DataModel::default();
+        let mut session = CommandSession::default();
+        let mut handler = CommandHandler::default();
+        let result = run_script(&mut session, &mut dm, SCRIPT, on_error, |dm, cmd| {
+            handler.execute(dm, cmd)
+        });
+        (dm, result)
+    }
+
+    #[test]
+    fn stop_on_error() {
+        let (dm, result) = run(OnError::Stop);
+        assert_eq!(result.executed, 2);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(
+            result.errors[0].to_string(),
+            "line 8:\ncircle 5,5 abc\n           ^ Expected radius, found 'abc'"
+        );
+        assert_eq!(dm.get_current_page().unwrap().get_node_ids().len(), 2);
+    }
+
+    #[test]
+    fn continue_on_error() {
+        let (dm, result) = run(OnError::Continue);
+        assert_eq!(result.executed, 4);
+        assert_eq!(result.errors[0].line_nr, 8);
+        assert_eq!(dm.get_current_page().unwrap().get_node_ids().len(), 4);
+    }
+
+    #[test]
+    fn execute_errors() {
+        let mut dm = DataModel::default();
+        let mut session = CommandSession::default();
+        let mut handler = CommandHandler::default();
+        let text = "page p1\nexport /no/such/dir/plan.dxf\nline 0,0 1,1\n";
+        let result = run_script(&mut session, &mut dm, text, OnError::Stop, |dm, cmd| {
+            handler.execute(dm, cmd)
+        });
+        assert_eq!(result.executed, 1);
+        assert_eq!(result.errors[0].line_nr, 2);
+        assert!(result.errors[0]
+            .error
+            .message
+            .starts_with("Can not write '/no/such/dir/plan.dxf'"));
+    }
+
+    #[test]
+    fn half_entered_command_at_the_end() {
+        let mut dm = DataModel::default();
+        let mut session = CommandSession::default();
+        let mut handler = CommandHandler::default();
+        let text = "page p1\nline\n0,0\n";
+        let result = run_script(&mut session, &mut dm, text, OnError::Stop, |dm, cmd| {
+            handler.execute(dm, cmd)
+        });
+        assert!(!result.is_ok(), "{:?}", result);
+        assert_eq!(result.errors[0].line_nr, 3);
+        assert_eq!(
+            result.errors[0].error.message,
+            "The command is not finished"
+        );
+        assert!(!session.is_active());
+    }
+
+    #[test]
+    fn script_command_output() {
+        let mut dm = DataModel::default();
+        let sink = MemorySink::default();
+        let mut handler = CommandHandler::default();
+        handler.set_sink(Box::new(sink.clone()));
+        let text = "page p1\nline 0,0 1,1\nexport a.json\nexport b.svg\n";
+        let cmd = ScriptCommand::new(text.to_string(), OnError::Stop);
+        handler.execute(&mut dm, Box::new(cmd)).unwrap();
+        let files = sink.take();
+        let names: Vec<&str> = files.iter().map(|file| file.filename.as_str()).collect();
+        assert_eq!(names, ["a.json", "b.svg"]);
+        assert!(files[1].bytes.starts_with(b"<?xml"));
+    }
+
+    #[test]
+    fn script_command_errors() {
+        let mut dm = DataModel::default();
+        let mut handler = CommandHandler::default();
+        let cmd = ScriptCommand::new(SCRIPT.to_string(), OnError::Continue);
+        let err = handler.execute(&mut dm, Box::new(cmd)).unwrap_err();
+        assert_eq!(
+            err,
+            "line 8:\ncircle 5,5 abc\n           ^ Expected radius, found 'abc'"
+        );
+        // the commands around the error are kept and undone at once
+        assert_eq!(dm.get_current_page().unwrap().get_node_ids().len(), 4);
+        handler.undo(&mut dm).unwrap();
+        assert!(dm.get_pages().is_empty());
+    }
+
+    #[test]
+    fn script_uses_the_command_line() {
+        let mut dm = DataModel::default();
+        let mut cl = CommandLine::default();
+        cl.get_registry_mut().add_alias("ln", "line").unwrap();
+        cl.set_last_point(Point2d::new(5.0, 5.0));
+        let cmd = ScriptCommand::new("page p1\nln @10,0 @0,10\n".to_string(), OnError::Stop)
+            .with_command_line(cl);
+        cmd.execute(&mut dm);
+        assert_eq!(cmd.get_error(), None);
+        let id = dm.get_current_page().unwrap().get_node_ids()[0].to_string();
+        let bbox = dm.get_node(&id).unwrap().get_bbox().unwrap();
+        assert_eq!(
+            (bbox.min, bbox.max),
+            (Point2d::new(15.0, 5.0), Point2d::new(15.0, 15.0))
+        );
+    }
+
+    #[test]
+    fn script_command_is_undone_at_once() {
+        let mut dm = DataModel::default();
+        let cmd = ScriptCommand::new(SCRIPT.to_string(), OnError::Continue);
+        cmd.execute(&mut dm);
+        assert_eq!(dm.get_pages().len(), 1);
+        cmd.undo(&mut dm);
+        assert!(dm.get_pages().is_empty());
+    }
+}
@@ -0,0 +1,125 @@
+//
+// user coordinate system
+//
+//   ucs [world]                 back to the world coordinate system
+//   ucs origin point            moves the origin, the axes are kept
+//   ucs z angle                 rotates the axes around the origin
+//   ucs axis origin xpoint      origin and a point on the positive x axis
+//
+// the points are given in the current ucs
+//
+
+use algebra::Ucs;
+use datamodel::DataModel;
+
+use crate::command::Command;
+use crate::parser::{ArgParser, ParseError};
+use crate::CommandRecord;
+
+#[derive(Debug)]
+pub struct UcsCommand {
+    old: Ucs,
+    new: Ucs,
+}
+impl Command for UcsCommand {
+    fn execute(&self, dm: &mut DataModel) {
+        dm.set_ucs(self.new);
+    }
+
+    fn undo(&self, dm: &mut DataModel) {
+        dm.set_ucs(self.old);
+    }
+
+    fn record(&self) -> CommandRecord {
+        CommandRecord::Ucs {
+            old: self.old,
+            new: self.new,
+        }
+    }
+}
+impl UcsCommand {
+    pub fn new(old: Ucs, new: Ucs) -> Self {
+        UcsCommand { old, new }
+    }
+}
+
+pub(crate) fn parse_ucs(
+    args: &mut ArgParser,
+    dm: &mut DataModel,
+) -> Result<Box<dyn Command>, ParseError> {
+    let old = *dm.get_ucs();
+    let new = if args.is_empty() {
+        Ucs::default()
+    } else {
+        match args
+            .keyword(&["world", "w", "origin", "o", "z", "axis"])?
+            .as_str()
+        {
+            "world" | "w" => Ucs::default(),
+            "origin" | "o" => Ucs::new(args.point("origin")?, old.angle),
+            "z" => Ucs::new(old.origin, old.angle + args.angle("rotation angle")?),
+            _ => {
+                let origin = args.point("origin")?;
+                args.set_last_point(origin);
+                let position = args.position();
+                let x_point = args.point("point on the x axis")?;
+                if x_point == origin {
+                    return Err(ParseError::new(
+                        "The point on the x axis must differ from the origin",
+                        position,
+                    ));
+                }
+                let delta = x_point - origin;
+                Ucs::new(origin, delta.y.atan2(delta.x).to_degrees())
+            }
+        }
+    };
+    args.finish()?;
+    Ok(Box::new(UcsCommand::new(old, new)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CommandLine;
+    use algebra::Point2d;
+    use datamodel::Line;
+
+    #[test]
+    fn points_in_the_ucs() {
+        let mut dm = DataModel::default();
+        let mut cl = CommandLine::default();
+        for line in ["page p1", "ucs origin 100,0", "ucs z 90", "line 0,0 10,0"] {
+            cl.parse(&mut dm, line).unwrap().execute(&mut dm);
+        }
+        assert_eq!(dm.get_ucs().origin, Point2d::new(100.0, 0.0));
+        let id = dm.get_current_page().unwrap().get_node_ids()[0].clone();
+        let line = dm
+            .get_node(&id)
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Line>()
+            .unwrap();
+        assert_eq!(line.get_p1(), Point2d::new(100.0, 0.0));
+        assert_eq!(
+            (algebra::round(line.x2), algebra::round(line.y2)),
+            (100.0, 10.0)
+        );
+
+        // the id of a point is shown in the ucs
+        let cmd = cl.parse(&mut dm, "id @").unwrap();
+        assert_eq!(
+            cmd.get_inquiry().unwrap().to_string(),
+            "X = 10.0000  Y = 0.0000"
+        );
+
+        let cmd = cl.parse(&mut dm, "ucs").unwrap();
+        cmd.execute(&mut dm);
+        assert!(dm.get_ucs().is_world());
+        cmd.undo(&mut dm);
+        assert_eq!(dm.get_ucs().angle, 90.0);
+
+        let err = cl.parse(&mut dm, "ucs axis 1,1 1,1").unwrap_err();
+        assert_eq!(err.position, 13);
+    }
+}
@@ -0,0 +1,224 @@
+//
+// zoom and pan change the viewport of the client, not the data model
+//
+//   zoom extents|e              all nodes of the current page
+//   zoom window|w p1 p2         the window between two corners
+//   zoom previous|p             back to the view before the last zoom or pan
+//   zoom scale|s factor         2 shows everything twice as large
+//   zoom center|c point [height]
+//   pan displacement | pan p1 p2
+//   view restore name           see namedviewcommand.rs
+//
+
+use algebra::{BBox, Point2d, View, Viewport};
+use datamodel::DataModel;
+
+use crate::command::Command;
+use crate::parser::{ArgParser, ParseError};
+use crate::CommandRecord;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Zoom {
+    Extents,
+    Window(Point2d, Point2d),
+    Previous,
+    Scale(f64),
+    Center {
+        center: Point2d,
+        height: Option<f64>,
+    },
+    // moves the drawing, in model coordinates
+    Pan(Point2d),
+    View(View),
+}
+
+impl Zoom {
+    // false if the view did not change, e.g. an empty page or no previous view
+    pub fn apply(&self, viewport: &mut Viewport, data_model: &DataModel) -> bool {
+        match self {
+            Zoom::Extents => {
+                let extents = data_model
+                    .get_spatial_index(data_model.get_current_page_id())
+                    .and_then(|index| index.extents());
+                match extents {
+                    Some(extents) => viewport.zoom_extents(&extents),
+                    None => return false,
+                }
+            }
+            Zoom::Window(p1, p2) => viewport.zoom_window(&BBox::from_points(p1, p2)),
+            Zoom::Previous => return viewport.zoom_previous(),
+            Zoom::Scale(factor) => viewport.zoom_scale(*factor),
+            Zoom::Center { center, height } => viewport.zoom_center(center, *height),
+            Zoom::Pan(delta) => viewport.pan(delta),
+            Zoom::View(view) => viewport.zoom_view(view),
+        }
+        true
+    }
+}
+
+#[derive(Debug)]
+pub struct ZoomCommand {
+    zoom: Zoom,
+}
+
+impl Command for ZoomCommand {
+    fn execute(&self, _data_model: &mut DataModel) {}
+
+    // nothing to replay
+    fn record(&self) -> CommandRecord {
+        CommandRecord::Group { commands: vec![] }
+    }
+
+    fn get_zoom(&self) -> Option<&Zoom> {
+        Some(&self.zoom)
+    }
+}
+
+impl ZoomCommand {
+    pub fn new(zoom: Zoom) -> Self {
+        ZoomCommand { zoom }
+    }
+}
+
+fn zoom(zoom: Zoom) -> Result<Box<dyn Command>, ParseError> {
+    Ok(Box::new(ZoomCommand::new(zoom)))
+}
+
+fn scale_factor(args: &mut ArgParser) -> Result<f64, ParseError> {
+    let position = args.position();
+    let factor = args.length("scale factor")?;
+    if factor <= 0.0 {
+        return Err(ParseError::new(
+            "The scale factor must be positive",
+            position,
+        ));
+    }
+    Ok(factor)
+}
+
+pub(crate) fn parse_zoom(
+    args: &mut ArgParser,
+    _dm: &mut DataModel,
+) -> Result<Box<dyn Command>, ParseError> {
+    // a number alone is a scale factor
+    if args.peek_number() {
+        let factor = scale_factor(args)?;
+        args.finish()?;
+        return zoom(Zoom::Scale(factor));
+    }
+    let keyword = args.keyword(&[
+        "extents", "e", "window", "w", "previous", "p", "scale", "s", "center", "c",
+    ])?;
+    let result = match &keyword[..1] {
+        "e" => Zoom::Extents,
+        "w" => {
+            let p1 = args.point("first corner")?;
+            args.set_last_point(p1);
+            let p2 = args.point("opposite corner")?;
+            Zoom::Window(p1, p2)
+        }
+        "p" => Zoom::Previous,
+        "s" => Zoom::Scale(scale_factor(args)?),
+        _ => {
+            let center = args.point("center point")?;
+            let height = if args.is_empty() {
+                None
+            } else {
+                Some(args.length("height")?)
+            };
+            Zoom::Center { center, height }
+        }
+    };
+    args.finish()?;
+    zoom(result)
+}
+
+pub(crate) fn parse_pan(
+    args: &mut ArgParser,
+    _dm: &mut DataModel,
+) -> Result<Box<dyn Command>, ParseError> {
+    let p1 = args.point("displacement")?;
+    let delta = if args.is_empty() {
+        p1
+    } else {
+        args.set_last_point(p1);
+        args.point("second point")? - p1
+    };
+    args.finish()?;
+    zoom(Zoom::Pan(delta))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CommandHandler, CommandLine};
+
+    fn zoom_of(line: &str) -> Result<Zoom, ParseError> {
+        let mut dm = DataModel::default();
+        let cmd = CommandLine::default().parse(&mut dm, line)?;
+        Ok(cmd.get_zoom().unwrap().clone())
+    }
+
+    #[test]
+    fn parse_zoom_and_pan() {
+        assert_eq!(zoom_of("zoom e").unwrap(), Zoom::Extents);
+        assert_eq!(zoom_of("Z 2").unwrap(), Zoom::Scale(2.0));
+        assert_eq!(
+            zoom_of("zoom window 0,0 @10,5").unwrap(),
+            Zoom::Window(Point2d::new(0.0, 0.0), Point2d::new(10.0, 5.0))
+        );
+        assert_eq!(
+            zoom_of("zoom c 5,5").unwrap(),
+            Zoom::Center {
+                center: Point2d::new(5.0, 5.0),
+                height: None
+            }
+        );
+        assert_eq!(
+            zoom_of("p 10,10 15,5").unwrap(),
+            Zoom::Pan(Point2d::new(5.0, -5.0))
+        );
+        assert_eq!(
+            zoom_of("zoom scale -1").unwrap_err(),
+            ParseError::new("The scale factor must be positive", 11)
+        );
+        assert_eq!(
+            zoom_of("zoom 0").unwrap_err(),
+            ParseError::new("The scale factor must be positive", 5)
+        );
+        assert_eq!(
+            zoom_of("Z -2").unwrap_err(),
+            ParseError::new("The scale factor must be positive", 2)
+        );
+        assert!(zoom_of("zoom all").is_err());
+    }
+
+    #[test]
+    fn zoom_extents_of_page() {
+        let mut dm = DataModel::default();
+        let mut cl = CommandLine::default();
+        let mut handler = CommandHandler::default();
+        let mut viewport = Viewport::new();
+        viewport.set_canvas_size(800.0, 400.0);
+
+        assert!(!Zoom::Extents.apply(&mut viewport, &dm));
+        for line in ["page p1", "line 0,0 100,100", "circle 100,50 50"] {
+            let cmd = cl.parse(&mut dm, line).unwrap();
+            handler.execute(&mut dm, cmd).unwrap();
+        }
+        let cmd = cl.parse(&mut dm, "zoom extents").unwrap();
+        assert!(cmd.get_zoom().unwrap().apply(&mut viewport, &dm));
+        // zooms are not undone
+        handler.execute(&mut dm, cmd).unwrap();
+        handler.undo(&mut dm).unwrap();
+        assert_eq!(dm.get_current_page().unwrap().get_node_ids().len(), 1);
+
+        let visible = viewport.get_visible_area();
+        assert!(visible.contains(&BBox::new(
+            Point2d::new(0.0, 0.0),
+            Point2d::new(150.0, 100.0)
+        )));
+        assert_eq!(viewport.get_center(), Point2d::new(75.0, 50.0));
+        assert!(Zoom::Previous.apply(&mut viewport, &dm));
+    }
+}
@@ -0,0 +1,67 @@
+//
+// cargo bench -p datamodel --bench document
+//
+// the sizes of the formats are printed before the timings
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use datamodel::{read_binary, read_document, write_binary, DataModel, Line, Page, Timestamp};
+
+const LINE_COUNT: usize = 100_000;
+
+// 100k lines on a 1000 x 100 raster, half of the coordinates have a fraction
+fn create_data_model() -> DataModel {
+    let mut dm = DataModel::default();
+    dm.insert_page(Page::new(
+        "page".to_string(),
+        "page".to_string(),
+        String::new(),
+    ));
+    for i in 0..LINE_COUNT {
+        let x = (i % 1000) as f64 * 10.0;
+        let y = (i / 1000) as f64 * 10.0;
+        let id = dm.next_id();
+        let mut line = Line::new(id.clone());
+        (line.x1, line.y1, line.x2, line.y2) = (x, y, x + 8.25, y + 7.125);
+        dm.insert_node(Box::new(line));
+        dm.add_node_to_current_page(&id);
+    }
+    dm
+}
+
+fn document(c: &mut Criterion) {
+    let dm = create_data_model();
+    let now = Timestamp::now();
+    let json = serde_json::to_vec(&dm.saved(now)).unwrap();
+    let binary = write_binary(&dm.saved(now), false).unwrap();
+    let compressed = write_binary(&dm.saved(now), true).unwrap();
+    println!(
+        "100k lines: json {} bytes, binary {} bytes, compressed {} bytes",
+        json.len(),
+        binary.len(),
+        compressed.len()
+    );
+
+    c.bench_function("write json of 100k lines", |b| {
+        b.iter(|| serde_json::to_vec(&dm.saved(now)).unwrap())
+    });
+    c.bench_function("write binary of 100k lines", |b| {
+        b.iter(|| write_binary(&dm.saved(now), false).unwrap())
+    });
+    c.bench_function("write compressed binary of 100k lines", |b| {
+        b.iter(|| write_binary(&dm.saved(now), true).unwrap())
+    });
+
+    let text = String::from_utf8(json).unwrap();
+    c.bench_function("read json of 100k lines", |b| {
+        b.iter(|| read_document(&text).unwrap())
+    });
+    c.bench_function("read binary of 100k lines", |b| {
+        b.iter(|| read_binary(&binary).unwrap())
+    });
+    c.bench_function("read compressed binary of 100k lines", |b| {
+        b.iter(|| read_binary(&compressed).unwrap())
+    });
+}
+
+criterion_group!(benches, document);
+criterion_main!(benches);
@@ -0,0 +1,68 @@
+//
+// cargo bench -p datamodel
+
+use algebra::{BBox, Point2d};
+use criterion::{criterion_group, criterion_main, Criterion};
+use datamodel::{DataModel, Line, Page};
+
+const LINE_COUNT: usize = 100_000;
+
+// 100k short lines on a 1000 x 100 raster
+fn create_data_model() -> DataModel {
+    let mut dm = DataModel::default();
+    dm.insert_page(Page::new(
+        "page".to_string(),
+        "page".to_string(),
+        String::new(),
+    ));
+    for i in 0..LINE_COUNT {
+        let x = (i % 1000) as f64 * 10.0;
+        let y = (i / 1000) as f64 * 10.0;
+        let id = format!("line_{}", i);
+        let mut line = Line::new(id.clone());
+        (line.x1, line.y1, line.x2, line.y2) = (x, y, x + 8.0, y + 8.0);
+        dm.insert_node(Box::new(line));
+        dm.add_node_to_current_page(&id);
+    }
+    dm
+}
+
+fn spatial_index(c: &mut Criterion) {
+    c.bench_function("insert 100k lines", |b| b.iter(create_data_model));
+
+    let dm = create_data_model();
+    let page = dm.get_current_page().unwrap();
+
+    // a viewport showing about 1% of the drawing
+    let visible_area = BBox::new(Point2d::new(4000.0, 400.0), Point2d::new(5000.0, 500.0));
+    c.bench_function("cull viewport of 100k lines", |b| {
+        b.iter(|| dm.query_nodes(page, &visible_area).len())
+    });
+    c.bench_function("cull viewport of 100k lines linear", |b| {
+        b.iter(|| {
+            page.get_node_ids()
+                .iter()
+                .filter_map(|id| dm.get_node(id))
+                .filter(
+                    |node| matches!(node.get_bbox(), Some(bbox) if bbox.intersects(&visible_area)),
+                )
+                .count()
+        })
+    });
+
+    let pt = Point2d::new(5004.0, 504.0);
+    c.bench_function("pick in 100k lines", |b| {
+        b.iter(|| dm.pick_node(page, &pt, 1.0))
+    });
+
+    let window = BBox::new(Point2d::new(100.0, 100.0), Point2d::new(300.0, 300.0));
+    c.bench_function("window selection in 100k lines", |b| {
+        b.iter(|| dm.select_window(page, &window).len())
+    });
+    c.bench_function("snap in 100k lines", |b| {
+        b.iter(|| dm.snap_to_node(page, &pt, 2.0))
+    });
+}
+
+criterion_group!(benches, spatial_index);
+criterion_main!(benches);
@@ -1,11 +1,15 @@
+use algebra::{arc_bbox, arc_intersects_bbox, arc_point, distance_to_arc, BBox, Point2d};
 use serde::{Deserialize, Serialize};
 
+use crate::id::NodeId;
 use crate::node::{Node, NodeType};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Arc {
     node_type: NodeType,
-    id: String,
+    id: NodeId,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    layer: String,
     x: f64,
     y: f64,
     r: f64,
@@ -13,8 +17,11 @@ pub struct Arc {
     angle_end: f64,
 }
 impl Node for Arc {
-    fn get_id(&self) -> &str {
-        self.id.as_str()
+    fn get_id(&self) -> &NodeId {
+        &self.id
+    }
+    fn set_id(&mut self, id: NodeId) {
+        self.id = id;
     }
 
     fn get_node_type(&self) -> &NodeType {
@@ -27,13 +34,74 @@ impl Node for Arc {
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
         self
     }
+    fn clone_node(&self) -> Box<dyn Node> {
+        Box::new(self.clone())
+    }
+
+    fn get_layer(&self) -> &str {
+        &self.layer
+    }
+    fn set_layer(&mut self, layer: &str) {
+        self.layer = layer.to_string();
+    }
+
+    fn get_bbox(&self) -> Option<BBox> {
+        Some(arc_bbox(
+            &self.get_center(),
+            self.r,
+            self.angle_start,
+            self.angle_end,
+        ))
+    }
+
+    fn distance(&self, pt: &Point2d) -> Option<f64> {
+        Some(distance_to_arc(
+            pt,
+            &self.get_center(),
+            self.r,
+            self.angle_start,
+            self.angle_end,
+        ))
+    }
+
+    fn intersects(&self, bbox: &BBox) -> bool {
+        arc_intersects_bbox(
+            &self.get_center(),
+            self.r,
+            self.angle_start,
+            self.angle_end,
+            bbox,
+        )
+    }
+
+    fn get_snap_points(&self) -> Vec<Point2d> {
+        let center = self.get_center();
+        vec![
+            center,
+            arc_point(&center, self.r, self.angle_start),
+            arc_point(&center, self.r, self.angle_end),
+        ]
+    }
+
+    fn translate(&mut self, delta: &Point2d) {
+        self.x += delta.x;
+        self.y += delta.y;
+    }
 }
 
 impl Arc {
-    pub fn new(id: String, x: f64, y: f64, r: f64, angle_start: f64, angle_end: f64) -> Self {
+    pub fn new(
+        id: impl Into<NodeId>,
+        x: f64,
+        y: f64,
+        r: f64,
+        angle_start: f64,
+        angle_end: f64,
+    ) -> Self {
         Arc {
             node_type: NodeType::Arc,
-            id,
+            id: id.into(),
+            layer: String::new(),
             x,
             y,
             r,
@@ -41,4 +109,17 @@ impl Arc {
             angle_end,
         }
     }
+
+    pub fn get_center(&self) -> Point2d {
+        Point2d::new(self.x, self.y)
+    }
+    pub fn get_r(&self) -> f64 {
+        self.r
+    }
+    pub fn get_angle_start(&self) -> f64 {
+        self.angle_start
+    }
+    pub fn get_angle_end(&self) -> f64 {
+        self.angle_end
+    }
 }
@@ -0,0 +1,750 @@
+//
+// a compact binary encoding of the json document, e.g. datamodel.dmb
+//
+//   "DMB" version:u8 flags:u8 length:varint payload
+//   payload: strings shapes value
+//   strings: count:varint (length:varint utf8)*
+//   shapes:  count:varint (count:varint string:varint*)*
+//
+// the value is the json document, so a binary document is migrated and
+// read like json and converts back to the same json. strings (ids, layers,
+// keys) are stored once and referenced by index, an object refers to its
+// shape, the list of its keys, e.g. one for all lines. numbers are varints,
+// floats without fraction as well, others little endian f64. with
+// COMPRESSED the payload is zlib compressed
+//
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use serde::ser::{self, Serialize};
+use serde_json::{Map, Number, Value};
+
+use crate::datamodel::DataModel;
+use crate::document::{read_document, read_document_value, Document};
+
+const MAGIC: &[u8; 3] = b"DMB";
+
+pub const BINARY_VERSION: u8 = 1;
+
+// flags
+const COMPRESSED: u8 = 1;
+
+// of arrays and objects, deeper documents are rejected
+const MAX_DEPTH: usize = 64;
+
+const NULL: u8 = 0;
+const FALSE: u8 = 1;
+const TRUE: u8 = 2;
+const UNSIGNED: u8 = 3;
+// -1 - n
+const NEGATIVE: u8 = 4;
+const FLOAT: u8 = 5;
+const STRING: u8 = 6;
+// count, values
+const ARRAY: u8 = 7;
+// shape, values
+const OBJECT: u8 = 8;
+// zigzag encoded
+const WHOLE_FLOAT: u8 = 9;
+
+pub fn write_binary(document: &Document, compress: bool) -> Result<Vec<u8>, String> {
+    let mut writer = Writer::default();
+    document
+        .serialize(&mut writer)
+        .map_err(|err| err.to_string())?;
+
+    let mut payload = vec![];
+    write_varint(&mut payload, writer.strings.len() as u64);
+    for text in &writer.strings {
+        write_varint(&mut payload, text.len() as u64);
+        payload.extend_from_slice(text.as_bytes());
+    }
+    write_varint(&mut payload, writer.shapes.len() as u64);
+    for keys in &writer.shapes {
+        write_varint(&mut payload, keys.len() as u64);
+        for key in keys {
+            write_varint(&mut payload, *key);
+        }
+    }
+    payload.extend_from_slice(&writer.body);
+    if compress {
+        let mut zlib = ZlibEncoder::new(vec![], Compression::fast());
+        zlib.write_all(&payload).map_err(|err| err.to_string())?;
+        payload = zlib.finish().map_err(|err| err.to_string())?;
+    }
+
+    let mut bytes = MAGIC.to_vec();
+    bytes.push(BINARY_VERSION);
+    bytes.push(if compress { COMPRESSED } else { 0 });
+    write_varint(&mut bytes, payload.len() as u64);
+    bytes.extend_from_slice(&payload);
+    Ok(bytes)
+}
+
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+// a data model from a binary document of any format version
+pub fn read_binary(bytes: &[u8]) -> Result<DataModel, String> {
+    read_document_value(binary_to_json(bytes)?)
+}
+
+// a json or binary document, e.g. a file that was saved in either format
+pub fn read_document_bytes(bytes: &[u8]) -> Result<DataModel, String> {
+    match is_binary(bytes) {
+        true => read_binary(bytes),
+        false => read_document(std::str::from_utf8(bytes).map_err(|err| err.to_string())?),
+    }
+}
+
+// the json document of a binary document
+pub fn binary_to_json(bytes: &[u8]) -> Result<Value, String> {
+    if !is_binary(bytes) || bytes.len() < 5 {
+        return Err("Not a binary document".to_string());
+    }
+    let (version, flags) = (bytes[3], bytes[4]);
+    if version != BINARY_VERSION {
+        return Err(format!(
+            "The binary document has version {}, this app reads version {}",
+            version, BINARY_VERSION
+        ));
+    }
+    let mut header = Decoder::new(&bytes[5..]);
+    let length = header.varint()? as usize;
+    let payload = header.take(length)?;
+    if !header.rest.is_empty() {
+        return Err("Unexpected data after the binary document".to_string());
+    }
+    let inflated;
+    let payload = if flags & COMPRESSED != 0 {
+        let mut bytes = vec![];
+        ZlibDecoder::new(payload)
+            .read_to_end(&mut bytes)
+            .map_err(|err| format!("Can not decompress the binary document: {}", err))?;
+        inflated = bytes;
+        &inflated[..]
+    } else {
+        payload
+    };
+
+    let mut decoder = Decoder::new(payload);
+    for _ in 0..decoder.varint()? {
+        let length = decoder.varint()? as usize;
+        let text = std::str::from_utf8(decoder.take(length)?)
+            .map_err(|_| "Invalid string in the binary document".to_string())?;
+        decoder.strings.push(text.to_string());
+    }
+    for _ in 0..decoder.varint()? {
+        let keys = (0..decoder.varint()?)
+            .map(|_| decoder.string())
+            .collect::<Result<_, _>>()?;
+        decoder.shapes.push(keys);
+    }
+    let value = decoder.value(0)?;
+    if !decoder.rest.is_empty() {
+        return Err("Unexpected data after the binary document".to_string());
+    }
+    Ok(value)
+}
+
+#[derive(Debug)]
+pub struct BinaryError(String);
+
+impl std::fmt::Display for BinaryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for BinaryError {}
+
+impl ser::Error for BinaryError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        BinaryError(msg.to_string())
+    }
+}
+
+// a serde serializer that writes the value serde_json::to_value would give,
+// the body refers to the strings and shapes in the order they were found
+#[derive(Default)]
+struct Writer {
+    strings: Vec<String>,
+    string_indexes: HashMap<String, u64>,
+    shapes: Vec<Vec<u64>>,
+    shape_indexes: HashMap<Vec<u64>, u64>,
+    // of the open objects, the innermost last
+    keys: Vec<u64>,
+    body: Vec<u8>,
+}
+
+impl Writer {
+    fn string(&mut self, text: &str) -> u64 {
+        if let Some(index) = self.string_indexes.get(text) {
+            return *index;
+        }
+        let index = self.strings.len() as u64;
+        self.strings.push(text.to_string());
+        self.string_indexes.insert(text.to_string(), index);
+        index
+    }
+
+    // of the keys from start on, they are removed
+    fn shape(&mut self, start: usize) -> u64 {
+        let keys = &self.keys[start..];
+        let index = match self.shape_indexes.get(keys) {
+            Some(index) => *index,
+            None => {
+                let index = self.shapes.len() as u64;
+                self.shapes.push(keys.to_vec());
+                self.shape_indexes.insert(keys.to_vec(), index);
+                index
+            }
+        };
+        self.keys.truncate(start);
+        index
+    }
+
+    fn write_string(&mut self, text: &str) {
+        let index = self.string(text);
+        self.body.push(STRING);
+        write_varint(&mut self.body, index);
+    }
+
+    fn write_float(&mut self, n: f64) {
+        let whole = n as i64;
+        if !n.is_finite() {
+            // as in json
+            self.body.push(NULL);
+        } else if whole as f64 == n && !(n == 0.0 && n.is_sign_negative()) {
+            self.body.push(WHOLE_FLOAT);
+            write_varint(&mut self.body, ((whole << 1) ^ (whole >> 63)) as u64);
+        } else {
+            self.body.push(FLOAT);
+            self.body.extend_from_slice(&n.to_le_bytes());
+        }
+    }
+
+    // the count or shape is known at the end
+    fn begin(&mut self, tag: u8) -> Compound<'_> {
+        self.body.push(tag);
+        let start = self.body.len();
+        let keys = self.keys.len();
+        Compound {
+            writer: self,
+            start,
+            count: 0,
+            keys,
+        }
+    }
+
+    // {variant: value} as serde_json writes enums
+    fn begin_variant(&mut self, variant: &str) -> usize {
+        self.body.push(OBJECT);
+        let key = self.string(variant);
+        self.keys.push(key);
+        let shape = self.shape(self.keys.len() - 1);
+        write_varint(&mut self.body, shape);
+        self.body.len()
+    }
+}
+
+struct Compound<'a> {
+    writer: &'a mut Writer,
+    // of the values
+    start: usize,
+    count: u64,
+    // the start of the keys
+    keys: usize,
+}
+
+impl Compound<'_> {
+    fn end_array(self) {
+        let mut count = vec![];
+        write_varint(&mut count, self.count);
+        self.writer.body.splice(self.start..self.start, count);
+    }
+
+    fn end_object(self) {
+        let shape = self.writer.shape(self.keys);
+        let mut header = vec![];
+        write_varint(&mut header, shape);
+        self.writer.body.splice(self.start..self.start, header);
+    }
+
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), BinaryError> {
+        self.count += 1;
+        value.serialize(&mut *self.writer)
+    }
+
+    fn field<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), BinaryError> {
+        let key = self.writer.string(key);
+        self.writer.keys.push(key);
+        value.serialize(&mut *self.writer)
+    }
+}
+
+impl<'a> ser::Serializer for &'a mut Writer {
+    type Ok = ();
+    type Error = BinaryError;
+    type SerializeSeq = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = Compound<'a>;
+    type SerializeMap = Compound<'a>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = Compound<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), BinaryError> {
+        self.body.push(if v { TRUE } else { FALSE });
+        Ok(())
+    }
+    fn serialize_i8(self, v: i8) -> Result<(), BinaryError> {
+        self.serialize_i64(v as i64)
+    }
+    fn serialize_i16(self, v: i16) -> Result<(), BinaryError> {
+        self.serialize_i64(v as i64)
+    }
+    fn serialize_i32(self, v: i32) -> Result<(), BinaryError> {
+        self.serialize_i64(v as i64)
+    }
+    fn serialize_i64(self, v: i64) -> Result<(), BinaryError> {
+        if v >= 0 {
+            return self.serialize_u64(v as u64);
+        }
+        self.body.push(NEGATIVE);
+        write_varint(&mut self.body, !(v as u64));
+        Ok(())
+    }
+    fn serialize_u8(self, v: u8) -> Result<(), BinaryError> {
+        self.serialize_u64(v as u64)
+    }
+    fn serialize_u16(self, v: u16) -> Result<(), BinaryError> {
+        self.serialize_u64(v as u64)
+    }
+    fn serialize_u32(self, v: u32) -> Result<(), BinaryError> {
+        self.serialize_u64(v as u64)
+    }
+    fn serialize_u64(self, v: u64) -> Result<(), BinaryError> {
+        self.body.push(UNSIGNED);
+        write_varint(&mut self.body, v);
+        Ok(())
+    }
+    fn serialize_f32(self, v: f32) -> Result<(), BinaryError> {
+        self.write_float(v as f64);
+        Ok(())
+    }
+    fn serialize_f64(self, v: f64) -> Result<(), BinaryError> {
+        self.write_float(v);
+        Ok(())
+    }
+    fn serialize_char(self, v: char) -> Result<(), BinaryError> {
+        self.write_string(v.encode_utf8(&mut [0; 4]));
+        Ok(())
+    }
+    fn serialize_str(self, v: &str) -> Result<(), BinaryError> {
+        self.write_string(v);
+        Ok(())
+    }
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), BinaryError> {
+        let mut array = self.begin(ARRAY);
+        for byte in v {
+            array.element(byte)?;
+        }
+        array.end_array();
+        Ok(())
+    }
+    fn serialize_none(self) -> Result<(), BinaryError> {
+        self.body.push(NULL);
+        Ok(())
+    }
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), BinaryError> {
+        value.serialize(self)
+    }
+    fn serialize_unit(self) -> Result<(), BinaryError> {
+        self.body.push(NULL);
+        Ok(())
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), BinaryError> {
+        self.serialize_unit()
+    }
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<(), BinaryError> {
+        self.serialize_str(variant)
+    }
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), BinaryError> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), BinaryError> {
+        self.begin_variant(variant);
+        value.serialize(self)
+    }
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'a>, BinaryError> {
+        Ok(self.begin(ARRAY))
+    }
+    fn serialize_tuple(self, _len: usize) -> Result<Compound<'a>, BinaryError> {
+        Ok(self.begin(ARRAY))
+    }
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, BinaryError> {
+        Ok(self.begin(ARRAY))
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, BinaryError> {
+        self.begin_variant(variant);
+        Ok(self.begin(ARRAY))
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'a>, BinaryError> {
+        Ok(self.begin(OBJECT))
+    }
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, BinaryError> {
+        Ok(self.begin(OBJECT))
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, BinaryError> {
+        self.begin_variant(variant);
+        Ok(self.begin(OBJECT))
+    }
+}
+
+impl ser::SerializeSeq for Compound<'_> {
+    type Ok = ();
+    type Error = BinaryError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), BinaryError> {
+        self.element(value)
+    }
+    fn end(self) -> Result<(), BinaryError> {
+        self.end_array();
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for Compound<'_> {
+    type Ok = ();
+    type Error = BinaryError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), BinaryError> {
+        self.element(value)
+    }
+    fn end(self) -> Result<(), BinaryError> {
+        self.end_array();
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for Compound<'_> {
+    type Ok = ();
+    type Error = BinaryError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), BinaryError> {
+        self.element(value)
+    }
+    fn end(self) -> Result<(), BinaryError> {
+        self.end_array();
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleVariant for Compound<'_> {
+    type Ok = ();
+    type Error = BinaryError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), BinaryError> {
+        self.element(value)
+    }
+    fn end(self) -> Result<(), BinaryError> {
+        self.end_array();
+        Ok(())
+    }
+}
+
+// the keys must be strings as in json
+impl ser::SerializeMap for Compound<'_> {
+    type Ok = ();
+    type Error = BinaryError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), BinaryError> {
+        let key = serde_json::to_value(key).map_err(ser::Error::custom)?;
+        let key = match key {
+            Value::String(key) => key,
+            Value::Number(number) => number.to_string(),
+            Value::Bool(b) => b.to_string(),
+            _ => return Err(ser::Error::custom("The key of a map must be a string")),
+        };
+        let key = self.writer.string(&key);
+        self.writer.keys.push(key);
+        Ok(())
+    }
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), BinaryError> {
+        value.serialize(&mut *self.writer)
+    }
+    fn end(self) -> Result<(), BinaryError> {
+        self.end_object();
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for Compound<'_> {
+    type Ok = ();
+    type Error = BinaryError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), BinaryError> {
+        self.field(key, value)
+    }
+    fn end(self) -> Result<(), BinaryError> {
+        self.end_object();
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for Compound<'_> {
+    type Ok = ();
+    type Error = BinaryError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), BinaryError> {
+        self.field(key, value)
+    }
+    fn end(self) -> Result<(), BinaryError> {
+        self.end_object();
+        Ok(())
+    }
+}
+
+struct Decoder<'a> {
+    rest: &'a [u8],
+    strings: Vec<String>,
+    shapes: Vec<Vec<String>>,
+}
+
+impl<'a> Decoder<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Decoder {
+            rest: bytes,
+            strings: vec![],
+            shapes: vec![],
+        }
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'a [u8], String> {
+        if length > self.rest.len() {
+            return Err("Unexpected end of the binary document".to_string());
+        }
+        let (bytes, rest) = self.rest.split_at(length);
+        self.rest = rest;
+        Ok(bytes)
+    }
+
+    // 7 bits per byte, the least significant first
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut result = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            result |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+        }
+        Err("Invalid number in the binary document".to_string())
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let index = self.varint()?;
+        self.strings
+            .get(index as usize)
+            .cloned()
+            .ok_or_else(|| format!("Invalid string {} in the binary document", index))
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, String> {
+        if depth > MAX_DEPTH {
+            return Err("The binary document is nested too deep".to_string());
+        }
+        let value = match self.take(1)?[0] {
+            NULL => Value::Null,
+            FALSE => Value::Bool(false),
+            TRUE => Value::Bool(true),
+            UNSIGNED => Value::Number(self.varint()?.into()),
+            NEGATIVE => Value::Number((!self.varint()? as i64).into()),
+            FLOAT => {
+                let bytes = self.take(8)?.try_into().unwrap_or_default();
+                Number::from_f64(f64::from_le_bytes(bytes))
+                    .map(Value::Number)
+                    .ok_or("Invalid number in the binary document")?
+            }
+            WHOLE_FLOAT => {
+                let n = self.varint()?;
+                let whole = (n >> 1) as i64 ^ -((n & 1) as i64);
+                Number::from_f64(whole as f64)
+                    .map(Value::Number)
+                    .ok_or("Invalid number in the binary document")?
+            }
+            STRING => Value::String(self.string()?),
+            ARRAY => {
+                let length = self.varint()? as usize;
+                // every value has at least one byte
+                let mut values = Vec::with_capacity(length.min(self.rest.len()));
+                for _ in 0..length {
+                    values.push(self.value(depth + 1)?);
+                }
+                Value::Array(values)
+            }
+            OBJECT => {
+                let shape = self.varint()? as usize;
+                let keys = self
+                    .shapes
+                    .get(shape)
+                    .cloned()
+                    .ok_or_else(|| format!("Invalid shape {} in the binary document", shape))?;
+                let mut map = Map::new();
+                for key in keys {
+                    let value = self.value(depth + 1)?;
+                    map.insert(key, value);
+                }
+                Value::Object(map)
+            }
+            tag => return Err(format!("Invalid value {} in the binary document", tag)),
+        };
+        Ok(value)
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        bytes.push((n as u8 & 0x7f) | 0x80);
+        n >>= 7;
+    }
+    bytes.push(n as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Arc, Line, Node, Page, Text, Timestamp};
+    use algebra::Point2d;
+
+    fn sample() -> DataModel {
+        let mut dm = DataModel::default();
+        dm.insert_page(Page::new("1", "plan".into(), "ground floor".into()));
+        for i in 0..20 {
+            let mut line = Line::new(format!("l{}", i));
+            (line.x1, line.y1, line.x2, line.y2) = (i as f64 * 0.1, -1.5, 1e300, -0.0);
+            line.set_layer("Walls");
+            dm.insert_node(Box::new(line));
+            dm.add_node_to_page("1", &format!("l{}", i));
+        }
+        dm.insert_node(Box::new(Arc::new("a", 1.0, 2.0, 3.0, 0.0, 90.0)));
+        let text = Text::new("t", Point2d::new(0.0, 0.0), 2.5, "äöü €".into());
+        dm.insert_node(Box::new(text));
+        dm
+    }
+
+    fn snapshot(dm: &DataModel, now: Timestamp) -> Value {
+        serde_json::to_value(dm.saved(now)).unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        let dm = sample();
+        let now = Timestamp::from_unix_seconds(1_800_000_000);
+        let json = serde_json::to_value(dm.saved(now)).unwrap();
+        for compress in [false, true] {
+            let bytes = write_binary(&dm.saved(now), compress).unwrap();
+            assert_eq!(&bytes[..5], &[b'D', b'M', b'B', 1, compress as u8]);
+            assert_eq!(binary_to_json(&bytes).unwrap(), json);
+            let read = read_binary(&bytes).unwrap();
+            assert_eq!(snapshot(&read, now), snapshot(&dm, now));
+        }
+
+        let text = serde_json::to_string(&json).unwrap();
+        let plain = write_binary(&dm.saved(now), false).unwrap();
+        let compressed = write_binary(&dm.saved(now), true).unwrap();
+        // most coordinates have a fraction
+        assert!(plain.len() < text.len() * 2 / 3);
+        assert!(compressed.len() < plain.len());
+        assert_eq!(
+            snapshot(&read_document_bytes(text.as_bytes()).unwrap(), now),
+            snapshot(&read_document_bytes(&compressed).unwrap(), now)
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        let dm = sample();
+        let bytes = write_binary(&dm.saved(Timestamp::now()), false).unwrap();
+        assert_eq!(read_binary(b"{}").unwrap_err(), "Not a binary document");
+        assert_eq!(
+            read_binary(b"DMB\x02\x00\x00").unwrap_err(),
+            "The binary document has version 2, this app reads version 1"
+        );
+        assert_eq!(
+            read_binary(&bytes[..bytes.len() - 1]).unwrap_err(),
+            "Unexpected end of the binary document"
+        );
+        assert!(read_binary(b"DMB\x01\x01\x02xx").is_err());
+        // no strings, no shapes and 100 nested arrays
+        let mut payload = vec![0, 0];
+        for i in 0..100 {
+            payload.extend([ARRAY, (i < 99) as u8]);
+        }
+        let mut nested = b"DMB\x01\x00".to_vec();
+        write_varint(&mut nested, payload.len() as u64);
+        nested.extend(payload);
+        assert_eq!(
+            read_binary(&nested).unwrap_err(),
+            "The binary document is nested too deep"
+        );
+    }
+
+    #[test]
+    fn varint() {
+        for n in [0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+            let mut bytes = vec![];
+            write_varint(&mut bytes, n);
+            assert_eq!(Decoder::new(&bytes).varint(), Ok(n));
+        }
+    }
+}
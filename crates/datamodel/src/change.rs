@@ -0,0 +1,158 @@
+//
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::id::NodeId;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(tag = "type", content = "id")]
+pub enum Change {
+    NodeAdded(NodeId),
+    NodeRemoved(NodeId),
+    NodeModified(NodeId),
+    PageAdded(NodeId),
+    PageRemoved(NodeId),
+    // the page has another position in the page order
+    PageMoved(NodeId),
+    CurrentPageChanged(NodeId),
+    // id of the page whose selection changed
+    SelectionChanged(NodeId),
+    // id of the page whose named views changed
+    NamedViewsChanged(NodeId),
+    DrawingAidsChanged,
+    UcsChanged,
+}
+
+pub type Observer = Box<dyn Fn(&[Change])>;
+
+// collects the changes of a batch (e.g. one command) and
+// hands them to the observers when the batch ends
+#[derive(Default)]
+pub(crate) struct ChangeNotifier {
+    observers: Vec<Observer>,
+    // in the order of the first change, each change once
+    pending: Vec<Change>,
+    pending_set: HashSet<Change>,
+    batch_depth: u32,
+}
+
+impl std::fmt::Debug for ChangeNotifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChangeNotifier")
+            .field("observers", &self.observers.len())
+            .field("pending", &self.pending)
+            .field("batch_depth", &self.batch_depth)
+            .finish()
+    }
+}
+
+impl ChangeNotifier {
+    pub(crate) fn add_observer(&mut self, observer: Observer) {
+        self.observers.push(observer);
+    }
+
+    pub(crate) fn begin_batch(&mut self) {
+        self.batch_depth += 1;
+    }
+
+    pub(crate) fn end_batch(&mut self) {
+        self.batch_depth = self.batch_depth.saturating_sub(1);
+        if self.batch_depth == 0 {
+            self.flush();
+        }
+    }
+
+    pub(crate) fn notify(&mut self, change: Change) {
+        if self.pending_set.insert(change.clone()) {
+            self.pending.push(change);
+        }
+        if self.batch_depth == 0 {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let changes = std::mem::take(&mut self.pending);
+        self.pending_set.clear();
+        for observer in &self.observers {
+            observer(&changes);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::{DataModel, Line, Page};
+
+    fn observe(dm: &mut DataModel) -> Rc<RefCell<Vec<Vec<Change>>>> {
+        let received = Rc::new(RefCell::new(vec![]));
+        let sink = received.clone();
+        dm.add_observer(Box::new(move |changes: &[Change]| {
+            sink.borrow_mut().push(changes.to_vec())
+        }));
+        received
+    }
+
+    #[test]
+    fn single_changes() {
+        let mut dm = DataModel::default();
+        let received = observe(&mut dm);
+
+        dm.insert_page(Page::new(
+            "p".to_string(),
+            "page".to_string(),
+            String::new(),
+        ));
+        dm.set_selection(vec![]);
+
+        assert_eq!(
+            *received.borrow(),
+            vec![
+                vec![Change::PageAdded("p".into())],
+                vec![Change::CurrentPageChanged("p".into())],
+                vec![Change::SelectionChanged("p".into())],
+            ]
+        );
+    }
+
+    #[test]
+    fn batch() {
+        let mut dm = DataModel::default();
+        dm.insert_page(Page::new(
+            "p".to_string(),
+            "page".to_string(),
+            String::new(),
+        ));
+        let received = observe(&mut dm);
+
+        dm.begin_batch();
+        dm.insert_node(Box::new(Line::new("l".to_string())));
+        dm.add_node_to_current_page("l");
+        dm.update_node("l", |_| {});
+        dm.update_node("l", |_| {});
+        assert!(received.borrow().is_empty());
+        dm.end_batch();
+
+        assert_eq!(
+            *received.borrow(),
+            vec![vec![
+                Change::NodeAdded("l".into()),
+                Change::NodeModified("l".into()),
+            ]]
+        );
+
+        dm.remove_node("l");
+        dm.remove_node("l");
+        assert_eq!(received.borrow()[1], vec![Change::NodeRemoved("l".into())]);
+        assert_eq!(received.borrow().len(), 2);
+    }
+}
@@ -1,50 +1,284 @@
+use algebra::{DrawingAids, Ucs, View};
 use itertools::Itertools;
-use serde::ser::{SerializeStruct, Serializer};
+use serde::ser::Serializer;
 use serde::Serialize;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 // use crate::command::Command;
+use crate::change::{Change, ChangeNotifier, Observer};
+use crate::document::{Document, DocumentInfo};
+use crate::id::{ClientId, IdCounter, NodeId};
 use crate::node::Node;
 use crate::page::Page;
-
-#[derive(Debug, Default)]
-struct IdCounter {
-    counter: u64,
-}
-impl IdCounter {
-    fn next(&mut self) -> String {
-        self.counter += 1;
-        self.counter.to_string()
-    }
-}
+use crate::spatialindex::SpatialIndex;
 
 #[derive(Debug, Default)]
 pub struct DataModel {
-    current_page_id: String,
-    pages: HashMap<String, Page>,
-    nodes: HashMap<String, Box<dyn Node>>,
-    // #[serde(skip_serializing)]
+    // persistent, written to the document
+    info: DocumentInfo,
+    pages: HashMap<NodeId, Page>,
+    // the order of the pages as the user arranged them
+    page_order: Vec<NodeId>,
+    nodes: HashMap<NodeId, Box<dyn Node>>,
     id_counter: IdCounter,
+
+    // session state
+    current_page_id: NodeId,
+    drawing_aids: DrawingAids,
+    // points typed in commands are given in the ucs
+    ucs: Ucs,
+    // one spatial index per page, key is the page id
+    indexes: HashMap<NodeId, SpatialIndex>,
+    notifier: ChangeNotifier,
 }
 impl DataModel {
-    pub fn next_id(&mut self) -> String {
+    pub fn next_id(&mut self) -> NodeId {
         self.id_counter.next()
     }
+    pub fn get_id_counter(&self) -> u64 {
+        self.id_counter.get()
+    }
+    // e.g. the counter of a document, ids up to it are not given out
+    pub(crate) fn reserve_ids(&mut self, counter: u64) {
+        self.id_counter.set(self.id_counter.get().max(counter));
+    }
+    // the ids given out after the counter were not used, e.g. by a command
+    // that failed to parse, they are given out again
+    pub fn release_ids(&mut self, counter: u64) {
+        if counter < self.id_counter.get() {
+            self.id_counter.set(counter);
+            let ids = self
+                .nodes
+                .keys()
+                .chain(self.pages.keys())
+                .cloned()
+                .collect_vec();
+            for id in ids {
+                self.id_counter.reserve(&id);
+            }
+        }
+    }
+
+    pub fn get_client_id(&self) -> Option<&ClientId> {
+        self.id_counter.get_client()
+    }
+    // the ids of a session start with its client id, e.g. ClientId::random(),
+    // so they do not collide with the ids of other sessions
+    pub fn set_client_id(&mut self, client: Option<ClientId>) {
+        self.id_counter.set_client(client);
+        let ids = self
+            .nodes
+            .keys()
+            .chain(self.pages.keys())
+            .cloned()
+            .collect_vec();
+        for id in ids {
+            self.id_counter.reserve(&id);
+        }
+    }
+
+    // new ids for nodes of another document before they are inserted,
+    // e.g. pasted or imported nodes
+    pub fn reidentify(&mut self, nodes: &mut [Box<dyn Node>]) {
+        for node in nodes {
+            node.set_id(self.id_counter.next());
+        }
+    }
+
+    pub fn get_info(&self) -> &DocumentInfo {
+        &self.info
+    }
+    pub fn set_info(&mut self, info: DocumentInfo) {
+        self.info = info;
+    }
 
     pub fn insert_page(&mut self, page: Page) {
-        self.current_page_id = page.get_id().to_string();
-        self.pages.insert(page.get_id().to_string(), page);
+        self.id_counter.reserve(page.get_id());
+        let index = self.build_index(&page);
+        self.indexes.insert(page.get_id().clone(), index);
+
+        let id = page.get_id().clone();
+        if self.pages.insert(id.clone(), page).is_none() {
+            self.page_order.push(id.clone());
+        }
+        self.notifier.notify(Change::PageAdded(id.clone()));
+        self.set_current_page(&id);
     }
     pub fn remove_page(&mut self, id: &str) {
-        self.pages.remove(id);
+        if self.pages.remove(id).is_some() {
+            self.page_order.retain(|page_id| page_id != id);
+            self.indexes.remove(id);
+            self.notifier.notify(Change::PageRemoved(NodeId::from(id)));
+        }
+    }
+
+    // moves the page to the position in the page order, returns the old position
+    pub fn move_page(&mut self, id: &str, position: usize) -> Option<usize> {
+        let old = self.page_order.iter().position(|page_id| page_id == id)?;
+        let page_id = self.page_order.remove(old);
+        let position = position.min(self.page_order.len());
+        self.page_order.insert(position, page_id);
+        if position != old {
+            self.notifier.notify(Change::PageMoved(NodeId::from(id)));
+        }
+        Some(old)
+    }
+
+    pub fn set_current_page(&mut self, id: &str) {
+        if self.current_page_id != id {
+            self.current_page_id = NodeId::from(id);
+            self.notifier
+                .notify(Change::CurrentPageChanged(NodeId::from(id)));
+        }
     }
 
     pub fn insert_node(&mut self, node: Box<dyn Node>) {
-        self.nodes.insert(node.get_id().to_string(), node);
+        let id = node.get_id().clone();
+        self.id_counter.reserve(&id);
+        self.nodes.insert(id.clone(), node);
+        // a new node is on no page yet
+        self.update_index(&id);
+        self.notifier.notify(Change::NodeAdded(id));
     }
+    // removes the node from its page as well
     pub fn remove_node(&mut self, id: &str) {
-        self.nodes.remove(id);
+        if self.nodes.remove(id).is_none() {
+            return;
+        }
+        for page in self.pages.values_mut() {
+            page.remove_node_id(id);
+        }
+        for index in self.indexes.values_mut() {
+            index.remove(id);
+        }
+        self.notifier.notify(Change::NodeRemoved(NodeId::from(id)));
+    }
+
+    pub fn add_node_to_page(&mut self, page_id: &str, node_id: &str) {
+        let bbox = self.get_node(node_id).and_then(|node| node.get_bbox());
+        if let Some(page) = self.pages.get_mut(page_id) {
+            page.add_node_id(NodeId::from(node_id));
+            let index = self.indexes.entry(NodeId::from(page_id)).or_default();
+            if let Some(bbox) = bbox {
+                index.insert(node_id, &bbox);
+            }
+        }
+    }
+    pub fn add_node_to_current_page(&mut self, node_id: &str) {
+        let page_id = self.current_page_id.clone();
+        self.add_node_to_page(&page_id, node_id);
+    }
+    // e.g. undo of erase, the node gets its old draw order back
+    pub fn insert_node_to_page(&mut self, page_id: &str, node_id: &str, position: usize) {
+        let Some(page) = self.pages.get_mut(page_id) else {
+            return;
+        };
+        let position = position.min(page.get_node_ids().len());
+        page.insert_node_id(position, NodeId::from(node_id));
+        self.index_in_draw_order(page_id, node_id, position);
+    }
+
+    // exchange a node with the same id, returns the old node
+    pub fn replace_node(&mut self, node: Box<dyn Node>) -> Option<Box<dyn Node>> {
+        let id = node.get_id().clone();
+        let old = self.nodes.get_mut(&id)?;
+        let old = std::mem::replace(old, node);
+        self.reindex_node(&id);
+        self.notifier.notify(Change::NodeModified(id));
+        Some(old)
+    }
+
+    // modify a node and keep the spatial index up to date
+    pub fn update_node<F>(&mut self, id: &str, f: F) -> bool
+    where
+        F: FnOnce(&mut dyn Node),
+    {
+        match self.nodes.get_mut(id) {
+            Some(node) => {
+                f(node.as_mut());
+                self.reindex_node(id);
+                self.notifier.notify(Change::NodeModified(NodeId::from(id)));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn get_spatial_index(&self, page_id: &str) -> Option<&SpatialIndex> {
+        self.indexes.get(page_id)
+    }
+
+    // the draw order in the index follows the node ids of the page
+    fn build_index(&self, page: &Page) -> SpatialIndex {
+        SpatialIndex::bulk_load(page.get_node_ids().iter().filter_map(|id| {
+            let bbox = self.get_node(id).and_then(|node| node.get_bbox())?;
+            Some((id.as_str(), bbox))
+        }))
+    }
+
+    // the node in the index of the page at its position in the draw order,
+    // between the orders of the nodes drawn before and after it
+    fn index_in_draw_order(&mut self, page_id: &str, node_id: &str, position: usize) {
+        let bbox = self.get_node(node_id).and_then(|node| node.get_bbox());
+        let (Some(bbox), Some(page)) = (bbox, self.pages.get(page_id)) else {
+            return;
+        };
+        let index = self.indexes.entry(NodeId::from(page_id)).or_default();
+        let ids = page.get_node_ids();
+        let lower = ids[..position].iter().rev().find_map(|id| index.order(id));
+        let upper = ids[position + 1..].iter().find_map(|id| index.order(id));
+        let Some(upper) = upper else {
+            index.insert(node_id, &bbox);
+            return;
+        };
+        match SpatialIndex::order_between(lower.unwrap_or(0), upper) {
+            Some(order) => index.insert_ordered(node_id, &bbox, order),
+            None => {
+                let index = self.build_index(page);
+                self.indexes.insert(NodeId::from(page_id), index);
+            }
+        }
+    }
+
+    // returns false if the node is in no index
+    fn update_index(&mut self, id: &str) -> bool {
+        let bbox = self.get_node(id).and_then(|node| node.get_bbox());
+        let mut found = false;
+        for index in self.indexes.values_mut() {
+            if index.contains(id) {
+                found = true;
+                match bbox {
+                    Some(bbox) => index.insert(id, &bbox),
+                    None => index.remove(id),
+                }
+            }
+        }
+        found
+    }
+
+    fn reindex_node(&mut self, id: &str) {
+        if self.update_index(id) {
+            return;
+        }
+        // e.g. an empty polyline that got points, its pages are searched
+        if self.get_node(id).and_then(|node| node.get_bbox()).is_none() {
+            return;
+        }
+        let places = self
+            .pages
+            .iter()
+            .filter_map(|(page_id, page)| {
+                let position = page
+                    .get_node_ids()
+                    .iter()
+                    .position(|node_id| node_id == id)?;
+                Some((page_id.clone(), position))
+            })
+            .collect_vec();
+        for (page_id, position) in places {
+            self.index_in_draw_order(&page_id, id, position);
+        }
     }
 
     pub fn get_current_page(&self) -> Option<&Page> {
@@ -54,58 +288,110 @@ impl DataModel {
         self.pages.get_mut(&self.current_page_id)
     }
 
+    // in page order
     pub fn get_pages(&self) -> Vec<&Page> {
-        let result = self.pages.values().collect_vec();
-        result
+        self.page_order
+            .iter()
+            .filter_map(|id| self.pages.get(id))
+            .collect()
     }
     pub fn get_page(&self, id: &str) -> Option<&Page> {
         self.pages.get(id)
     }
 
-    pub fn get_node(&self, id: &str) -> Option<&Box<dyn Node>> {
-        self.nodes.get(id)
+    pub fn get_node(&self, id: &str) -> Option<&dyn Node> {
+        self.nodes.get(id).map(|node| node.as_ref())
     }
-
-    pub fn get_node_mut(&mut self, id: &str) -> Option<&mut Box<dyn Node>> {
-        self.nodes.get_mut(id)
+    // the nodes of the pages in page and draw order, then the nodes on no
+    // page by id, so a document is written the same way every time
+    pub fn get_nodes(&self) -> Vec<&dyn Node> {
+        let mut seen = HashSet::new();
+        let mut result = vec![];
+        for id in self.get_pages().iter().flat_map(|page| page.get_node_ids()) {
+            if let Some(node) = self.get_node(id).filter(|_| seen.insert(id)) {
+                result.push(node);
+            }
+        }
+        let rest = self
+            .nodes
+            .iter()
+            .filter(|(id, _)| !seen.contains(id))
+            .sorted_by_key(|(id, _)| *id)
+            .map(|(_, node)| node.as_ref());
+        result.extend(rest);
+        result
     }
 
-    pub fn get_current_page_id(&self) -> &str {
+    pub fn get_current_page_id(&self) -> &NodeId {
         &self.current_page_id
     }
+
+    pub fn get_drawing_aids(&self) -> &DrawingAids {
+        &self.drawing_aids
+    }
+
+    pub fn set_drawing_aids(&mut self, drawing_aids: DrawingAids) {
+        self.drawing_aids = drawing_aids;
+        self.notifier.notify(Change::DrawingAidsChanged);
+    }
+
+    pub fn get_ucs(&self) -> &Ucs {
+        &self.ucs
+    }
+
+    pub fn set_ucs(&mut self, ucs: Ucs) {
+        self.ucs = ucs;
+        self.notifier.notify(Change::UcsChanged);
+    }
+
+    pub fn set_selection(&mut self, node_ids: Vec<NodeId>) {
+        if let Some(page) = self.pages.get_mut(&self.current_page_id) {
+            page.set_selected_ids(node_ids);
+            self.notifier
+                .notify(Change::SelectionChanged(self.current_page_id.clone()));
+        }
+    }
+
+    // the view of the client, no change is notified
+    pub fn set_page_view(&mut self, page_id: &str, view: View) {
+        if let Some(page) = self.pages.get_mut(page_id) {
+            page.set_view(view);
+        }
+    }
+
+    // None deletes the view, returns the old view
+    pub fn set_named_view(
+        &mut self,
+        page_id: &str,
+        name: &str,
+        view: Option<View>,
+    ) -> Option<View> {
+        let page = self.pages.get_mut(page_id)?;
+        let old = page.set_named_view(name, view);
+        self.notifier
+            .notify(Change::NamedViewsChanged(NodeId::from(page_id)));
+        old
+    }
+
+    // observers get the changes of a batch at once,
+    // or each single change if no batch is running
+    pub fn add_observer(&mut self, observer: Observer) {
+        self.notifier.add_observer(observer);
+    }
+    pub fn begin_batch(&mut self) {
+        self.notifier.begin_batch();
+    }
+    pub fn end_batch(&mut self) {
+        self.notifier.end_batch();
+    }
 }
 
+// the document with the info as it is, see DataModel::saved
 impl Serialize for DataModel {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("DataModel", 3)?;
-
-        state.serialize_field("id_counter", &self.id_counter.counter)?;
-        // serialize only the values, the keys are not needed
-        let serialized_pages: Vec<_> = self
-            .pages
-            .values()
-            .map(|page| {
-                let page_data = serde_json::to_value(page).map_err(serde::ser::Error::custom)?;
-                Ok(page_data)
-            })
-            .collect::<Result<_, _>>()?;
-
-        state.serialize_field("pages", &serialized_pages)?;
-
-        // Serialize nodes by converting them into a vector of serializable representations
-        let serializable_nodes: Vec<_> = self
-            .nodes
-            .values()
-            .map(|node| {
-                let node_data = serde_json::to_value(node).map_err(serde::ser::Error::custom)?;
-                Ok(node_data)
-            })
-            .collect::<Result<_, _>>()?;
-        state.serialize_field("nodes", &serializable_nodes)?;
-
-        state.end()
+        Document::new(self, self.info.clone()).serialize(serializer)
     }
 }
@@ -0,0 +1,532 @@
+//
+// the file format of a data model, datamodel.json:
+//
+//   { "format_version": 2, "app_version": "0.1.0", "created": "2026-10-19T08:30:00Z",
+//     "modified": ..., "units": "mm", "id_counter": 12, "pages": [...], "nodes": [...] }
+//
+// only the persistent state is written, the selection, the current page,
+// the drawing aids and the ucs belong to the session. older documents are
+// migrated step by step when they are read, see MIGRATIONS
+//
+// the pages are written in page order, the nodes in draw order, see
+// DataModel::get_nodes. JsonStyle::Canonical puts one page or node on a
+// line, so a diff shows the changed nodes
+//
+
+use std::io::{self, Write};
+
+use serde::ser::{SerializeStruct, Serializer};
+use serde::{Deserialize, Serialize};
+use serde_json::ser::{CompactFormatter, Formatter, PrettyFormatter};
+use serde_json::{Map, Value};
+
+use crate::datamodel::DataModel;
+use crate::node::{node_from_json, Node};
+use crate::page::Page;
+use crate::timestamp::Timestamp;
+
+pub const FORMAT_VERSION: u32 = 2;
+
+pub const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// the json schema of the current format version
+pub const DOCUMENT_SCHEMA: &str = include_str!("../schema/document.schema.json");
+
+// what one drawing unit is, drawings without units were made in mm
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Units {
+    #[default]
+    #[serde(rename = "mm")]
+    Millimeter,
+    #[serde(rename = "cm")]
+    Centimeter,
+    #[serde(rename = "m")]
+    Meter,
+    #[serde(rename = "in")]
+    Inch,
+    #[serde(rename = "ft")]
+    Foot,
+    #[serde(rename = "unitless")]
+    Unitless,
+}
+
+// the envelope of the pages and nodes
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct DocumentInfo {
+    // the version of the app that wrote the document, empty for a new one
+    #[serde(default)]
+    pub app_version: String,
+    #[serde(default)]
+    pub created: Option<Timestamp>,
+    #[serde(default)]
+    pub modified: Option<Timestamp>,
+    #[serde(default)]
+    pub units: Units,
+}
+
+// a data model as it is saved, e.g. serde_json::to_vec(&data_model.saved(Timestamp::now()))
+pub struct Document<'a> {
+    data_model: &'a DataModel,
+    info: DocumentInfo,
+}
+
+impl<'a> Document<'a> {
+    pub fn new(data_model: &'a DataModel, info: DocumentInfo) -> Self {
+        Document { data_model, info }
+    }
+}
+
+impl DataModel {
+    // the document saved at the given time, a new document is created then
+    pub fn saved(&self, now: Timestamp) -> Document<'_> {
+        let info = self.get_info();
+        let info = DocumentInfo {
+            app_version: APP_VERSION.to_string(),
+            created: info.created.or(Some(now)),
+            modified: Some(now),
+            units: info.units,
+        };
+        Document::new(self, info)
+    }
+}
+
+impl Serialize for Document<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Document", 8)?;
+        state.serialize_field("format_version", &FORMAT_VERSION)?;
+        state.serialize_field("app_version", &self.info.app_version)?;
+        state.serialize_field("created", &self.info.created)?;
+        state.serialize_field("modified", &self.info.modified)?;
+        state.serialize_field("units", &self.info.units)?;
+        state.serialize_field("id_counter", &self.data_model.get_id_counter())?;
+        state.serialize_field("pages", &self.data_model.get_pages())?;
+        let nodes: Vec<&dyn Node> = self.data_model.get_nodes();
+        state.serialize_field("nodes", &nodes)?;
+        state.end()
+    }
+}
+
+// how a document is written as json
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum JsonStyle {
+    #[default]
+    Compact,
+    // one value per line, indented by the string, e.g. two spaces or a tab
+    Pretty(String),
+    // one page or node per line and a newline at the end
+    Canonical,
+}
+
+pub fn write_json<T: Serialize + ?Sized>(value: &T, style: &JsonStyle) -> Result<Vec<u8>, String> {
+    let mut bytes = vec![];
+    let result = match style {
+        JsonStyle::Compact => serde_json::to_writer(&mut bytes, value),
+        JsonStyle::Pretty(indent) => {
+            let formatter = PrettyFormatter::with_indent(indent.as_bytes());
+            value.serialize(&mut serde_json::Serializer::with_formatter(
+                &mut bytes, formatter,
+            ))
+        }
+        JsonStyle::Canonical => {
+            let formatter = CanonicalFormatter::default();
+            let result = value.serialize(&mut serde_json::Serializer::with_formatter(
+                &mut bytes, formatter,
+            ));
+            bytes.push(b'\n');
+            result
+        }
+    };
+    result.map_err(|err| err.to_string())?;
+    Ok(bytes)
+}
+
+// the top level object and its arrays are broken into lines, deeper values
+// are compact, e.g. a node
+#[derive(Default)]
+struct CanonicalFormatter {
+    depth: usize,
+    has_value: bool,
+}
+
+impl CanonicalFormatter {
+    const BROKEN_DEPTH: usize = 2;
+
+    fn broken(&self) -> bool {
+        self.depth <= Self::BROKEN_DEPTH
+    }
+
+    fn begin<W: ?Sized + Write>(&mut self, writer: &mut W, bracket: &[u8]) -> io::Result<()> {
+        self.depth += 1;
+        self.has_value = false;
+        writer.write_all(bracket)
+    }
+
+    fn end<W: ?Sized + Write>(&mut self, writer: &mut W, bracket: &[u8]) -> io::Result<()> {
+        let broken = self.broken();
+        self.depth -= 1;
+        if broken && self.has_value {
+            writer.write_all(b"\n")?;
+            writer.write_all(&b"  ".repeat(self.depth))?;
+        }
+        writer.write_all(bracket)
+    }
+
+    fn begin_value<W: ?Sized + Write>(&mut self, writer: &mut W, first: bool) -> io::Result<()> {
+        if !first {
+            writer.write_all(b",")?;
+        }
+        if self.broken() {
+            writer.write_all(b"\n")?;
+            writer.write_all(&b"  ".repeat(self.depth))?;
+        }
+        Ok(())
+    }
+}
+
+impl Formatter for CanonicalFormatter {
+    fn begin_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.begin(writer, b"[")
+    }
+
+    fn end_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.end(writer, b"]")
+    }
+
+    fn begin_array_value<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        self.begin_value(writer, first)
+    }
+
+    fn end_array_value<W: ?Sized + Write>(&mut self, _writer: &mut W) -> io::Result<()> {
+        self.has_value = true;
+        Ok(())
+    }
+
+    fn begin_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.begin(writer, b"{")
+    }
+
+    fn end_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.end(writer, b"}")
+    }
+
+    fn begin_object_key<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        self.begin_value(writer, first)
+    }
+
+    fn begin_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        match self.broken() {
+            true => writer.write_all(b": "),
+            false => CompactFormatter.begin_object_value(writer),
+        }
+    }
+
+    fn end_object_value<W: ?Sized + Write>(&mut self, _writer: &mut W) -> io::Result<()> {
+        self.has_value = true;
+        Ok(())
+    }
+}
+
+// the current format, filled in after the migrations
+#[derive(Deserialize)]
+struct DocumentFile {
+    #[serde(flatten)]
+    info: DocumentInfo,
+    id_counter: u64,
+    pages: Vec<Page>,
+    nodes: Vec<Value>,
+}
+
+// a data model from a document of any format version
+pub fn read_document(json: &str) -> Result<DataModel, String> {
+    read_document_value(serde_json::from_str(json).map_err(|err| err.to_string())?)
+}
+
+pub(crate) fn read_document_value(value: Value) -> Result<DataModel, String> {
+    let file: DocumentFile =
+        serde_json::from_value(migrate_document(value)?).map_err(|err| err.to_string())?;
+
+    let mut data_model = DataModel::default();
+    data_model.set_info(file.info);
+    for node in file.nodes {
+        data_model.insert_node(node_from_json(node)?);
+    }
+    let first = file.pages.first().map(|page| page.get_id().to_string());
+    for page in file.pages {
+        data_model.insert_page(page);
+    }
+    if let Some(id) = first {
+        data_model.set_current_page(&id);
+    }
+    data_model.reserve_ids(file.id_counter);
+    Ok(data_model)
+}
+
+// MIGRATIONS[i] upgrades a document of format version i + 1 to version i + 2
+const MIGRATIONS: [fn(&mut Map<String, Value>); FORMAT_VERSION as usize - 1] = [envelope];
+
+// the document in the current format version
+pub fn migrate_document(mut value: Value) -> Result<Value, String> {
+    let document = value
+        .as_object_mut()
+        .ok_or("The document is not an object")?;
+    // the first documents had no version
+    let version = match document.get("format_version") {
+        None => 1,
+        Some(version) => version
+            .as_u64()
+            .filter(|version| *version >= 1)
+            .ok_or_else(|| format!("Invalid format_version {}", version))?,
+    };
+    if version > FORMAT_VERSION as u64 {
+        return Err(format!(
+            "The document has format version {}, this app reads up to version {}",
+            version, FORMAT_VERSION
+        ));
+    }
+    for migration in &MIGRATIONS[version as usize - 1..] {
+        migration(document);
+    }
+    document.insert("format_version".to_string(), FORMAT_VERSION.into());
+    Ok(value)
+}
+
+// 1 -> 2: the envelope around the pages and nodes, the selection is not saved
+fn envelope(document: &mut Map<String, Value>) {
+    document.insert("app_version".to_string(), "".into());
+    document.insert("created".to_string(), Value::Null);
+    document.insert("modified".to_string(), Value::Null);
+    document.insert("units".to_string(), "mm".into());
+    document.entry("id_counter").or_insert(0.into());
+    document.entry("pages").or_insert(Value::Array(vec![]));
+    document.entry("nodes").or_insert(Value::Array(vec![]));
+    for page in document["pages"].as_array_mut().into_iter().flatten() {
+        if let Some(page) = page.as_object_mut() {
+            page.remove("selected_ids");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Line;
+    use std::collections::BTreeSet;
+
+    fn sample() -> DataModel {
+        let mut dm = DataModel::default();
+        dm.insert_page(Page::new("1", "plan".into(), String::new()));
+        let mut line = Line::new("2".to_string());
+        (line.x2, line.y2) = (10.0, 5.0);
+        dm.insert_node(Box::new(line));
+        dm.add_node_to_page("1", "2");
+        dm.set_selection(vec!["2".into()]);
+        dm
+    }
+
+    #[test]
+    fn save_and_read() {
+        let dm = sample();
+        let created = Timestamp::from_unix_seconds(1_800_000_000);
+        let json = serde_json::to_string(&dm.saved(created)).unwrap();
+        assert!(json.starts_with("{\"format_version\":2,\"app_version\":\""));
+        // the selection is session state
+        assert!(!json.contains("selected_ids"));
+
+        let mut read = read_document(&json).unwrap();
+        assert_eq!(read.get_info().created, Some(created));
+        assert_eq!(read.get_current_page_id(), "1");
+        assert_eq!(read.get_page("1").unwrap().get_node_ids(), &["2"]);
+        assert!(read.get_page("1").unwrap().get_selected_ids().is_empty());
+        assert_eq!(read.next_id(), "3");
+
+        // created stays, modified is the time of saving
+        let later = Timestamp::from_unix_seconds(1_800_000_060);
+        let value = serde_json::to_value(read.saved(later)).unwrap();
+        assert_eq!(value["created"], "2027-01-15T08:00:00Z");
+        assert_eq!(value["modified"], "2027-01-15T08:01:00Z");
+        assert_eq!(value["app_version"], APP_VERSION);
+    }
+
+    #[test]
+    fn page_and_draw_order() {
+        let mut dm = sample();
+        dm.insert_page(Page::new("10", "detail".into(), String::new()));
+        dm.insert_page(Page::new("3", "section".into(), String::new()));
+        for id in ["9", "4", "8", "5"] {
+            dm.insert_node(Box::new(Line::new(id)));
+        }
+        dm.add_node_to_page("3", "9");
+        dm.add_node_to_page("3", "4");
+        dm.add_node_to_page("1", "8");
+        assert_eq!(dm.move_page("3", 0), Some(2));
+        assert_eq!(dm.move_page("x", 0), None);
+
+        let value = serde_json::to_value(&dm).unwrap();
+        let ids = |key: &str| -> Vec<String> {
+            let list = value[key].as_array().unwrap();
+            list.iter()
+                .map(|v| v["id"].as_str().unwrap().into())
+                .collect()
+        };
+        assert_eq!(ids("pages"), ["3", "1", "10"]);
+        // the nodes of the pages in draw order, then "5" on no page
+        assert_eq!(ids("nodes"), ["9", "4", "2", "8", "5"]);
+
+        // the order survives reading and another node order of the map
+        let read = read_document(&serde_json::to_string(&dm).unwrap()).unwrap();
+        assert_eq!(serde_json::to_value(&read).unwrap(), value);
+        dm.remove_page("1");
+        assert_eq!(dm.get_pages().len(), 2);
+    }
+
+    #[test]
+    fn json_styles() {
+        let mut dm = sample();
+        dm.insert_page(Page::new("3", "empty".into(), String::new()));
+        let document = Document::new(&dm, DocumentInfo::default());
+        let canonical = write_json(&document, &JsonStyle::Canonical).unwrap();
+        assert_eq!(
+            String::from_utf8(canonical).unwrap(),
+            r#"{
+  "format_version": 2,
+  "app_version": "",
+  "created": null,
+  "modified": null,
+  "units": "mm",
+  "id_counter": 3,
+  "pages": [
+    {"node_type":"Page","id":"1","name":"plan","description":"","node_ids":["2"],"named_views":{}},
+    {"node_type":"Page","id":"3","name":"empty","description":"","node_ids":[],"named_views":{}}
+  ],
+  "nodes": [
+    {"node_type":"Line","id":"2","x1":0.0,"y1":0.0,"x2":10.0,"y2":5.0}
+  ]
+}
+"#
+        );
+
+        let tab = write_json(&document, &JsonStyle::Pretty("\t".into())).unwrap();
+        assert!(tab.starts_with(b"{\n\t\"format_version\": 2,"));
+        let compact = write_json(&document, &JsonStyle::Compact).unwrap();
+        assert_eq!(compact, serde_json::to_vec(&document).unwrap());
+    }
+
+    #[test]
+    fn migrate_version_1() {
+        let json = r#"{
+            "id_counter": 2,
+            "pages": [{ "node_type": "Page", "id": "1", "name": "p1", "description": "",
+                        "node_ids": ["2"], "selected_ids": ["2"] }],
+            "nodes": [{ "node_type": "Line", "id": "2", "x1": 0.0, "y1": 0.0, "x2": 1.0, "y2": 1.0 }]
+        }"#;
+        let value = migrate_document(serde_json::from_str(json).unwrap()).unwrap();
+        assert_eq!(value["format_version"], 2);
+        assert_eq!(value["units"], "mm");
+        assert!(value["created"].is_null());
+        assert!(value["pages"][0].get("selected_ids").is_none());
+
+        let dm = read_document(json).unwrap();
+        assert_eq!(dm.get_info().units, Units::Millimeter);
+        assert!(dm.get_node("2").is_some());
+
+        // the repository sample is a version 1 document
+        let sample = include_str!("../../../datamodel.json");
+        assert_eq!(read_document(sample).unwrap().get_nodes().len(), 2);
+
+        assert_eq!(
+            read_document(r#"{"format_version": 3}"#).unwrap_err(),
+            "The document has format version 3, this app reads up to version 2"
+        );
+        assert!(read_document(r#"{"format_version": "2"}"#).is_err());
+        assert!(read_document("[]").is_err());
+    }
+
+    // the keys of the schema object and its variants, e.g. of the node types
+    fn schema_keys(schema: &Value, definition: &Value) -> (BTreeSet<String>, BTreeSet<String>) {
+        let definition = match definition.get("$ref").and_then(|r| r.as_str()) {
+            Some(path) => schema.pointer(path.trim_start_matches('#')).unwrap(),
+            None => definition,
+        };
+        let keys = |field: &str| -> BTreeSet<String> {
+            match &definition[field] {
+                Value::Object(map) => map.keys().cloned().collect(),
+                Value::Array(list) => list.iter().map(|v| v.as_str().unwrap().into()).collect(),
+                _ => BTreeSet::new(),
+            }
+        };
+        (keys("properties"), keys("required"))
+    }
+
+    // the schema is checked for the keys, a validator is not a dependency
+    #[test]
+    fn schema() {
+        let schema: Value = serde_json::from_str(DOCUMENT_SCHEMA).unwrap();
+        assert_eq!(
+            schema["properties"]["format_version"]["const"],
+            FORMAT_VERSION
+        );
+
+        let mut dm = sample();
+        for node in [
+            r#"{"node_type": "Arc", "id": "3", "layer": "a", "x": 0.0, "y": 0.0, "r": 1.0,
+                "angle_start": 0.0, "angle_end": 90.0}"#,
+            r#"{"node_type": "Polyline", "id": "4", "points": [{"x": 0.0, "y": 0.0}], "closed": true}"#,
+            r#"{"node_type": "Text", "id": "5", "x": 0.0, "y": 0.0, "height": 2.5,
+                "rotation": 0.0, "text": "a"}"#,
+        ] {
+            dm.insert_node(node_from_json(serde_json::from_str(node).unwrap()).unwrap());
+        }
+        let view = algebra::View {
+            x: 0.0,
+            y: 0.0,
+            width: 10.0,
+            height: 10.0,
+            rotation: 0.0,
+        };
+        dm.set_page_view("1", view);
+        dm.set_named_view("1", "all", Some(view));
+        let document = serde_json::to_value(dm.saved(Timestamp::now())).unwrap();
+
+        let check = |value: &Value, definition: &Value| {
+            let (properties, required) = schema_keys(&schema, definition);
+            let keys: BTreeSet<String> = value.as_object().unwrap().keys().cloned().collect();
+            assert!(keys.is_subset(&properties), "{:?} {:?}", keys, properties);
+            assert!(required.is_subset(&keys), "{:?} {:?}", required, keys);
+        };
+        check(&document, &schema);
+        check(
+            &document["pages"][0],
+            &schema["properties"]["pages"]["items"],
+        );
+        // the last view is not saved, the named views are
+        assert!(document["pages"][0].get("view").is_none());
+        assert!(document["pages"][0]["named_views"].get("all").is_some());
+        let variants = schema["properties"]["nodes"]["items"]["oneOf"]
+            .as_array()
+            .unwrap();
+        let nodes = document["nodes"].as_array().unwrap();
+        assert_eq!(nodes.len(), 4);
+        for node in nodes {
+            let variant = variants
+                .iter()
+                .find(|variant| {
+                    let path = variant["$ref"].as_str().unwrap().trim_start_matches('#');
+                    schema.pointer(path).unwrap()["properties"]["node_type"]["const"]
+                        == node["node_type"]
+                })
+                .unwrap();
+            check(node, variant);
+        }
+    }
+}
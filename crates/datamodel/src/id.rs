@@ -0,0 +1,250 @@
+//
+// ids of nodes and pages, e.g. "kqzmbtwa-12"
+//
+// the client id of a session is random, its counter makes the ids of the
+// session unique, so ids of two clients or of two documents do not collide.
+// a data model without a client id gives out "1", "2", ... as before
+//
+
+use std::borrow::Borrow;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct NodeId(String);
+
+impl NodeId {
+    pub fn new(id: impl Into<String>) -> Self {
+        NodeId(id.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::ops::Deref for NodeId {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+// a map of node ids is looked up with a &str
+impl Borrow<str> for NodeId {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for NodeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<String> for NodeId {
+    fn from(id: String) -> Self {
+        NodeId(id)
+    }
+}
+
+impl From<&str> for NodeId {
+    fn from(id: &str) -> Self {
+        NodeId(id.to_string())
+    }
+}
+
+impl From<NodeId> for String {
+    fn from(id: NodeId) -> Self {
+        id.0
+    }
+}
+
+impl PartialEq<str> for NodeId {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for NodeId {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<NodeId> for &str {
+    fn eq(&self, other: &NodeId) -> bool {
+        *self == other.0
+    }
+}
+
+// the prefix of the ids of one session, lowercase letters and digits
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClientId(String);
+
+const CLIENT_ID_LENGTH: usize = 8;
+
+impl ClientId {
+    // starts with a letter so an id is never read as a number
+    pub fn new(id: &str) -> Result<Self, String> {
+        let valid = id.starts_with(|c: char| c.is_ascii_lowercase())
+            && id
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit());
+        match valid {
+            true => Ok(ClientId(id.to_string())),
+            false => Err(format!(
+                "Invalid client id '{}', use lowercase letters and digits",
+                id
+            )),
+        }
+    }
+
+    // 8 random letters, 26^8 clients
+    pub fn random() -> Self {
+        let id = random_bytes()
+            .iter()
+            .map(|byte| (b'a' + byte % 26) as char)
+            .collect();
+        ClientId(id)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for ClientId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn random_bytes() -> [u8; CLIENT_ID_LENGTH] {
+    let mut bytes = [0; CLIENT_ID_LENGTH];
+    if getrandom::fill(&mut bytes).is_err() {
+        // no random source, the time still differs between sessions
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_nanos() as u64)
+            .unwrap_or(0);
+        bytes = nanos.to_le_bytes();
+    }
+    bytes
+}
+
+// getrandom needs a feature for the browser, js has Math.random
+#[cfg(target_arch = "wasm32")]
+fn random_bytes() -> [u8; CLIENT_ID_LENGTH] {
+    std::array::from_fn(|_| (js_sys::Math::random() * 256.0) as u8)
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct IdCounter {
+    client: Option<ClientId>,
+    counter: u64,
+}
+
+impl IdCounter {
+    pub(crate) fn next(&mut self) -> NodeId {
+        self.counter += 1;
+        match &self.client {
+            Some(client) => NodeId(format!("{}-{}", client, self.counter)),
+            None => NodeId(self.counter.to_string()),
+        }
+    }
+
+    pub(crate) fn get(&self) -> u64 {
+        self.counter
+    }
+
+    // e.g. a counter of a document or of a failed command
+    pub(crate) fn set(&mut self, counter: u64) {
+        self.counter = counter;
+    }
+
+    pub(crate) fn get_client(&self) -> Option<&ClientId> {
+        self.client.as_ref()
+    }
+
+    // the counter starts again, the ids of the client are new
+    pub(crate) fn set_client(&mut self, client: Option<ClientId>) {
+        self.client = client;
+        self.counter = 0;
+    }
+
+    // ids of this client inserted from outside, e.g. by a replay, are not
+    // given out again
+    pub(crate) fn reserve(&mut self, id: &str) {
+        let number = match &self.client {
+            Some(client) => id
+                .strip_prefix(client.as_str())
+                .and_then(|rest| rest.strip_prefix('-')),
+            None => Some(id),
+        };
+        if let Some(n) = number.and_then(|n| n.parse::<u64>().ok()) {
+            self.counter = self.counter.max(n);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataModel, Line, Node, Page};
+    use std::collections::HashMap;
+
+    #[test]
+    fn client_ids() {
+        let mut counter = IdCounter::default();
+        assert_eq!(counter.next(), "1");
+        counter.reserve("7");
+        assert_eq!(counter.next(), "8");
+
+        counter.set_client(Some(ClientId::new("abc").unwrap()));
+        assert_eq!(counter.next(), "abc-1");
+        // ids of other clients and plain numbers do not count
+        counter.reserve("abd-9");
+        counter.reserve("12");
+        counter.reserve("abc-4");
+        assert_eq!(counter.next(), "abc-5");
+
+        let a = ClientId::random();
+        assert_eq!(a.as_str().len(), 8);
+        assert!(ClientId::new(a.as_str()).is_ok());
+        assert_ne!(a, ClientId::random());
+        assert!(ClientId::new("1a").is_err());
+        assert!(ClientId::new("a-b").is_err());
+        assert!(ClientId::new("").is_err());
+
+        // typed ids look up maps by &str and are written as strings
+        let id = NodeId::from("abc-5");
+        let map = HashMap::from([(id.clone(), 1)]);
+        assert_eq!(map.get("abc-5"), Some(&1));
+        assert_eq!(serde_json::to_string(&id).unwrap(), "\"abc-5\"");
+    }
+
+    #[test]
+    fn data_model_ids() {
+        let mut dm = DataModel::default();
+        dm.insert_page(Page::new("abc-3", "p".into(), String::new()));
+        dm.set_client_id(Some(ClientId::new("abc").unwrap()));
+        // ids of the client in the data model are not given out again
+        assert_eq!(dm.next_id(), "abc-4");
+
+        let counter = dm.get_id_counter();
+        let id = dm.next_id();
+        dm.insert_node(Box::new(Line::new(id)));
+        dm.next_id();
+        dm.release_ids(counter);
+        assert_eq!(dm.next_id(), "abc-6");
+
+        let mut nodes: Vec<Box<dyn Node>> = vec![Box::new(Line::new("abc-5"))];
+        dm.reidentify(&mut nodes);
+        assert_eq!(nodes[0].get_id(), "abc-7");
+    }
+}
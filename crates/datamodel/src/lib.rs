@@ -3,16 +3,33 @@ pub fn add(left: u64, right: u64) -> u64 {
 }
 
 mod arc;
+mod binary;
+mod change;
 mod datamodel;
+mod document;
+mod id;
 mod line;
 mod node;
 mod page;
+mod pick;
+mod polyline;
+mod spatialindex;
+mod text;
+mod timestamp;
 
 pub use arc::*;
+pub use binary::*;
+pub use change::{Change, Observer};
 pub use datamodel::*;
+pub use document::*;
+pub use id::{ClientId, NodeId};
 pub use line::*;
-pub use node::{Node, NodeType};
+pub use node::{node_from_json, Node, NodeType};
 pub use page::*;
+pub use polyline::*;
+pub use spatialindex::*;
+pub use text::*;
+pub use timestamp::*;
 
 #[cfg(test)]
 mod tests {
@@ -1,13 +1,17 @@
 //
 
+use algebra::{distance_to_segment, segment_intersects_bbox, BBox, Point2d};
 use serde::{Deserialize, Serialize};
 
+use crate::id::NodeId;
 use crate::node::{Node, NodeType};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Line {
     node_type: NodeType,
-    id: String,
+    id: NodeId,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    layer: String,
     pub x1: f64,
     pub y1: f64,
     pub x2: f64,
@@ -15,8 +19,11 @@ pub struct Line {
 }
 
 impl Node for Line {
-    fn get_id(&self) -> &str {
-        self.id.as_str()
+    fn get_id(&self) -> &NodeId {
+        &self.id
+    }
+    fn set_id(&mut self, id: NodeId) {
+        self.id = id;
     }
 
     fn get_node_type(&self) -> &NodeType {
@@ -29,13 +36,48 @@ impl Node for Line {
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
         self
     }
+    fn clone_node(&self) -> Box<dyn Node> {
+        Box::new(self.clone())
+    }
+
+    fn get_layer(&self) -> &str {
+        &self.layer
+    }
+    fn set_layer(&mut self, layer: &str) {
+        self.layer = layer.to_string();
+    }
+
+    fn get_bbox(&self) -> Option<BBox> {
+        Some(BBox::from_points(&self.get_p1(), &self.get_p2()))
+    }
+
+    fn distance(&self, pt: &Point2d) -> Option<f64> {
+        Some(distance_to_segment(pt, &self.get_p1(), &self.get_p2()))
+    }
+
+    fn intersects(&self, bbox: &BBox) -> bool {
+        segment_intersects_bbox(&self.get_p1(), &self.get_p2(), bbox)
+    }
+
+    fn get_snap_points(&self) -> Vec<Point2d> {
+        let mid = Point2d::new((self.x1 + self.x2) / 2.0, (self.y1 + self.y2) / 2.0);
+        vec![self.get_p1(), self.get_p2(), mid]
+    }
+
+    fn translate(&mut self, delta: &Point2d) {
+        self.x1 += delta.x;
+        self.y1 += delta.y;
+        self.x2 += delta.x;
+        self.y2 += delta.y;
+    }
 }
 
 impl Line {
-    pub fn new(id: String) -> Self {
+    pub fn new(id: impl Into<NodeId>) -> Self {
         Line {
             node_type: NodeType::Line,
-            id,
+            id: id.into(),
+            layer: String::new(),
             x1: 0.0,
             y1: 0.0,
             x2: 50.0,
@@ -54,4 +96,10 @@ impl Line {
     pub fn get_y2(&self) -> f64 {
         self.y2
     }
+    pub fn get_p1(&self) -> Point2d {
+        Point2d::new(self.x1, self.y1)
+    }
+    pub fn get_p2(&self) -> Point2d {
+        Point2d::new(self.x2, self.y2)
+    }
 }
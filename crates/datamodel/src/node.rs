@@ -1,18 +1,24 @@
 use std::any::Any;
 use std::fmt::Display;
 
+use algebra::{BBox, Point2d};
 use serde::{Deserialize, Serialize};
 
 use crate::arc::Arc;
+use crate::id::NodeId;
 use crate::line::Line;
 use crate::page::Page;
+use crate::polyline::Polyline;
+use crate::text::Text;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum NodeType {
     // Root,
     Page,
     Line,
     Arc,
+    Polyline,
+    Text,
 }
 impl Display for NodeType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -20,6 +26,8 @@ impl Display for NodeType {
             NodeType::Page => write!(f, "Page"),
             NodeType::Line => write!(f, "Line"),
             NodeType::Arc => write!(f, "Arc"),
+            NodeType::Polyline => write!(f, "Polyline"),
+            NodeType::Text => write!(f, "Text"),
         }
     }
 }
@@ -29,6 +37,8 @@ impl From<&str> for NodeType {
             "page" => NodeType::Page,
             "line" => NodeType::Line,
             "arc" => NodeType::Arc,
+            "polyline" => NodeType::Polyline,
+            "text" => NodeType::Text,
             _ => panic!("Invalid node type"),
         }
     }
@@ -37,14 +47,57 @@ impl From<&str> for NodeType {
 // ------------------------
 
 pub trait Node: std::fmt::Debug {
-    fn get_id(&self) -> &str;
+    fn get_id(&self) -> &NodeId;
+    // e.g. new ids for nodes pasted from another document
+    fn set_id(&mut self, id: NodeId);
     fn get_node_type(&self) -> &NodeType;
     fn as_any(&self) -> &dyn Any;
     fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn clone_node(&self) -> Box<dyn Node>;
+
+    // the name of the layer, empty for the default layer
+    fn get_layer(&self) -> &str {
+        ""
+    }
+    fn set_layer(&mut self, _layer: &str) {}
+
+    // geometry for hit-testing, nodes without geometry are never picked
+    fn get_bbox(&self) -> Option<BBox> {
+        None
+    }
+    fn distance(&self, _pt: &Point2d) -> Option<f64> {
+        None
+    }
+    fn intersects(&self, _bbox: &BBox) -> bool {
+        false
+    }
+    fn get_snap_points(&self) -> Vec<Point2d> {
+        vec![]
+    }
+    fn translate(&mut self, _delta: &Point2d) {}
+}
+
+// the concrete node from its json, the node_type field decides the type
+pub fn node_from_json(value: serde_json::Value) -> Result<Box<dyn Node>, String> {
+    let node_type = value
+        .get("node_type")
+        .and_then(|t| t.as_str())
+        .ok_or("Missing node_type")?;
+    let node: Box<dyn Node> = match node_type {
+        "Line" => Box::new(serde_json::from_value::<Line>(value).map_err(|e| e.to_string())?),
+        "Arc" => Box::new(serde_json::from_value::<Arc>(value).map_err(|e| e.to_string())?),
+        "Polyline" => {
+            Box::new(serde_json::from_value::<Polyline>(value).map_err(|e| e.to_string())?)
+        }
+        "Text" => Box::new(serde_json::from_value::<Text>(value).map_err(|e| e.to_string())?),
+        "Page" => Box::new(serde_json::from_value::<Page>(value).map_err(|e| e.to_string())?),
+        _ => return Err(format!("Unknown node_type '{}'", node_type)),
+    };
+    Ok(node)
 }
 
 // Implement Serialize for dyn Node to allow serialization of concrete types
-impl Serialize for dyn Node {
+impl Serialize for dyn Node + '_ {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
@@ -71,6 +124,20 @@ impl Serialize for dyn Node {
                     Err(serde::ser::Error::custom("Failed to downcast to Arc"))
                 }
             }
+            NodeType::Polyline => {
+                if let Some(polyline) = self.as_any().downcast_ref::<Polyline>() {
+                    polyline.serialize(serializer)
+                } else {
+                    Err(serde::ser::Error::custom("Failed to downcast to Polyline"))
+                }
+            }
+            NodeType::Text => {
+                if let Some(text) = self.as_any().downcast_ref::<Text>() {
+                    text.serialize(serializer)
+                } else {
+                    Err(serde::ser::Error::custom("Failed to downcast to Text"))
+                }
+            }
         }
     }
 }
@@ -1,22 +1,36 @@
+use std::collections::BTreeMap;
+
+use algebra::View;
 use serde::{Deserialize, Serialize};
 
+use crate::id::NodeId;
 use crate::node::{Node, NodeType};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Page {
     node_type: NodeType,
-    id: String,
+    id: NodeId,
     name: String,
     description: String,
-    node_ids: Vec<String>,
+    node_ids: Vec<NodeId>,
+
+    // session state, not saved
+    #[serde(skip)]
+    selected_ids: Vec<NodeId>,
+    // where the page was looked at last, restored when it becomes current
+    #[serde(skip)]
+    view: Option<View>,
 
-    // #[serde(skip_serializing)]
-    selected_ids: Vec<String>,
+    #[serde(default)]
+    named_views: BTreeMap<String, View>,
 }
 
 impl Node for Page {
-    fn get_id(&self) -> &str {
-        self.id.as_str()
+    fn get_id(&self) -> &NodeId {
+        &self.id
+    }
+    fn set_id(&mut self, id: NodeId) {
+        self.id = id;
     }
 
     fn get_node_type(&self) -> &NodeType {
@@ -30,17 +44,23 @@ impl Node for Page {
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
         self
     }
+
+    fn clone_node(&self) -> Box<dyn Node> {
+        Box::new(self.clone())
+    }
 }
 
 impl Page {
-    pub fn new(id: String, name: String, content: String) -> Self {
+    pub fn new(id: impl Into<NodeId>, name: String, content: String) -> Self {
         Page {
             node_type: NodeType::Page,
-            id,
+            id: id.into(),
             name,
             description: content,
             node_ids: vec![],
             selected_ids: vec![],
+            view: None,
+            named_views: BTreeMap::new(),
         }
     }
 
@@ -48,19 +68,58 @@ impl Page {
         self.name.as_str()
     }
 
-    pub fn add_node_id(&mut self, node_id: String) {
+    pub fn get_description(&self) -> &str {
+        self.description.as_str()
+    }
+
+    // use DataModel::add_node_to_page, which keeps the spatial index up to date
+    pub(crate) fn add_node_id(&mut self, node_id: NodeId) {
         self.node_ids.push(node_id);
     }
 
-    pub fn get_node_ids(&self) -> &Vec<String> {
+    pub(crate) fn insert_node_id(&mut self, position: usize, node_id: NodeId) {
+        let position = position.min(self.node_ids.len());
+        self.node_ids.insert(position, node_id);
+    }
+
+    pub(crate) fn remove_node_id(&mut self, node_id: &str) {
+        self.node_ids.retain(|id| id != node_id);
+        self.selected_ids.retain(|id| id != node_id);
+    }
+
+    pub fn get_node_ids(&self) -> &Vec<NodeId> {
         &self.node_ids
     }
 
-    pub fn set_selected_ids(&mut self, node_ids: Vec<String>) {
+    pub fn set_selected_ids(&mut self, node_ids: Vec<NodeId>) {
         self.selected_ids = node_ids;
     }
 
-    pub fn get_selected_ids(&self) -> &Vec<String> {
+    pub fn get_selected_ids(&self) -> &Vec<NodeId> {
         &self.selected_ids
     }
+
+    pub fn get_view(&self) -> Option<&View> {
+        self.view.as_ref()
+    }
+
+    pub(crate) fn set_view(&mut self, view: View) {
+        self.view = Some(view);
+    }
+
+    pub fn get_named_views(&self) -> &BTreeMap<String, View> {
+        &self.named_views
+    }
+
+    pub fn get_named_view(&self, name: &str) -> Option<&View> {
+        self.named_views.get(name)
+    }
+
+    // None deletes the view, returns the old view
+    pub(crate) fn set_named_view(&mut self, name: &str, view: Option<View>) -> Option<View> {
+        match view {
+            Some(view) => self.named_views.insert(name.to_string(), view),
+            None => self.named_views.remove(name),
+        }
+    }
 }
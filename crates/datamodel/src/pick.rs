@@ -0,0 +1,191 @@
+//
+
+use algebra::{BBox, Point2d};
+
+use crate::datamodel::DataModel;
+use crate::id::NodeId;
+use crate::node::Node;
+use crate::page::Page;
+
+impl DataModel {
+    // the topmost node is the one drawn last
+    pub fn pick_node(&self, page: &Page, pt: &Point2d, tolerance: f64) -> Option<NodeId> {
+        let area = BBox::new(*pt, *pt).inflate(tolerance);
+        self.query_nodes(page, &area)
+            .into_iter()
+            .rev()
+            .find(|node| matches!(node.distance(pt), Some(d) if d <= tolerance))
+            .map(|node| node.get_id().clone())
+    }
+
+    // window selection: nodes completely inside the rectangle
+    pub fn select_window(&self, page: &Page, window: &BBox) -> Vec<NodeId> {
+        self.query_nodes(page, window)
+            .into_iter()
+            .filter(|node| matches!(node.get_bbox(), Some(bbox) if window.contains(&bbox)))
+            .map(|node| node.get_id().clone())
+            .collect()
+    }
+
+    // crossing selection: nodes inside or crossing the rectangle
+    pub fn select_crossing(&self, page: &Page, window: &BBox) -> Vec<NodeId> {
+        self.query_nodes(page, window)
+            .into_iter()
+            .filter(|node| node.intersects(window))
+            .map(|node| node.get_id().clone())
+            .collect()
+    }
+
+    // nearest snap point (end point, mid point, center ...) of a node
+    pub fn snap_to_node(&self, page: &Page, pt: &Point2d, tolerance: f64) -> Option<Point2d> {
+        let area = BBox::new(*pt, *pt).inflate(tolerance);
+        self.query_nodes(page, &area)
+            .into_iter()
+            .flat_map(|node| node.get_snap_points())
+            .map(|snap_pt| (pt.distance(&snap_pt), snap_pt))
+            .filter(|(d, _)| *d <= tolerance)
+            .min_by(|(d1, _), (d2, _)| d1.total_cmp(d2))
+            .map(|(_, snap_pt)| snap_pt)
+    }
+
+    // nodes of the page whose bounding box intersects the area, in draw order
+    pub fn query_nodes(&self, page: &Page, area: &BBox) -> Vec<&dyn Node> {
+        match self.get_spatial_index(page.get_id()) {
+            Some(index) => index
+                .query(area)
+                .into_iter()
+                .filter_map(|id| self.get_node(id))
+                .collect(),
+            None => page
+                .get_node_ids()
+                .iter()
+                .filter_map(|id| self.get_node(id))
+                .filter(|node| matches!(node.get_bbox(), Some(bbox) if bbox.intersects(area)))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Arc, Line, Polyline};
+
+    fn create_data_model() -> DataModel {
+        let mut dm = DataModel::default();
+        dm.insert_page(Page::new(
+            "p".to_string(),
+            "page".to_string(),
+            String::new(),
+        ));
+
+        let mut line = Line::new("l1".to_string());
+        (line.x1, line.y1, line.x2, line.y2) = (0.0, 0.0, 100.0, 0.0);
+        dm.insert_node(Box::new(line));
+        let mut line = Line::new("l2".to_string());
+        (line.x1, line.y1, line.x2, line.y2) = (50.0, -10.0, 50.0, 10.0);
+        dm.insert_node(Box::new(line));
+        let arc = Arc::new("a1".to_string(), 200.0, 0.0, 20.0, 0.0, 360.0);
+        dm.insert_node(Box::new(arc));
+
+        for id in ["l1", "l2", "a1"] {
+            dm.add_node_to_current_page(id);
+        }
+        dm
+    }
+
+    #[test]
+    fn pick_topmost() {
+        let dm = create_data_model();
+        let page = dm.get_current_page().unwrap();
+
+        let id = dm.pick_node(page, &Point2d::new(50.0, 1.0), 2.0);
+        assert_eq!(id.as_deref(), Some("l2"));
+        let id = dm.pick_node(page, &Point2d::new(20.0, 1.0), 2.0);
+        assert_eq!(id.as_deref(), Some("l1"));
+        let id = dm.pick_node(page, &Point2d::new(221.0, 0.0), 2.0);
+        assert_eq!(id.as_deref(), Some("a1"));
+        assert_eq!(dm.pick_node(page, &Point2d::new(200.0, 0.0), 2.0), None);
+    }
+
+    #[test]
+    fn window_and_crossing() {
+        let dm = create_data_model();
+        let page = dm.get_current_page().unwrap();
+        let window = BBox::from_points(&Point2d::new(40.0, -20.0), &Point2d::new(250.0, 20.0));
+
+        assert_eq!(dm.select_window(page, &window), vec!["l2", "a1"]);
+        assert_eq!(dm.select_crossing(page, &window), vec!["l1", "l2", "a1"]);
+    }
+
+    #[test]
+    fn index_follows_updates() {
+        let mut dm = create_data_model();
+        dm.update_node("l2", |node| {
+            let line = node.as_any_mut().downcast_mut::<Line>().unwrap();
+            (line.x1, line.x2) = (500.0, 500.0);
+        });
+        let page = dm.get_current_page().unwrap();
+        let id = dm.pick_node(page, &Point2d::new(50.0, 1.0), 2.0);
+        assert_eq!(id.as_deref(), Some("l1"));
+        let id = dm.pick_node(page, &Point2d::new(500.0, 1.0), 2.0);
+        assert_eq!(id.as_deref(), Some("l2"));
+
+        dm.remove_node("l2");
+        let page = dm.get_current_page().unwrap();
+        assert_eq!(page.get_node_ids(), &vec!["l1", "a1"]);
+        assert_eq!(dm.pick_node(page, &Point2d::new(500.0, 1.0), 2.0), None);
+    }
+
+    #[test]
+    fn node_without_bbox_is_indexed_later() {
+        let mut dm = create_data_model();
+        dm.insert_node(Box::new(Polyline::new("e", vec![], false)));
+        dm.insert_node_to_page("p", "e", 1);
+        dm.update_node("e", |node| {
+            let polyline = node.as_any_mut().downcast_mut::<Polyline>().unwrap();
+            polyline.points = vec![Point2d::new(50.0, -5.0), Point2d::new(50.0, 5.0)];
+        });
+        let page = dm.get_current_page().unwrap();
+        let window = BBox::from_points(&Point2d::new(40.0, -20.0), &Point2d::new(60.0, 20.0));
+        // in the draw order of the page, not in the order of indexing
+        assert_eq!(dm.select_crossing(page, &window), vec!["l1", "e", "l2"]);
+        let id = dm.pick_node(page, &Point2d::new(50.0, 1.0), 2.0);
+        assert_eq!(id.as_deref(), Some("l2"));
+    }
+
+    #[test]
+    fn inserted_node_keeps_draw_order() {
+        let mut dm = create_data_model();
+        let mut line = Line::new("l3".to_string());
+        (line.x1, line.y1, line.x2, line.y2) = (45.0, -5.0, 55.0, 5.0);
+        dm.insert_node(Box::new(line));
+        dm.insert_node_to_page("p", "l3", 1);
+        let page = dm.get_current_page().unwrap();
+        let window = BBox::from_points(&Point2d::new(40.0, -20.0), &Point2d::new(60.0, 20.0));
+        assert_eq!(dm.select_crossing(page, &window), vec!["l1", "l3", "l2"]);
+
+        // at the end, after the node drawn last
+        dm.remove_node("l3");
+        let mut line = Line::new("l4".to_string());
+        (line.x1, line.y1, line.x2, line.y2) = (45.0, -5.0, 55.0, 5.0);
+        dm.insert_node(Box::new(line));
+        dm.insert_node_to_page("p", "l4", 10);
+        let page = dm.get_current_page().unwrap();
+        assert_eq!(page.get_node_ids(), &vec!["l1", "l2", "a1", "l4"]);
+        assert_eq!(dm.select_crossing(page, &window), vec!["l1", "l2", "l4"]);
+        let id = dm.pick_node(page, &Point2d::new(50.0, 0.0), 2.0);
+        assert_eq!(id.as_deref(), Some("l4"));
+    }
+
+    #[test]
+    fn snap_to_end_point() {
+        let dm = create_data_model();
+        let page = dm.get_current_page().unwrap();
+        let pt = dm.snap_to_node(page, &Point2d::new(98.0, 1.0), 5.0);
+        assert_eq!(pt, Some(Point2d::new(100.0, 0.0)));
+        let pt = dm.snap_to_node(page, &Point2d::new(201.0, 1.0), 5.0);
+        assert_eq!(pt, Some(Point2d::new(200.0, 0.0)));
+        assert_eq!(dm.snap_to_node(page, &Point2d::new(30.0, 0.0), 5.0), None);
+    }
+}
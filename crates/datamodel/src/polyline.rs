@@ -0,0 +1,127 @@
+//
+
+use algebra::{distance_to_segment, segment_intersects_bbox, BBox, Point2d};
+use serde::{Deserialize, Serialize};
+
+use crate::id::NodeId;
+use crate::node::{Node, NodeType};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Polyline {
+    node_type: NodeType,
+    id: NodeId,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    layer: String,
+    pub points: Vec<Point2d>,
+    // the last point is connected to the first
+    pub closed: bool,
+}
+
+impl Node for Polyline {
+    fn get_id(&self) -> &NodeId {
+        &self.id
+    }
+    fn set_id(&mut self, id: NodeId) {
+        self.id = id;
+    }
+
+    fn get_node_type(&self) -> &NodeType {
+        &self.node_type
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+    fn clone_node(&self) -> Box<dyn Node> {
+        Box::new(self.clone())
+    }
+
+    fn get_layer(&self) -> &str {
+        &self.layer
+    }
+    fn set_layer(&mut self, layer: &str) {
+        self.layer = layer.to_string();
+    }
+
+    fn get_bbox(&self) -> Option<BBox> {
+        let (first, rest) = self.points.split_first()?;
+        Some(rest.iter().fold(BBox::new(*first, *first), |bbox, pt| {
+            bbox.union(&BBox::new(*pt, *pt))
+        }))
+    }
+
+    fn distance(&self, pt: &Point2d) -> Option<f64> {
+        self.segments()
+            .map(|(a, b)| distance_to_segment(pt, a, b))
+            .min_by(f64::total_cmp)
+    }
+
+    fn intersects(&self, bbox: &BBox) -> bool {
+        self.segments()
+            .any(|(a, b)| segment_intersects_bbox(a, b, bbox))
+    }
+
+    fn get_snap_points(&self) -> Vec<Point2d> {
+        let mids = self.segments().map(|(a, b)| (*a + *b) / 2.0);
+        self.points.iter().copied().chain(mids).collect()
+    }
+
+    fn translate(&mut self, delta: &Point2d) {
+        for pt in &mut self.points {
+            *pt = *pt + *delta;
+        }
+    }
+}
+
+impl Polyline {
+    pub fn new(id: impl Into<NodeId>, points: Vec<Point2d>, closed: bool) -> Self {
+        Polyline {
+            node_type: NodeType::Polyline,
+            id: id.into(),
+            layer: String::new(),
+            points,
+            closed,
+        }
+    }
+
+    // a closed polyline includes the segment back to the first point,
+    // a single point is a segment of length 0
+    pub fn segments(&self) -> impl Iterator<Item = (&Point2d, &Point2d)> {
+        let n = self.points.len();
+        let count = match n {
+            0 => 0,
+            1 => 1,
+            _ if self.closed => n,
+            _ => n - 1,
+        };
+        (0..count).map(move |i| (&self.points[i], &self.points[(i + 1) % n]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closed_polyline() {
+        let points = vec![
+            Point2d::new(0.0, 0.0),
+            Point2d::new(10.0, 0.0),
+            Point2d::new(10.0, 10.0),
+        ];
+        let mut polyline = Polyline::new("p".to_string(), points, false);
+        assert_eq!(polyline.segments().count(), 2);
+        assert_eq!(polyline.distance(&Point2d::new(0.0, 10.0)), Some(10.0));
+
+        polyline.closed = true;
+        assert_eq!(polyline.segments().count(), 3);
+        assert!(polyline.distance(&Point2d::new(0.0, 10.0)).unwrap() < 7.1);
+        assert_eq!(
+            polyline.get_bbox(),
+            Some(BBox::new(Point2d::new(0.0, 0.0), Point2d::new(10.0, 10.0)))
+        );
+    }
+}
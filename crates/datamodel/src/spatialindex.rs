@@ -0,0 +1,186 @@
+//
+
+use std::collections::HashMap;
+
+use algebra::{BBox, Point2d};
+use rstar::primitives::{GeomWithData, Rectangle};
+use rstar::{RTree, AABB};
+
+// the data is the node id and its draw order on the page
+type IndexEntry = GeomWithData<Rectangle<[f64; 2]>, (String, u64)>;
+
+// the orders leave room for nodes inserted between two nodes
+const ORDER_STEP: u64 = 1 << 16;
+
+// r-tree over the bounding boxes of the nodes of one page
+#[derive(Debug, Default)]
+pub struct SpatialIndex {
+    tree: RTree<IndexEntry>,
+    entries: HashMap<String, IndexEntry>,
+    next_order: u64,
+}
+
+impl SpatialIndex {
+    // the nodes in draw order at once, much faster than inserting them one by one
+    pub fn bulk_load<'a>(nodes: impl IntoIterator<Item = (&'a str, BBox)>) -> Self {
+        let mut entries = HashMap::new();
+        let mut next_order = 0;
+        for (id, bbox) in nodes {
+            next_order += ORDER_STEP;
+            let entry = IndexEntry::new(to_rectangle(&bbox), (id.to_string(), next_order));
+            entries.insert(id.to_string(), entry);
+        }
+        SpatialIndex {
+            tree: RTree::bulk_load(entries.values().cloned().collect()),
+            entries,
+            next_order,
+        }
+    }
+
+    pub fn insert(&mut self, id: &str, bbox: &BBox) {
+        let order = match self.entries.get(id) {
+            Some(entry) => entry.data.1,
+            None => {
+                self.next_order += ORDER_STEP;
+                self.next_order
+            }
+        };
+        self.insert_ordered(id, bbox, order);
+    }
+
+    // e.g. a node between two others, see order_between
+    pub(crate) fn insert_ordered(&mut self, id: &str, bbox: &BBox, order: u64) {
+        self.remove(id);
+        let entry = IndexEntry::new(to_rectangle(bbox), (id.to_string(), order));
+        self.tree.insert(entry.clone());
+        self.entries.insert(id.to_string(), entry);
+        self.next_order = self.next_order.max(order);
+    }
+
+    pub(crate) fn order(&self, id: &str) -> Option<u64> {
+        self.entries.get(id).map(|entry| entry.data.1)
+    }
+
+    // None if there is no room left, the index is built again then
+    pub(crate) fn order_between(lower: u64, upper: u64) -> Option<u64> {
+        (upper > lower + 1).then(|| lower + (upper - lower) / 2)
+    }
+
+    pub fn remove(&mut self, id: &str) {
+        if let Some(entry) = self.entries.remove(id) {
+            self.tree.remove(&entry);
+        }
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.entries.contains_key(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // bounding box of all nodes, None for an empty page
+    pub fn extents(&self) -> Option<BBox> {
+        if self.is_empty() {
+            return None;
+        }
+        let envelope = self.tree.root().envelope();
+        let (min, max) = (envelope.lower(), envelope.upper());
+        Some(BBox::new(
+            Point2d::new(min[0], min[1]),
+            Point2d::new(max[0], max[1]),
+        ))
+    }
+
+    // ids of all nodes whose bounding box intersects the area, in draw order
+    pub fn query(&self, area: &BBox) -> Vec<&str> {
+        let envelope = AABB::from_corners([area.min.x, area.min.y], [area.max.x, area.max.y]);
+        let mut found: Vec<&IndexEntry> = self
+            .tree
+            .locate_in_envelope_intersecting(&envelope)
+            .collect();
+        found.sort_by_key(|entry| entry.data.1);
+        found.iter().map(|entry| entry.data.0.as_str()).collect()
+    }
+
+    pub fn query_point(&self, pt: &Point2d, tolerance: f64) -> Vec<&str> {
+        self.query(&BBox::new(*pt, *pt).inflate(tolerance))
+    }
+}
+
+fn to_rectangle(bbox: &BBox) -> Rectangle<[f64; 2]> {
+    Rectangle::from_corners([bbox.min.x, bbox.min.y], [bbox.max.x, bbox.max.y])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bbox(x1: f64, y1: f64, x2: f64, y2: f64) -> BBox {
+        BBox::from_points(&Point2d::new(x1, y1), &Point2d::new(x2, y2))
+    }
+
+    #[test]
+    fn query_in_draw_order() {
+        let mut index = SpatialIndex::default();
+        index.insert("b", &bbox(0.0, 0.0, 10.0, 10.0));
+        index.insert("a", &bbox(5.0, 5.0, 20.0, 20.0));
+        index.insert("c", &bbox(100.0, 100.0, 110.0, 110.0));
+
+        assert_eq!(index.query(&bbox(6.0, 6.0, 8.0, 8.0)), vec!["b", "a"]);
+        assert_eq!(
+            index.query_point(&Point2d::new(105.0, 99.0), 2.0),
+            vec!["c"]
+        );
+        assert!(index.query(&bbox(50.0, 50.0, 60.0, 60.0)).is_empty());
+
+        assert_eq!(index.extents(), Some(bbox(0.0, 0.0, 110.0, 110.0)));
+        assert_eq!(SpatialIndex::default().extents(), None);
+    }
+
+    #[test]
+    fn bulk_load() {
+        let boxes = [
+            ("b", bbox(0.0, 0.0, 10.0, 10.0)),
+            ("a", bbox(5.0, 5.0, 20.0, 20.0)),
+            ("c", bbox(100.0, 100.0, 110.0, 110.0)),
+        ];
+        let mut index = SpatialIndex::bulk_load(boxes);
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.query(&bbox(6.0, 6.0, 8.0, 8.0)), vec!["b", "a"]);
+        index.insert("d", &bbox(0.0, 0.0, 10.0, 10.0));
+        assert_eq!(
+            index.query_point(&Point2d::new(6.0, 6.0), 0.0),
+            vec!["b", "a", "d"]
+        );
+    }
+
+    #[test]
+    fn update_keeps_order() {
+        let mut index = SpatialIndex::default();
+        index.insert("a", &bbox(0.0, 0.0, 10.0, 10.0));
+        index.insert("b", &bbox(0.0, 0.0, 10.0, 10.0));
+        index.insert("a", &bbox(5.0, 5.0, 15.0, 15.0));
+
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.query(&bbox(6.0, 6.0, 8.0, 8.0)), vec!["a", "b"]);
+        assert_eq!(index.query(&bbox(12.0, 12.0, 14.0, 14.0)), vec!["a"]);
+
+        index.remove("a");
+        assert!(!index.contains("a"));
+        assert_eq!(index.query(&bbox(6.0, 6.0, 8.0, 8.0)), vec!["b"]);
+
+        // between two nodes until there is no room left
+        index.insert("c", &bbox(0.0, 0.0, 10.0, 10.0));
+        let (lower, upper) = (index.order("b").unwrap(), index.order("c").unwrap());
+        let order = SpatialIndex::order_between(lower, upper).unwrap();
+        index.insert_ordered("d", &bbox(0.0, 0.0, 10.0, 10.0), order);
+        assert_eq!(index.query(&bbox(6.0, 6.0, 8.0, 8.0)), vec!["b", "d", "c"]);
+        assert_eq!(SpatialIndex::order_between(4, 5), None);
+    }
+}
@@ -0,0 +1,161 @@
+//
+
+use algebra::{distance_to_segment, segments_intersect, BBox, Matrix, Point2d};
+use serde::{Deserialize, Serialize};
+
+use crate::id::NodeId;
+use crate::node::{Node, NodeType};
+
+// the width of a character is estimated, there is no font metric
+const CHAR_WIDTH: f64 = 0.6;
+
+// a single line of text, the insertion point is at the left of the baseline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Text {
+    node_type: NodeType,
+    id: NodeId,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    layer: String,
+    pub x: f64,
+    pub y: f64,
+    pub height: f64,
+    // degrees, counter clockwise
+    pub rotation: f64,
+    pub text: String,
+}
+
+impl Node for Text {
+    fn get_id(&self) -> &NodeId {
+        &self.id
+    }
+    fn set_id(&mut self, id: NodeId) {
+        self.id = id;
+    }
+
+    fn get_node_type(&self) -> &NodeType {
+        &self.node_type
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+    fn clone_node(&self) -> Box<dyn Node> {
+        Box::new(self.clone())
+    }
+
+    fn get_layer(&self) -> &str {
+        &self.layer
+    }
+    fn set_layer(&mut self, layer: &str) {
+        self.layer = layer.to_string();
+    }
+
+    fn get_bbox(&self) -> Option<BBox> {
+        let [first, rest @ ..] = self.get_corners();
+        Some(rest.iter().fold(BBox::new(first, first), |bbox, pt| {
+            bbox.union(&BBox::new(*pt, *pt))
+        }))
+    }
+
+    fn distance(&self, pt: &Point2d) -> Option<f64> {
+        // inside the text box counts as a hit
+        if self.contains(pt) {
+            return Some(0.0);
+        }
+        let corners = self.get_corners();
+        (0..4)
+            .map(|i| distance_to_segment(pt, &corners[i], &corners[(i + 1) % 4]))
+            .min_by(f64::total_cmp)
+    }
+
+    fn intersects(&self, bbox: &BBox) -> bool {
+        let corners = self.get_corners();
+        let edges = bbox.corners();
+        corners.iter().any(|pt| bbox.contains_point(pt))
+            || edges.iter().any(|pt| self.contains(pt))
+            || (0..4).any(|i| {
+                (0..4).any(|j| {
+                    segments_intersect(
+                        &corners[i],
+                        &corners[(i + 1) % 4],
+                        &edges[j],
+                        &edges[(j + 1) % 4],
+                    )
+                })
+            })
+    }
+
+    fn get_snap_points(&self) -> Vec<Point2d> {
+        vec![self.get_position()]
+    }
+
+    fn translate(&mut self, delta: &Point2d) {
+        self.x += delta.x;
+        self.y += delta.y;
+    }
+}
+
+impl Text {
+    pub fn new(id: impl Into<NodeId>, position: Point2d, height: f64, text: String) -> Self {
+        Text {
+            node_type: NodeType::Text,
+            id: id.into(),
+            layer: String::new(),
+            x: position.x,
+            y: position.y,
+            height,
+            rotation: 0.0,
+            text,
+        }
+    }
+
+    pub fn get_position(&self) -> Point2d {
+        Point2d::new(self.x, self.y)
+    }
+
+    pub fn get_width(&self) -> f64 {
+        self.text.chars().count() as f64 * self.height * CHAR_WIDTH
+    }
+
+    // from text coordinates, the baseline is the x axis
+    fn get_matrix(&self) -> Matrix {
+        Matrix::translate(self.x, self.y) * Matrix::rotate(self.rotation.to_radians())
+    }
+
+    fn contains(&self, pt: &Point2d) -> bool {
+        match self.get_matrix().inverse() {
+            Some(inverse) => {
+                let local = inverse.multiply(pt);
+                (0.0..=self.get_width()).contains(&local.x)
+                    && (0.0..=self.height).contains(&local.y)
+            }
+            None => false,
+        }
+    }
+
+    fn get_corners(&self) -> [Point2d; 4] {
+        let matrix = self.get_matrix();
+        let (w, h) = (self.get_width(), self.height);
+        [(0.0, 0.0), (w, 0.0), (w, h), (0.0, h)].map(|(x, y)| matrix.multiply(&Point2d::new(x, y)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotated_text() {
+        let mut text = Text::new("t".to_string(), Point2d::new(10.0, 0.0), 5.0, "ab".into());
+        text.rotation = 90.0;
+        let bbox = text.get_bbox().unwrap();
+        assert_eq!(algebra::round(bbox.min.x), 5.0);
+        assert_eq!(algebra::round(bbox.max.y), 6.0);
+        assert_eq!(text.distance(&Point2d::new(8.0, 3.0)), Some(0.0));
+        assert!(text.intersects(&BBox::new(Point2d::new(7.0, 1.0), Point2d::new(8.0, 2.0))));
+        assert!(!text.intersects(&BBox::new(Point2d::new(11.0, 1.0), Point2d::new(12.0, 2.0))));
+    }
+}
@@ -0,0 +1,189 @@
+//
+// a point in time in whole seconds, written as rfc 3339 in utc,
+// e.g. "2026-10-19T08:30:00Z"
+//
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp {
+    // since 1970-01-01T00:00:00Z
+    seconds: i64,
+}
+
+impl Timestamp {
+    pub fn from_unix_seconds(seconds: i64) -> Self {
+        Timestamp { seconds }
+    }
+
+    pub fn unix_seconds(&self) -> i64 {
+        self.seconds
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn now() -> Self {
+        let seconds = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as i64)
+            .unwrap_or(0);
+        Timestamp { seconds }
+    }
+
+    // the system time is not available in the browser
+    #[cfg(target_arch = "wasm32")]
+    pub fn now() -> Self {
+        let seconds = (js_sys::Date::now() / 1000.0).floor() as i64;
+        Timestamp { seconds }
+    }
+}
+
+impl std::fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let days = self.seconds.div_euclid(86400);
+        let time = self.seconds.rem_euclid(86400);
+        let (year, month, day) = civil_from_days(days);
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            year,
+            month,
+            day,
+            time / 3600,
+            time / 60 % 60,
+            time % 60
+        )
+    }
+}
+
+// fractions of seconds are dropped, other offsets than Z are converted to utc
+impl std::str::FromStr for Timestamp {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid timestamp '{}'", s);
+        let bytes = s.as_bytes();
+        if bytes.len() < 20
+            || bytes[4] != b'-'
+            || bytes[7] != b'-'
+            || !matches!(bytes[10], b'T' | b't' | b' ')
+            || bytes[13] != b':'
+            || bytes[16] != b':'
+        {
+            return Err(invalid());
+        }
+        let number = |range: std::ops::Range<usize>| -> Result<i64, String> {
+            let text = s.get(range).ok_or_else(invalid)?;
+            match text.bytes().all(|b| b.is_ascii_digit()) {
+                true => text.parse().map_err(|_| invalid()),
+                false => Err(invalid()),
+            }
+        };
+        let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+        let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+        let days_in_month = match month {
+            2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            1..=12 => 31,
+            _ => 0,
+        };
+        if day < 1 || day > days_in_month || hour > 23 || minute > 59 || second > 60 {
+            return Err(invalid());
+        }
+
+        let mut rest = &s[19..];
+        if let Some(fraction) = rest.strip_prefix('.') {
+            let digits = fraction.bytes().take_while(|b| b.is_ascii_digit()).count();
+            if digits == 0 {
+                return Err(invalid());
+            }
+            rest = &fraction[digits..];
+        }
+        let offset = match rest {
+            "Z" | "z" => 0,
+            _ if rest.len() == 6 && rest.as_bytes()[3] == b':' => {
+                let minutes =
+                    number(s.len() - 5..s.len() - 3)? * 60 + number(s.len() - 2..s.len())?;
+                match rest.as_bytes()[0] {
+                    b'+' => minutes,
+                    b'-' => -minutes,
+                    _ => return Err(invalid()),
+                }
+            }
+            _ => return Err(invalid()),
+        };
+        let seconds = days_from_civil(year, month, day) * 86400
+            + hour * 3600
+            + (minute - offset) * 60
+            + second.min(59);
+        Ok(Timestamp { seconds })
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+// days since 1970-01-01 of a date in the proleptic gregorian calendar,
+// the years start in march so the leap day is the last day of a year
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_and_parse() {
+        let epoch = Timestamp::from_unix_seconds(0);
+        assert_eq!(epoch.to_string(), "1970-01-01T00:00:00Z");
+        let time = Timestamp::from_unix_seconds(1_803_457_800);
+        assert_eq!(time.to_string(), "2027-02-24T08:30:00Z");
+        assert_eq!("2027-02-24T08:30:00Z".parse(), Ok(time));
+        assert_eq!("2027-02-24T10:30:00.125+02:00".parse(), Ok(time));
+        let leap = "2024-02-29T00:00:00Z".parse::<Timestamp>().unwrap();
+        assert_eq!(leap.to_string(), "2024-02-29T00:00:00Z");
+        let before = Timestamp::from_unix_seconds(-1);
+        assert_eq!(before.to_string(), "1969-12-31T23:59:59Z");
+
+        for text in [
+            "2023-02-29T00:00:00Z",
+            "2026-10-19",
+            "2026-10-19T08:30:00",
+            "2026-13-01T00:00:00Z",
+            "2026-10-19T08:30:00.Z",
+            "2026-1a-19T08:30:00Z",
+        ] {
+            assert!(text.parse::<Timestamp>().is_err(), "{}", text);
+        }
+    }
+}
@@ -0,0 +1,50 @@
+//
+// ascii dxf import and export
+//
+// read: LINE, ARC, CIRCLE, LWPOLYLINE, POLYLINE, TEXT and INSERT from R12 (AC1009)
+// up to newer versions, inserted blocks are exploded into their nodes.
+// write: R12 or R2000 (AC1015) with a layer table of the used layers
+//
+// the layer "0" of dxf is the default layer of a node, an empty name
+//
+
+mod reader;
+mod shape;
+mod writer;
+
+pub use reader::*;
+pub use writer::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::tests::read;
+    use datamodel::Node;
+
+    const R12: &str = include_str!("../samples/r12.dxf");
+    const R2000: &str = include_str!("../samples/r2000.dxf");
+
+    fn to_json(nodes: &[Box<dyn Node>]) -> serde_json::Value {
+        serde_json::Value::Array(
+            nodes
+                .iter()
+                .map(|node| serde_json::to_value(node.as_ref()).unwrap())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn round_trip() {
+        for sample in [R12, R2000] {
+            let first = read(sample).unwrap();
+            let nodes: Vec<&dyn Node> = first.nodes.iter().map(|node| node.as_ref()).collect();
+            for version in [DxfVersion::R12, DxfVersion::R2000] {
+                let text = write_dxf(&nodes, version);
+                let second = read(&text).unwrap();
+                assert_eq!(second.version, version.acadver());
+                assert!(second.skipped.is_empty());
+                assert_eq!(to_json(&first.nodes), to_json(&second.nodes));
+            }
+        }
+    }
+}
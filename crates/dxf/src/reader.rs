@@ -0,0 +1,496 @@
+//
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use algebra::{Matrix, Point2d};
+use datamodel::{Node, NodeId};
+
+use crate::shape::{Geometry, Shape};
+
+// inserts of inserts of ... a block that inserts itself ends here
+const MAX_NESTING: usize = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DxfError {
+    pub message: String,
+    // line in the file, starting with 1
+    pub line: usize,
+}
+
+impl DxfError {
+    pub(crate) fn new(message: impl Into<String>, line: usize) -> Self {
+        DxfError {
+            message: message.into(),
+            line,
+        }
+    }
+}
+
+impl fmt::Display for DxfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for DxfError {}
+
+#[derive(Debug)]
+pub struct DxfImport {
+    pub nodes: Vec<Box<dyn Node>>,
+    // $ACADVER of the header, e.g. AC1009 for R12
+    pub version: String,
+    // entity type -> number of entities that are not supported
+    pub skipped: BTreeMap<String, usize>,
+}
+
+// files before R2007 are written in the code page of the system,
+// text that is not utf-8 is read as latin-1
+pub fn decode_dxf(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => bytes.iter().map(|&b| b as char).collect(),
+    }
+}
+
+// next_id gives the ids of the new nodes
+pub fn read_dxf(text: &str, next_id: &mut dyn FnMut() -> NodeId) -> Result<DxfImport, DxfError> {
+    let mut reader = Reader {
+        groups: tokenize(text)?,
+        pos: 0,
+    };
+    let mut version = String::new();
+    let mut blocks = HashMap::new();
+    let mut entities = vec![];
+    loop {
+        let group = reader.next("SECTION or EOF")?;
+        match (group.code, group.value.trim()) {
+            (0, "EOF") => break,
+            (0, "SECTION") => {}
+            _ => return Err(group.unexpected("SECTION")),
+        }
+        let name = reader.next("section name")?;
+        match name.value.trim() {
+            "HEADER" => version = reader.read_header()?,
+            "BLOCKS" => blocks = read_blocks(reader.read_entities()?)?,
+            "ENTITIES" => entities = reader.read_entities()?,
+            _ => reader.skip_section()?,
+        }
+    }
+
+    let mut import = DxfImport {
+        nodes: vec![],
+        version,
+        skipped: BTreeMap::new(),
+    };
+    let mut shapes = vec![];
+    for entity in &entities {
+        explode(
+            entity,
+            &blocks,
+            &Matrix::identity(),
+            None,
+            0,
+            &mut shapes,
+            &mut import.skipped,
+        )?;
+    }
+    for shape in shapes {
+        import.nodes.extend(shape.into_nodes(next_id));
+    }
+    Ok(import)
+}
+
+#[derive(Debug, Clone)]
+struct Group {
+    code: i32,
+    value: String,
+    line: usize,
+}
+
+impl Group {
+    fn unexpected(&self, expected: &str) -> DxfError {
+        DxfError::new(
+            format!(
+                "Expected {}, found {} '{}'",
+                expected,
+                self.code,
+                self.value.trim()
+            ),
+            self.line,
+        )
+    }
+}
+
+// pairs of lines: group code and value
+fn tokenize(text: &str) -> Result<Vec<Group>, DxfError> {
+    let mut groups = vec![];
+    let mut lines = text.lines().enumerate();
+    while let Some((index, code)) = lines.next() {
+        let code = code.trim();
+        if code.is_empty() {
+            continue;
+        }
+        let code = code
+            .parse::<i32>()
+            .map_err(|_| DxfError::new(format!("Invalid group code '{}'", code), index + 1))?;
+        let (index, value) = lines.next().ok_or_else(|| {
+            DxfError::new(format!("Missing value of group code {}", code), index + 1)
+        })?;
+        groups.push(Group {
+            code,
+            value: value.to_string(),
+            line: index + 1,
+        });
+    }
+    Ok(groups)
+}
+
+struct Reader {
+    groups: Vec<Group>,
+    pos: usize,
+}
+
+impl Reader {
+    fn next(&mut self, expected: &str) -> Result<Group, DxfError> {
+        let group = self.groups.get(self.pos).cloned().ok_or_else(|| {
+            let line = self.groups.last().map_or(0, |group| group.line);
+            DxfError::new(
+                format!("Expected {}, found the end of the file", expected),
+                line,
+            )
+        })?;
+        self.pos += 1;
+        Ok(group)
+    }
+
+    fn peek(&self) -> Option<&Group> {
+        self.groups.get(self.pos)
+    }
+
+    fn read_header(&mut self) -> Result<String, DxfError> {
+        let mut version = String::new();
+        loop {
+            let group = self.next("ENDSEC")?;
+            match (group.code, group.value.trim()) {
+                (0, "ENDSEC") => return Ok(version),
+                (9, "$ACADVER") => version = self.next("version")?.value.trim().to_string(),
+                _ => {}
+            }
+        }
+    }
+
+    fn skip_section(&mut self) -> Result<(), DxfError> {
+        loop {
+            let group = self.next("ENDSEC")?;
+            if group.code == 0 && group.value.trim() == "ENDSEC" {
+                return Ok(());
+            }
+        }
+    }
+
+    // the entities of a section up to ENDSEC
+    fn read_entities(&mut self) -> Result<Vec<Entity>, DxfError> {
+        let mut entities = vec![];
+        loop {
+            let mut entity = self.read_entity()?;
+            match entity.kind.as_str() {
+                "ENDSEC" => return Ok(entities),
+                // followed by VERTEX or ATTRIB entities up to SEQEND
+                "POLYLINE" => entity.children = self.read_sequence()?,
+                "INSERT" if entity.integer(66)? & 1 == 1 => {
+                    entity.children = self.read_sequence()?;
+                }
+                _ => {}
+            }
+            entities.push(entity);
+        }
+    }
+
+    fn read_sequence(&mut self) -> Result<Vec<Entity>, DxfError> {
+        let mut children = vec![];
+        loop {
+            let entity = self.read_entity()?;
+            match entity.kind.as_str() {
+                "SEQEND" => return Ok(children),
+                "ENDSEC" => return Err(DxfError::new("Missing SEQEND", entity.line)),
+                _ => children.push(entity),
+            }
+        }
+    }
+
+    fn read_entity(&mut self) -> Result<Entity, DxfError> {
+        let start = self.next("entity")?;
+        if start.code != 0 {
+            return Err(start.unexpected("entity"));
+        }
+        let mut groups = vec![];
+        while let Some(group) = self.peek() {
+            if group.code == 0 {
+                break;
+            }
+            groups.push(group.clone());
+            self.pos += 1;
+        }
+        Ok(Entity {
+            kind: start.value.trim().to_string(),
+            line: start.line,
+            groups,
+            children: vec![],
+        })
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Entity {
+    pub(crate) kind: String,
+    pub(crate) line: usize,
+    groups: Vec<Group>,
+    pub(crate) children: Vec<Entity>,
+}
+
+impl Entity {
+    fn group(&self, code: i32) -> Option<&Group> {
+        self.groups.iter().find(|group| group.code == code)
+    }
+
+    pub(crate) fn text(&self, code: i32) -> &str {
+        self.group(code).map_or("", |group| group.value.as_str())
+    }
+
+    pub(crate) fn number(&self, code: i32, default: f64) -> Result<f64, DxfError> {
+        match self.group(code) {
+            Some(group) => parse_number(group),
+            None => Ok(default),
+        }
+    }
+
+    pub(crate) fn integer(&self, code: i32) -> Result<i64, DxfError> {
+        match self.group(code) {
+            Some(group) => group.value.trim().parse().map_err(|_| {
+                DxfError::new(
+                    format!("Invalid integer '{}'", group.value.trim()),
+                    group.line,
+                )
+            }),
+            None => Ok(0),
+        }
+    }
+
+    // x in x_code, y in x_code + 10
+    pub(crate) fn point(&self, x_code: i32) -> Result<Point2d, DxfError> {
+        Ok(Point2d::new(
+            self.number(x_code, 0.0)?,
+            self.number(x_code + 10, 0.0)?,
+        ))
+    }
+
+    // the groups in file order, e.g. the vertices of a LWPOLYLINE
+    pub(crate) fn numbers(&self) -> impl Iterator<Item = Result<(i32, f64), DxfError>> + '_ {
+        self.groups
+            .iter()
+            .filter(|group| matches!(group.code, 10 | 20 | 42))
+            .map(|group| Ok((group.code, parse_number(group)?)))
+    }
+
+    pub(crate) fn layer(&self) -> &str {
+        self.text(8).trim()
+    }
+}
+
+fn parse_number(group: &Group) -> Result<f64, DxfError> {
+    group.value.trim().parse().map_err(|_| {
+        DxfError::new(
+            format!("Invalid number '{}'", group.value.trim()),
+            group.line,
+        )
+    })
+}
+
+struct Block {
+    base: Point2d,
+    entities: Vec<Entity>,
+}
+
+// BLOCK, its entities, ENDBLK
+fn read_blocks(entities: Vec<Entity>) -> Result<HashMap<String, Block>, DxfError> {
+    let mut blocks = HashMap::new();
+    let mut current: Option<(String, Block)> = None;
+    for entity in entities {
+        match entity.kind.as_str() {
+            "BLOCK" => {
+                let block = Block {
+                    base: entity.point(10)?,
+                    entities: vec![],
+                };
+                current = Some((entity.text(2).trim().to_string(), block));
+            }
+            "ENDBLK" => {
+                if let Some((name, block)) = current.take() {
+                    blocks.insert(name, block);
+                }
+            }
+            _ => match &mut current {
+                Some((_, block)) => block.entities.push(entity),
+                None => return Err(DxfError::new("Entity outside of a block", entity.line)),
+            },
+        }
+    }
+    Ok(blocks)
+}
+
+// the shapes of an entity placed by matrix, inserts are resolved.
+// entities of a block on layer "0" get the layer of the insert
+fn explode(
+    entity: &Entity,
+    blocks: &HashMap<String, Block>,
+    matrix: &Matrix,
+    insert_layer: Option<&str>,
+    depth: usize,
+    shapes: &mut Vec<Shape>,
+    skipped: &mut BTreeMap<String, usize>,
+) -> Result<(), DxfError> {
+    let layer = match (entity.layer(), insert_layer) {
+        ("0", Some(insert_layer)) => insert_layer,
+        (layer, _) => layer,
+    };
+    if entity.kind != "INSERT" {
+        match Geometry::from_entity(entity)? {
+            Some(geometry) => shapes.push(Shape {
+                layer: layer.to_string(),
+                geometry: geometry.transform(matrix, entity.line)?,
+            }),
+            None => *skipped.entry(entity.kind.clone()).or_default() += 1,
+        }
+        return Ok(());
+    }
+
+    let name = entity.text(2).trim();
+    let block = blocks
+        .get(name)
+        .ok_or_else(|| DxfError::new(format!("Unknown block '{}'", name), entity.line))?;
+    if depth == MAX_NESTING {
+        return Err(DxfError::new(
+            format!("Block '{}' is nested too deep", name),
+            entity.line,
+        ));
+    }
+    let position = entity.point(10)?;
+    let insert = Matrix::translate(position.x, position.y)
+        * Matrix::rotate(entity.number(50, 0.0)?.to_radians())
+        * Matrix::scale(entity.number(41, 1.0)?, entity.number(42, 1.0)?)
+        * Matrix::translate(-block.base.x, -block.base.y);
+    let matrix = *matrix * insert;
+    for child in &block.entities {
+        explode(
+            child,
+            blocks,
+            &matrix,
+            Some(layer),
+            depth + 1,
+            shapes,
+            skipped,
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use datamodel::{Arc, Line, Polyline, Text};
+
+    // ids 1, 2, 3 ..., also for the round trips in lib.rs
+    pub(crate) fn read(text: &str) -> Result<DxfImport, DxfError> {
+        let mut counter = 0;
+        read_dxf(text, &mut || {
+            counter += 1;
+            counter.to_string().into()
+        })
+    }
+
+    fn node<T: 'static>(import: &DxfImport, index: usize) -> &T {
+        import.nodes[index].as_any().downcast_ref::<T>().unwrap()
+    }
+
+    #[test]
+    fn read_r12() {
+        let import = read(include_str!("../samples/r12.dxf")).unwrap();
+        assert_eq!(import.version, "AC1009");
+        assert_eq!(import.nodes.len(), 7);
+
+        let line = node::<Line>(&import, 0);
+        assert_eq!((line.x1, line.y1, line.x2, line.y2), (0.0, 0.0, 100.0, 0.0));
+        assert_eq!(line.get_layer(), "");
+        let circle = node::<Arc>(&import, 1);
+        assert_eq!(circle.get_layer(), "Walls");
+        assert_eq!((circle.get_r(), circle.get_angle_end()), (25.0, 360.0));
+        let polyline = node::<Polyline>(&import, 3);
+        assert!(polyline.closed);
+        assert_eq!(polyline.points.len(), 4);
+        assert_eq!(node::<Text>(&import, 4).text, "Ground floor 20°");
+
+        // the door block is inserted at 100,0 rotated by 90 degrees,
+        // its layer 0 entities take the layer of the insert
+        let leaf = node::<Line>(&import, 5);
+        assert_eq!(leaf.get_layer(), "Doors");
+        assert_eq!(
+            (algebra::round(leaf.x2), algebra::round(leaf.y2)),
+            (100.0, 20.0)
+        );
+        let swing = node::<Arc>(&import, 6);
+        assert_eq!(swing.get_center(), Point2d::new(100.0, 0.0));
+        assert_eq!(algebra::round(swing.get_angle_start()), 0.0);
+        assert_eq!(algebra::round(swing.get_angle_end()), 90.0);
+    }
+
+    #[test]
+    fn read_r2000() {
+        let import = read(include_str!("../samples/r2000.dxf")).unwrap();
+        assert_eq!(import.version, "AC1015");
+        assert_eq!(import.skipped.get("MTEXT"), Some(&1));
+
+        // a lwpolyline with a bulge is split into lines and arcs
+        let types: Vec<String> = import
+            .nodes
+            .iter()
+            .map(|node| node.get_node_type().to_string())
+            .collect();
+        assert_eq!(
+            types,
+            ["Line", "Arc", "Line", "Polyline", "Text", "Arc", "Line", "Line"]
+        );
+        let arc = node::<Arc>(&import, 1);
+        assert_eq!(arc.get_center(), Point2d::new(100.0, 25.0));
+        assert_eq!(
+            (
+                algebra::round(arc.get_angle_start()),
+                algebra::round(arc.get_angle_end())
+            ),
+            (-90.0, 90.0)
+        );
+        let text = node::<Text>(&import, 4);
+        assert_eq!((text.text.as_str(), text.rotation), ("Tür ⌀10", 30.0));
+
+        // nested block scaled by 2 and mirrored
+        let line = node::<Line>(&import, 7);
+        assert_eq!(
+            (line.x1, line.y1, line.x2, line.y2),
+            (300.0, 0.0, 280.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let err = read("0\nSECTION\n2\nENTITIES\nx\nLINE\n").unwrap_err();
+        assert_eq!(err, DxfError::new("Invalid group code 'x'", 5));
+        let err =
+            read("0\nSECTION\n2\nENTITIES\n0\nLINE\n10\nabc\n0\nENDSEC\n0\nEOF\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 8: Invalid number 'abc'");
+        let err = read("0\nSECTION\n2\nENTITIES\n0\nLINE\n").unwrap_err();
+        assert_eq!(err.message, "Expected entity, found the end of the file");
+        let err =
+            read("0\nSECTION\n2\nENTITIES\n0\nINSERT\n2\nX\n0\nENDSEC\n0\nEOF\n").unwrap_err();
+        assert_eq!(err, DxfError::new("Unknown block 'X'", 6));
+    }
+}
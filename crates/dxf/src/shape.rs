@@ -0,0 +1,348 @@
+//
+// the geometry of a dxf entity between reading and creating the nodes
+//
+
+use algebra::{arc_point, Matrix, Point2d};
+use datamodel::{Arc, Line, Node, NodeId, Polyline, Text};
+
+use crate::reader::{DxfError, Entity};
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Geometry {
+    Line(Point2d, Point2d),
+    // start == end is a circle
+    Arc {
+        center: Point2d,
+        r: f64,
+        start: f64,
+        end: f64,
+    },
+    // each vertex with the bulge of the segment that starts there,
+    // tan(sweep / 4), positive is counter clockwise
+    Polyline {
+        vertices: Vec<(Point2d, f64)>,
+        closed: bool,
+    },
+    Text {
+        position: Point2d,
+        height: f64,
+        rotation: f64,
+        text: String,
+    },
+}
+
+#[derive(Debug)]
+pub(crate) struct Shape {
+    pub(crate) layer: String,
+    pub(crate) geometry: Geometry,
+}
+
+impl Geometry {
+    // None for entities that are not supported
+    pub(crate) fn from_entity(entity: &Entity) -> Result<Option<Self>, DxfError> {
+        let geometry = match entity.kind.as_str() {
+            "LINE" => Geometry::Line(entity.point(10)?, entity.point(11)?),
+            "CIRCLE" => Geometry::Arc {
+                center: entity.point(10)?,
+                r: entity.number(40, 0.0)?,
+                start: 0.0,
+                end: 360.0,
+            },
+            "ARC" => Geometry::Arc {
+                center: entity.point(10)?,
+                r: entity.number(40, 0.0)?,
+                start: entity.number(50, 0.0)?,
+                end: entity.number(51, 360.0)?,
+            },
+            "LWPOLYLINE" => {
+                let mut vertices: Vec<(Point2d, f64)> = vec![];
+                for group in entity.numbers() {
+                    let (code, value) = group?;
+                    match (code, vertices.last_mut()) {
+                        (10, _) => vertices.push((Point2d::new(value, 0.0), 0.0)),
+                        (20, Some((pt, _))) => pt.y = value,
+                        (42, Some((_, bulge))) => *bulge = value,
+                        _ => {}
+                    }
+                }
+                Geometry::Polyline {
+                    vertices,
+                    closed: entity.integer(70)? & 1 == 1,
+                }
+            }
+            "POLYLINE" => {
+                // 3d polylines and meshes
+                if entity.integer(70)? & (8 | 16 | 64) != 0 {
+                    return Ok(None);
+                }
+                let vertices = entity
+                    .children
+                    .iter()
+                    .filter(|child| child.kind == "VERTEX")
+                    .map(|vertex| Ok((vertex.point(10)?, vertex.number(42, 0.0)?)))
+                    .collect::<Result<_, DxfError>>()?;
+                Geometry::Polyline {
+                    vertices,
+                    closed: entity.integer(70)? & 1 == 1,
+                }
+            }
+            // the alignment points are ignored, the text starts at the insertion point
+            "TEXT" => Geometry::Text {
+                position: entity.point(10)?,
+                height: entity.number(40, 1.0)?,
+                rotation: entity.number(50, 0.0)?,
+                text: decode_text(entity.text(1)),
+            },
+            _ => return Ok(None),
+        };
+        Ok(Some(geometry))
+    }
+
+    // e.g. the placement of a block, arcs, bulges and texts can not be
+    // scaled differently in x and y
+    pub(crate) fn transform(self, matrix: &Matrix, line: usize) -> Result<Self, DxfError> {
+        if *matrix == Matrix::identity() {
+            return Ok(self);
+        }
+        let mirrored = matrix.is_mirrored();
+        let scale = matrix.uniform_scale();
+        let needs_uniform = || {
+            scale.ok_or_else(|| {
+                DxfError::new("Arcs and texts can not be scaled non-uniformly", line)
+            })
+        };
+
+        let geometry = match self {
+            Geometry::Line(p1, p2) => Geometry::Line(matrix.multiply(&p1), matrix.multiply(&p2)),
+            Geometry::Arc {
+                center,
+                r,
+                start,
+                end,
+            } => {
+                let scale = needs_uniform()?;
+                let new_center = matrix.multiply(&center);
+                let angle = |angle: f64| {
+                    let pt = matrix.multiply(&arc_point(&center, r, angle));
+                    (pt.y - new_center.y)
+                        .atan2(pt.x - new_center.x)
+                        .to_degrees()
+                };
+                let (start, end) = if is_circle(start, end) {
+                    (0.0, 360.0)
+                } else if mirrored {
+                    (angle(end), angle(start))
+                } else {
+                    (angle(start), angle(end))
+                };
+                Geometry::Arc {
+                    center: new_center,
+                    r: r * scale,
+                    start,
+                    end,
+                }
+            }
+            Geometry::Polyline { vertices, closed } => {
+                if vertices.iter().any(|(_, bulge)| *bulge != 0.0) {
+                    needs_uniform()?;
+                }
+                let sign = if mirrored { -1.0 } else { 1.0 };
+                Geometry::Polyline {
+                    vertices: vertices
+                        .into_iter()
+                        .map(|(pt, bulge)| (matrix.multiply(&pt), sign * bulge))
+                        .collect(),
+                    closed,
+                }
+            }
+            Geometry::Text {
+                position,
+                height,
+                rotation,
+                text,
+            } => {
+                let scale = needs_uniform()?;
+                let (sin, cos) = rotation.to_radians().sin_cos();
+                let direction = matrix.multiply_vector(&Point2d::new(cos, sin));
+                Geometry::Text {
+                    position: matrix.multiply(&position),
+                    height: height * scale,
+                    rotation: direction.y.atan2(direction.x).to_degrees(),
+                    text,
+                }
+            }
+        };
+        Ok(geometry)
+    }
+}
+
+impl Shape {
+    // a polyline with bulges becomes lines and arcs
+    pub(crate) fn into_nodes(self, next_id: &mut dyn FnMut() -> NodeId) -> Vec<Box<dyn Node>> {
+        let mut nodes: Vec<Box<dyn Node>> = vec![];
+        match self.geometry {
+            Geometry::Line(p1, p2) => nodes.push(Box::new(line(next_id(), p1, p2))),
+            Geometry::Arc {
+                center,
+                r,
+                start,
+                end,
+            } => nodes.push(Box::new(Arc::new(
+                next_id(),
+                center.x,
+                center.y,
+                r,
+                start,
+                end,
+            ))),
+            Geometry::Polyline { vertices, closed } => {
+                if vertices.iter().all(|(_, bulge)| *bulge == 0.0) {
+                    let points = vertices.into_iter().map(|(pt, _)| pt).collect();
+                    nodes.push(Box::new(Polyline::new(next_id(), points, closed)));
+                } else {
+                    let n = vertices.len();
+                    let count = if closed { n } else { n.saturating_sub(1) };
+                    for i in 0..count {
+                        let (p1, bulge) = vertices[i];
+                        let (p2, _) = vertices[(i + 1) % n];
+                        match bulge_arc(&p1, &p2, bulge) {
+                            Some(arc) => nodes.push(Box::new(arc(next_id()))),
+                            None => nodes.push(Box::new(line(next_id(), p1, p2))),
+                        }
+                    }
+                }
+            }
+            Geometry::Text {
+                position,
+                height,
+                rotation,
+                text,
+            } => {
+                let mut node = Text::new(next_id(), position, height, text);
+                node.rotation = rotation;
+                nodes.push(Box::new(node));
+            }
+        }
+        if self.layer != "0" {
+            for node in &mut nodes {
+                node.set_layer(&self.layer);
+            }
+        }
+        nodes
+    }
+}
+
+pub(crate) fn is_circle(start: f64, end: f64) -> bool {
+    algebra::arc_sweep(start, end) == 360.0
+}
+
+fn line(id: NodeId, p1: Point2d, p2: Point2d) -> Line {
+    let mut line = Line::new(id);
+    (line.x1, line.y1, line.x2, line.y2) = (p1.x, p1.y, p2.x, p2.y);
+    line
+}
+
+// the arc from p1 to p2, None for a straight segment
+fn bulge_arc(p1: &Point2d, p2: &Point2d, bulge: f64) -> Option<impl FnOnce(NodeId) -> Arc> {
+    let chord = p1.distance(p2);
+    if bulge == 0.0 || chord == 0.0 {
+        return None;
+    }
+    // sagitta and the left normal of the chord
+    let half = chord / 2.0;
+    let sagitta = bulge * half;
+    let normal = Point2d::new(p1.y - p2.y, p2.x - p1.x) / chord;
+    let center = (*p1 + *p2) / 2.0 + normal * ((half * half - sagitta * sagitta) / (2.0 * sagitta));
+    let r = center.distance(p1);
+    let angle = |pt: &Point2d| (pt.y - center.y).atan2(pt.x - center.x).to_degrees();
+    // arcs run counter clockwise
+    let (start, end) = if bulge > 0.0 {
+        (angle(p1), angle(p2))
+    } else {
+        (angle(p2), angle(p1))
+    };
+    Some(move |id| Arc::new(id, center.x, center.y, r, start, end))
+}
+
+// \U+00FC and the control codes %%d, %%p and %%c
+fn decode_text(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        if let Some(hex) = rest.strip_prefix("\\U+").and_then(|r| r.get(..4)) {
+            if let Some(c) = u32::from_str_radix(hex, 16).ok().and_then(char::from_u32) {
+                result.push(c);
+                rest = &rest[7..];
+                continue;
+            }
+        }
+        let special = [
+            ("%%d", '°'),
+            ("%%D", '°'),
+            ("%%p", '±'),
+            ("%%P", '±'),
+            ("%%c", '⌀'),
+            ("%%C", '⌀'),
+            ("%%%", '%'),
+        ]
+        .iter()
+        .find(|(code, _)| rest.starts_with(code));
+        if let Some((code, c)) = special {
+            result.push(*c);
+            rest = &rest[code.len()..];
+            continue;
+        }
+        let c = rest.chars().next().unwrap_or_default();
+        result.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    result
+}
+
+// the text of a group value: non ascii characters as \U+XXXX
+pub(crate) fn encode_text(text: &str) -> String {
+    let mut result = String::new();
+    for c in text.chars() {
+        match c {
+            '%' => result.push_str("%%%"),
+            c if c.is_ascii_control() => result.push(' '),
+            c if c.is_ascii() => result.push(c),
+            c if (c as u32) <= 0xFFFF => result.push_str(&format!("\\U+{:04X}", c as u32)),
+            // outside of the basic plane
+            _ => result.push('?'),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_codes() {
+        assert_eq!(
+            decode_text("%%c10 %%p0.1 20%%d \\U+00FCber"),
+            "⌀10 ±0.1 20° über"
+        );
+        assert_eq!(decode_text("100%"), "100%");
+        for text in ["Tür ⌀10", "50%% off", "a\\b"] {
+            assert_eq!(decode_text(&encode_text(text)), text);
+        }
+    }
+
+    #[test]
+    fn bulge() {
+        // a half circle to the right of the chord
+        let p1 = Point2d::new(0.0, 0.0);
+        let p2 = Point2d::new(10.0, 0.0);
+        let arc = bulge_arc(&p1, &p2, 1.0).unwrap()("a".into());
+        assert_eq!(arc.get_center(), Point2d::new(5.0, 0.0));
+        assert_eq!((arc.get_angle_start(), arc.get_angle_end()), (180.0, 0.0));
+        // a quarter circle to the left
+        let arc = bulge_arc(&p1, &p2, -(22.5f64.to_radians().tan())).unwrap()("a".into());
+        assert_eq!(algebra::round(arc.get_center().y), -5.0);
+        assert_eq!(algebra::round(arc.get_r()), 7.07);
+        assert!(bulge_arc(&p1, &p2, 0.0).is_none());
+    }
+}
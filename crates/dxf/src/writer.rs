@@ -0,0 +1,235 @@
+//
+
+use std::collections::BTreeSet;
+use std::fmt::Display;
+
+use datamodel::{Arc, Line, Node, Polyline, Text};
+
+use crate::shape::{encode_text, is_circle};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DxfVersion {
+    // polylines are written as POLYLINE with VERTEX entities
+    R12,
+    // LWPOLYLINE, handles and subclass markers
+    #[default]
+    R2000,
+}
+
+impl DxfVersion {
+    pub fn acadver(&self) -> &'static str {
+        match self {
+            DxfVersion::R12 => "AC1009",
+            DxfVersion::R2000 => "AC1015",
+        }
+    }
+}
+
+impl std::str::FromStr for DxfVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "r12" | "ac1009" => Ok(DxfVersion::R12),
+            "r2000" | "ac1015" => Ok(DxfVersion::R2000),
+            _ => Err(format!("Unknown dxf version '{}', use r12 or r2000", s)),
+        }
+    }
+}
+
+// nodes without a dxf entity, e.g. pages, are left out.
+// only the sections HEADER, TABLES and ENTITIES are written, which is
+// enough for the common readers but not a complete R2000 object structure
+pub fn write_dxf(nodes: &[&dyn Node], version: DxfVersion) -> String {
+    let mut writer = Writer {
+        version,
+        text: String::new(),
+        // 1 .. 0xF are left to the tables
+        handle: 0x10,
+    };
+    let layers: BTreeSet<&str> = nodes
+        .iter()
+        .map(|node| layer_name(*node))
+        .chain(["0"])
+        .collect();
+
+    let mut body = Writer {
+        text: String::new(),
+        ..writer
+    };
+    body.section("TABLES");
+    body.layer_table(&layers);
+    body.group(0, "ENDSEC");
+    body.section("ENTITIES");
+    for node in nodes {
+        body.node(*node);
+    }
+    body.group(0, "ENDSEC");
+    body.group(0, "EOF");
+
+    writer.section("HEADER");
+    writer.group(9, "$ACADVER");
+    writer.group(1, version.acadver());
+    if version == DxfVersion::R2000 {
+        writer.group(9, "$HANDSEED");
+        writer.group(5, format!("{:X}", body.handle));
+        // millimeter
+        writer.group(9, "$INSUNITS");
+        writer.group(70, 4);
+    }
+    writer.group(0, "ENDSEC");
+    writer.text.push_str(&body.text);
+    writer.text
+}
+
+fn layer_name(node: &dyn Node) -> &str {
+    match node.get_layer() {
+        "" => "0",
+        layer => layer,
+    }
+}
+
+struct Writer {
+    version: DxfVersion,
+    text: String,
+    handle: u32,
+}
+
+impl Writer {
+    fn group(&mut self, code: i32, value: impl Display) {
+        self.text.push_str(&format!("{:>3}\n{}\n", code, value));
+    }
+
+    fn section(&mut self, name: &str) {
+        self.group(0, "SECTION");
+        self.group(2, name);
+    }
+
+    fn r2000(&self) -> bool {
+        self.version == DxfVersion::R2000
+    }
+
+    fn handle(&mut self) {
+        if self.r2000() {
+            self.group(5, format!("{:X}", self.handle));
+            self.handle += 1;
+        }
+    }
+
+    fn subclass(&mut self, name: &str) {
+        if self.r2000() {
+            self.group(100, name);
+        }
+    }
+
+    fn layer_table(&mut self, layers: &BTreeSet<&str>) {
+        self.group(0, "TABLE");
+        self.group(2, "LAYER");
+        if self.r2000() {
+            self.group(5, "2");
+        }
+        self.subclass("AcDbSymbolTable");
+        self.group(70, layers.len());
+        for layer in layers {
+            self.group(0, "LAYER");
+            self.handle();
+            self.subclass("AcDbSymbolTableRecord");
+            self.subclass("AcDbLayerTableRecord");
+            self.group(2, encode_text(layer));
+            self.group(70, 0);
+            // white and continuous
+            self.group(62, 7);
+            self.group(6, "CONTINUOUS");
+        }
+        self.group(0, "ENDTAB");
+    }
+
+    fn entity(&mut self, kind: &str, node: &dyn Node) {
+        self.group(0, kind);
+        self.handle();
+        self.subclass("AcDbEntity");
+        self.group(8, encode_text(layer_name(node)));
+    }
+
+    fn point(&mut self, x_code: i32, x: f64, y: f64) {
+        self.group(x_code, x);
+        self.group(x_code + 10, y);
+    }
+
+    fn node(&mut self, node: &dyn Node) {
+        let any = node.as_any();
+        if let Some(line) = any.downcast_ref::<Line>() {
+            self.entity("LINE", node);
+            self.subclass("AcDbLine");
+            self.point(10, line.x1, line.y1);
+            self.point(11, line.x2, line.y2);
+        } else if let Some(arc) = any.downcast_ref::<Arc>() {
+            let circle = is_circle(arc.get_angle_start(), arc.get_angle_end());
+            self.entity(if circle { "CIRCLE" } else { "ARC" }, node);
+            self.subclass("AcDbCircle");
+            let center = arc.get_center();
+            self.point(10, center.x, center.y);
+            self.group(40, arc.get_r());
+            if !circle {
+                self.subclass("AcDbArc");
+                self.group(50, arc.get_angle_start());
+                self.group(51, arc.get_angle_end());
+            }
+        } else if let Some(polyline) = any.downcast_ref::<Polyline>() {
+            self.polyline(node, polyline);
+        } else if let Some(text) = any.downcast_ref::<Text>() {
+            self.entity("TEXT", node);
+            self.subclass("AcDbText");
+            self.point(10, text.x, text.y);
+            self.group(40, text.height);
+            self.group(1, encode_text(&text.text));
+            if text.rotation != 0.0 {
+                self.group(50, text.rotation);
+            }
+            self.subclass("AcDbText");
+        }
+    }
+
+    fn polyline(&mut self, node: &dyn Node, polyline: &Polyline) {
+        let flags = if polyline.closed { 1 } else { 0 };
+        if self.r2000() {
+            self.entity("LWPOLYLINE", node);
+            self.subclass("AcDbPolyline");
+            self.group(90, polyline.points.len());
+            self.group(70, flags);
+            for pt in &polyline.points {
+                self.point(10, pt.x, pt.y);
+            }
+            return;
+        }
+        self.entity("POLYLINE", node);
+        self.group(66, 1);
+        self.point(10, 0.0, 0.0);
+        self.group(70, flags);
+        for pt in &polyline.points {
+            self.entity("VERTEX", node);
+            self.point(10, pt.x, pt.y);
+        }
+        self.entity("SEQEND", node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use algebra::Point2d;
+
+    #[test]
+    fn write_r12() {
+        let mut text = Text::new("1".to_string(), Point2d::new(1.0, 2.0), 2.5, "Tür".into());
+        text.set_layer("Labels");
+        let polyline = Polyline::new("2".to_string(), vec![Point2d::new(0.0, 0.5)], true);
+        let dxf = write_dxf(&[&text, &polyline], DxfVersion::R12);
+        assert!(dxf.starts_with("  0\nSECTION\n  2\nHEADER\n  9\n$ACADVER\n  1\nAC1009\n"));
+        assert!(dxf.contains("  2\nLabels\n"));
+        assert!(dxf.contains("  0\nTEXT\n  8\nLabels\n 10\n1\n 20\n2\n 40\n2.5\n  1\nT\\U+00FCr\n"));
+        assert!(dxf.contains("  0\nPOLYLINE\n  8\n0\n 66\n1\n 10\n0\n 20\n0\n 70\n1\n"));
+        assert!(dxf.ends_with("  0\nSEQEND\n  8\n0\n  0\nENDSEC\n  0\nEOF\n"));
+        assert!(!dxf.contains("AcDb"));
+    }
+}
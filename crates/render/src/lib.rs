@@ -1,7 +1,19 @@
 //
 
+mod pdf;
+mod plot;
+mod raster;
 mod renderer;
+mod strokefont;
+mod surface;
+mod svg;
 mod transform;
 
+pub use pdf::*;
+pub use plot::*;
+pub use raster::*;
 pub use renderer::*;
+pub use strokefont::*;
+pub use surface::*;
+pub use svg::*;
 pub use transform::*;
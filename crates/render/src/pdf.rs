@@ -0,0 +1,240 @@
+//
+// pdf 1.4 without dependencies: one pdf page per page of the data model,
+// uncompressed content streams and the standard font Helvetica
+//
+
+use std::fmt::Write;
+
+use algebra::{BBox, Matrix, Point2d};
+use datamodel::{DataModel, Node, Page};
+
+use crate::plot::PlotSettings;
+use crate::surface::{draw_page, PathSegment, Surface};
+
+const POINTS_PER_MM: f64 = 72.0 / 25.4;
+
+// the content stream of a pdf page
+#[derive(Debug, Default)]
+pub struct PdfSurface {
+    content: String,
+}
+
+impl PdfSurface {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // only the area is painted
+    pub fn clip(&mut self, area: &BBox) {
+        let _ = writeln!(
+            self.content,
+            "{} {} {} {} re W n",
+            number(area.min.x),
+            number(area.min.y),
+            number(area.width()),
+            number(area.height())
+        );
+    }
+
+    pub fn into_content(self) -> String {
+        self.content
+    }
+
+    fn point(&mut self, pt: &Point2d) {
+        let _ = write!(self.content, "{} {} ", number(pt.x), number(pt.y));
+    }
+}
+
+impl Surface for PdfSurface {
+    fn stroke(&mut self, path: &[PathSegment], width: f64) {
+        let _ = writeln!(self.content, "{} w", number(width));
+        for segment in path {
+            match segment {
+                PathSegment::MoveTo(pt) => {
+                    self.point(pt);
+                    self.content.push_str("m\n");
+                }
+                PathSegment::LineTo(pt) => {
+                    self.point(pt);
+                    self.content.push_str("l\n");
+                }
+                PathSegment::CubicTo(c1, c2, pt) => {
+                    self.point(c1);
+                    self.point(c2);
+                    self.point(pt);
+                    self.content.push_str("c\n");
+                }
+                PathSegment::Close => self.content.push_str("h\n"),
+            }
+        }
+        self.content.push_str("S\n");
+    }
+
+    fn text(&mut self, text: &str, position: Point2d, height: f64, rotation: f64) {
+        let (sin, cos) = rotation.to_radians().sin_cos();
+        let _ = writeln!(
+            self.content,
+            "BT /F1 {} Tf {} {} {} {} {} {} Tm ({}) Tj ET",
+            number(height),
+            number(cos),
+            number(sin),
+            number(-sin),
+            number(cos),
+            number(position.x),
+            number(position.y),
+            encode_text(text)
+        );
+    }
+}
+
+// the pages with the nodes in draw order, each page on its own sheet
+pub fn write_pdf(data_model: &DataModel, pages: &[&Page], settings: &PlotSettings) -> Vec<u8> {
+    let (width, height) = settings.sheet_size();
+    let to_points = Matrix::scale(POINTS_PER_MM, POINTS_PER_MM);
+    let area = settings.printable_area();
+
+    // 1 catalog, 2 page tree, 3 font, then a page and its content per page
+    let kids: Vec<String> = (0..pages.len())
+        .map(|i| format!("{} 0 R", 4 + 2 * i))
+        .collect();
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            pages.len()
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+    ];
+    for (i, page) in pages.iter().enumerate() {
+        let extents = data_model
+            .get_spatial_index(page.get_id())
+            .and_then(|index| index.extents());
+        let matrix = to_points * settings.layout(extents.as_ref());
+
+        let mut surface = PdfSurface::new();
+        // round caps and joins
+        surface.content.push_str("1 J 1 j\n");
+        surface.clip(&BBox::new(
+            to_points.multiply(&area.min),
+            to_points.multiply(&area.max),
+        ));
+        let width_of = |layer: &str| settings.line_width_of(layer) * POINTS_PER_MM;
+        draw_page(&mut surface, data_model, page, &matrix, &width_of);
+        let content = surface.into_content();
+
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+             /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            number(width * POINTS_PER_MM),
+            number(height * POINTS_PER_MM),
+            5 + 2 * i
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            content.len(),
+            content
+        ));
+    }
+
+    // a comment with binary characters marks the file as binary
+    let mut pdf = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+    let mut offsets = vec![];
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).as_bytes());
+    }
+    let xref = pdf.len();
+    let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(trailer, "{:010} 00000 n ", offset);
+    }
+    let _ = write!(
+        trailer,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref
+    );
+    pdf.extend_from_slice(trailer.as_bytes());
+    pdf
+}
+
+// at most 3 decimals, no trailing zeros
+pub(crate) fn number(value: f64) -> String {
+    let text = format!("{:.3}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    match text {
+        "-0" => "0".to_string(),
+        _ => text.to_string(),
+    }
+}
+
+// a pdf string of WinAnsiEncoding, characters outside of latin-1 become '?'
+fn encode_text(text: &str) -> String {
+    let mut result = String::new();
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                result.push('\\');
+                result.push(c);
+            }
+            ' '..='~' => result.push(c),
+            '\u{a0}'..='\u{ff}' => {
+                let _ = write!(result, "\\{:03o}", c as u32);
+            }
+            _ => result.push('?'),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datamodel::{Line, Text};
+
+    #[test]
+    fn pages_and_xref() {
+        let mut dm = DataModel::default();
+        dm.insert_page(Page::new("p1", "first".into(), String::new()));
+        let mut line = Line::new("1".to_string());
+        (line.x2, line.y2) = (1000.0, 500.0);
+        dm.insert_node(Box::new(line));
+        dm.add_node_to_page("p1", "1");
+        let text = Text::new(
+            "2".to_string(),
+            Point2d::new(0.0, 0.0),
+            50.0,
+            "Tür (1)".into(),
+        );
+        dm.insert_node(Box::new(text));
+        dm.add_node_to_page("p1", "2");
+
+        let settings = PlotSettings {
+            scale: "1:10".parse().unwrap(),
+            ..Default::default()
+        };
+        let page = dm.get_page("p1").unwrap();
+        let pdf = write_pdf(&dm, &[page, page], &settings);
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.starts_with("%PDF-1.4\n"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains("/Count 2"));
+        // a4 landscape
+        assert!(text.contains("/MediaBox [0 0 841.89 595.276]"));
+        // 1000 units at 1:10 are 100 mm, centered on the sheet at 148.5, 105 mm
+        assert!(text.contains("0.709 w\n279.213 226.772 m\n562.677 368.504 l\nS\n"));
+        assert!(
+            text.contains("BT /F1 14.173 Tf 1 0 0 1 279.213 226.772 Tm (T\\374r \\(1\\)) Tj ET")
+        );
+
+        // the offsets of the cross reference table
+        let xref = text.rfind("startxref\n").unwrap();
+        let start: usize = text[xref + 10..].lines().next().unwrap().parse().unwrap();
+        assert!(pdf[start..].starts_with(b"xref\n0 8\n"));
+        let table = String::from_utf8_lossy(&pdf[start..]);
+        let first: usize = table.lines().nth(3).unwrap()[..10].parse().unwrap();
+        assert!(pdf[first..].starts_with(b"1 0 obj\n<< /Type /Catalog"));
+    }
+}
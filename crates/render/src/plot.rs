@@ -0,0 +1,230 @@
+//
+// the layout of a page on a sheet of paper, all paper sizes in millimeters
+//
+
+use std::collections::BTreeMap;
+
+use algebra::{BBox, Matrix};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Paper {
+    // portrait
+    pub width: f64,
+    pub height: f64,
+}
+
+impl std::str::FromStr for Paper {
+    type Err = String;
+
+    // a0 .. a4, letter, legal, tabloid or width x height, e.g. 500x300
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (width, height) = match s.to_ascii_lowercase().as_str() {
+            "a0" => (841.0, 1189.0),
+            "a1" => (594.0, 841.0),
+            "a2" => (420.0, 594.0),
+            "a3" => (297.0, 420.0),
+            "a4" => (210.0, 297.0),
+            "letter" => (215.9, 279.4),
+            "legal" => (215.9, 355.6),
+            "tabloid" => (279.4, 431.8),
+            size => size
+                .split_once('x')
+                .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+                .filter(|(w, h): &(f64, f64)| *w > 0.0 && *h > 0.0)
+                .ok_or_else(|| {
+                    format!(
+                        "Unknown paper '{}', use a0 .. a4, letter, legal, tabloid or <width>x<height>",
+                        s
+                    )
+                })?,
+        };
+        Ok(Paper { width, height })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Orientation {
+    Portrait,
+    #[default]
+    Landscape,
+}
+
+impl std::str::FromStr for Orientation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "portrait" | "p" => Ok(Orientation::Portrait),
+            "landscape" | "l" => Ok(Orientation::Landscape),
+            _ => Err(format!(
+                "Unknown orientation '{}', use portrait or landscape",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PlotScale {
+    // the nodes of the page fill the printable area
+    #[default]
+    Fit,
+    // drawing units per millimeter on paper, 1:50 is 50
+    Ratio(f64),
+}
+
+impl std::str::FromStr for PlotScale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("fit") {
+            return Ok(PlotScale::Fit);
+        }
+        s.split_once(':')
+            .and_then(|(paper, model)| Some((paper.parse().ok()?, model.parse().ok()?)))
+            .filter(|(paper, model): &(f64, f64)| *paper > 0.0 && *model > 0.0)
+            .map(|(paper, model)| PlotScale::Ratio(model / paper))
+            .ok_or_else(|| format!("Unknown scale '{}', use fit or e.g. 1:50", s))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlotSettings {
+    pub paper: Paper,
+    pub orientation: Orientation,
+    pub scale: PlotScale,
+    // on all sides of the sheet
+    pub margin: f64,
+    // the width of the lines on paper
+    pub line_width: f64,
+    // layer -> line width, other layers use line_width
+    pub layer_widths: BTreeMap<String, f64>,
+}
+
+impl Default for PlotSettings {
+    fn default() -> Self {
+        Self {
+            paper: Paper {
+                width: 210.0,
+                height: 297.0,
+            },
+            orientation: Orientation::default(),
+            scale: PlotScale::default(),
+            margin: 10.0,
+            line_width: 0.25,
+            layer_widths: BTreeMap::new(),
+        }
+    }
+}
+
+impl PlotSettings {
+    // width and height of the sheet
+    pub fn sheet_size(&self) -> (f64, f64) {
+        let Paper { width, height } = self.paper;
+        match self.orientation {
+            Orientation::Portrait => (width.min(height), width.max(height)),
+            Orientation::Landscape => (width.max(height), width.min(height)),
+        }
+    }
+
+    // the sheet without the margins
+    pub fn printable_area(&self) -> BBox {
+        let (width, height) = self.sheet_size();
+        let margin = self.margin.clamp(0.0, width.min(height) / 2.0);
+        BBox::new(
+            algebra::Point2d::new(margin, margin),
+            algebra::Point2d::new(width - margin, height - margin),
+        )
+    }
+
+    // drawing coordinates to millimeters on the sheet, the extents of the
+    // page are centered on the printable area
+    pub fn layout(&self, extents: Option<&BBox>) -> Matrix {
+        let area = self.printable_area();
+        let Some(extents) = extents else {
+            return Matrix::translate(area.min.x, area.min.y);
+        };
+        let factor = match self.scale {
+            PlotScale::Ratio(units_per_mm) => 1.0 / units_per_mm,
+            PlotScale::Fit => {
+                let fit = |paper: f64, model: f64| {
+                    if model > 0.0 {
+                        paper / model
+                    } else {
+                        f64::INFINITY
+                    }
+                };
+                let factor =
+                    fit(area.width(), extents.width()).min(fit(area.height(), extents.height()));
+                // a single point
+                if factor.is_finite() {
+                    factor
+                } else {
+                    1.0
+                }
+            }
+        };
+        let (sheet, model) = (area.center(), extents.center());
+        Matrix::translate(sheet.x, sheet.y)
+            * Matrix::scale(factor, factor)
+            * Matrix::translate(-model.x, -model.y)
+    }
+
+    pub fn line_width_of(&self, layer: &str) -> f64 {
+        self.layer_widths
+            .get(layer)
+            .copied()
+            .unwrap_or(self.line_width)
+    }
+
+    // "Walls:0.5,Text:0.18"
+    pub fn parse_layer_widths(&mut self, text: &str) -> Result<(), String> {
+        for item in text.split(',').filter(|item| !item.is_empty()) {
+            let (layer, width) = item
+                .rsplit_once(':')
+                .and_then(|(layer, width)| Some((layer, width.parse::<f64>().ok()?)))
+                .filter(|(_, width)| *width >= 0.0)
+                .ok_or_else(|| format!("Invalid line width '{}', use <layer>:<mm>", item))?;
+            self.layer_widths.insert(layer.to_string(), width);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use algebra::Point2d;
+
+    #[test]
+    fn layout() {
+        let mut settings = PlotSettings {
+            paper: "a3".parse().unwrap(),
+            scale: "1:50".parse().unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(settings.sheet_size(), (420.0, 297.0));
+        assert_eq!(settings.scale, PlotScale::Ratio(50.0));
+
+        // 10 m at 1:50 are 200 mm, centered on the sheet
+        let extents = BBox::new(Point2d::new(0.0, 0.0), Point2d::new(10000.0, 5000.0));
+        let matrix = settings.layout(Some(&extents));
+        assert_eq!(matrix.multiply(&extents.min), Point2d::new(110.0, 98.5));
+        assert_eq!(matrix.multiply(&extents.max), Point2d::new(310.0, 198.5));
+
+        settings.scale = PlotScale::Fit;
+        settings.orientation = "portrait".parse().unwrap();
+        let matrix = settings.layout(Some(&extents));
+        assert_eq!(matrix.multiply(&extents.min), Point2d::new(10.0, 140.75));
+
+        settings.parse_layer_widths("Walls:0.5,a:b:0.18").unwrap();
+        assert_eq!(settings.line_width_of("Walls"), 0.5);
+        assert_eq!(settings.line_width_of("a:b"), 0.18);
+        assert_eq!(settings.line_width_of(""), 0.25);
+        assert!(settings.parse_layer_widths("Walls").is_err());
+
+        assert_eq!("500x300".parse::<Paper>().unwrap().width, 500.0);
+        assert!("b5".parse::<Paper>().is_err());
+        assert!("1:0".parse::<PlotScale>().is_err());
+    }
+}
@@ -0,0 +1,389 @@
+//
+// png images of a page, anti-aliased on the cpu with tiny-skia
+//
+
+use algebra::{Matrix, Point2d, Viewport};
+use datamodel::{DataModel, Node, Page};
+use tiny_skia::{LineCap, LineJoin, Paint, PathBuilder, Pixmap, Stroke, Transform};
+
+use crate::strokefont::text_strokes;
+use crate::surface::{draw_page, PathSegment, Surface};
+
+const MM_PER_INCH: f64 = 25.4;
+// pixels per side, a larger image is not allocated
+pub const MAX_IMAGE_SIZE: u32 = 4096;
+
+pub fn check_image_size(width: u32, height: u32) -> Result<(), String> {
+    if width == 0 || height == 0 || width > MAX_IMAGE_SIZE || height > MAX_IMAGE_SIZE {
+        return Err(format!(
+            "Invalid image size {}x{}, use 1 to {} pixels per side",
+            width, height, MAX_IMAGE_SIZE
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RasterOptions {
+    // pixels
+    pub width: u32,
+    pub height: u32,
+    // pixels per inch, for the line widths and the png header
+    pub dpi: f64,
+    // millimeters
+    pub line_width: f64,
+}
+
+impl Default for RasterOptions {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            height: 600,
+            dpi: 96.0,
+            line_width: 0.25,
+        }
+    }
+}
+
+impl RasterOptions {
+    fn pixels(&self, mm: f64) -> f64 {
+        mm / MM_PER_INCH * self.dpi
+    }
+}
+
+// straight, not premultiplied rgba pixels row by row
+#[derive(Debug, Clone, PartialEq)]
+pub struct RasterImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl RasterImage {
+    pub fn to_png(&self, dpi: f64) -> Result<Vec<u8>, String> {
+        let mut bytes = vec![];
+        let mut encoder = png::Encoder::new(&mut bytes, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let pixels_per_meter = (dpi / MM_PER_INCH * 1000.0).round() as u32;
+        encoder.set_pixel_dims(Some(png::PixelDimensions {
+            xppu: pixels_per_meter,
+            yppu: pixels_per_meter,
+            unit: png::Unit::Meter,
+        }));
+        let mut writer = encoder.write_header().map_err(|err| err.to_string())?;
+        writer
+            .write_image_data(&self.pixels)
+            .map_err(|err| err.to_string())?;
+        writer.finish().map_err(|err| err.to_string())?;
+        Ok(bytes)
+    }
+
+    // 8 bit rgb or rgba, e.g. a reference image of a test
+    pub fn from_png(bytes: &[u8]) -> Result<Self, String> {
+        let decoder = png::Decoder::new(bytes);
+        let mut reader = decoder.read_info().map_err(|err| err.to_string())?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader
+            .next_frame(&mut buffer)
+            .map_err(|err| err.to_string())?;
+        buffer.truncate(info.buffer_size());
+        let pixels = match (info.color_type, info.bit_depth) {
+            (png::ColorType::Rgba, png::BitDepth::Eight) => buffer,
+            (png::ColorType::Rgb, png::BitDepth::Eight) => buffer
+                .chunks_exact(3)
+                .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+                .collect(),
+            (color_type, bit_depth) => {
+                return Err(format!(
+                    "Unsupported png of {:?} with {:?} bits",
+                    color_type, bit_depth
+                ))
+            }
+        };
+        Ok(RasterImage {
+            width: info.width,
+            height: info.height,
+            pixels,
+        })
+    }
+
+    // the number of pixels with a channel that differs by more than the
+    // tolerance, None if the sizes differ
+    pub fn diff(&self, other: &RasterImage, tolerance: u8) -> Option<usize> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return None;
+        }
+        let count = self
+            .pixels
+            .chunks_exact(4)
+            .zip(other.pixels.chunks_exact(4))
+            .filter(|(a, b)| {
+                a.iter()
+                    .zip(b.iter())
+                    .any(|(a, b)| a.abs_diff(*b) > tolerance)
+            })
+            .count();
+        Some(count)
+    }
+}
+
+// the pixels of the image with y up, drawn black on a transparent pixmap
+pub struct RasterSurface {
+    pixmap: Pixmap,
+    paint: Paint<'static>,
+}
+
+impl RasterSurface {
+    pub fn new(width: u32, height: u32) -> Result<Self, String> {
+        check_image_size(width, height)?;
+        let pixmap = Pixmap::new(width, height)
+            .ok_or_else(|| format!("Invalid image size {}x{}", width, height))?;
+        let mut paint = Paint::default();
+        paint.set_color_rgba8(0, 0, 0, 255);
+        paint.anti_alias = true;
+        Ok(Self { pixmap, paint })
+    }
+
+    pub fn fill(&mut self, red: u8, green: u8, blue: u8) {
+        self.pixmap
+            .fill(tiny_skia::Color::from_rgba8(red, green, blue, 255));
+    }
+
+    pub fn into_image(self) -> RasterImage {
+        let pixels = self
+            .pixmap
+            .pixels()
+            .iter()
+            .flat_map(|pixel| {
+                let color = pixel.demultiply();
+                [color.red(), color.green(), color.blue(), color.alpha()]
+            })
+            .collect();
+        RasterImage {
+            width: self.pixmap.width(),
+            height: self.pixmap.height(),
+            pixels,
+        }
+    }
+
+    fn point(&self, pt: &Point2d) -> (f32, f32) {
+        (pt.x as f32, (self.pixmap.height() as f64 - pt.y) as f32)
+    }
+
+    fn stroke_path(&mut self, builder: PathBuilder, width: f64) {
+        let Some(path) = builder.finish() else {
+            return;
+        };
+        let stroke = Stroke {
+            width: width as f32,
+            line_cap: LineCap::Round,
+            line_join: LineJoin::Round,
+            ..Default::default()
+        };
+        self.pixmap
+            .stroke_path(&path, &self.paint, &stroke, Transform::identity(), None);
+    }
+}
+
+impl Surface for RasterSurface {
+    fn stroke(&mut self, path: &[PathSegment], width: f64) {
+        let mut builder = PathBuilder::new();
+        for segment in path {
+            match segment {
+                PathSegment::MoveTo(pt) => {
+                    let (x, y) = self.point(pt);
+                    builder.move_to(x, y);
+                }
+                PathSegment::LineTo(pt) => {
+                    let (x, y) = self.point(pt);
+                    builder.line_to(x, y);
+                }
+                PathSegment::CubicTo(c1, c2, pt) => {
+                    let ((x1, y1), (x2, y2), (x, y)) =
+                        (self.point(c1), self.point(c2), self.point(pt));
+                    builder.cubic_to(x1, y1, x2, y2, x, y);
+                }
+                PathSegment::Close => builder.close(),
+            }
+        }
+        self.stroke_path(builder, width);
+    }
+
+    // the stroke font, the lines get thicker with the height
+    fn text(&mut self, text: &str, position: Point2d, height: f64, rotation: f64) {
+        let mut builder = PathBuilder::new();
+        for stroke in text_strokes(text, position, height, rotation) {
+            for (i, pt) in stroke.iter().enumerate() {
+                let (x, y) = self.point(pt);
+                match i {
+                    0 => builder.move_to(x, y),
+                    _ => builder.line_to(x, y),
+                }
+            }
+        }
+        self.stroke_path(builder, (height / 10.0).max(1.0));
+    }
+}
+
+// the view box of the viewport fills the image, centered like an svg with
+// the same viewBox. the matrix maps drawing coordinates to pixels with y up
+pub fn raster_matrix(viewport: &Viewport, width: u32, height: u32) -> Matrix {
+    let (width, height) = (width as f64, height as f64);
+    let scale = (width / viewport.width).min(height / viewport.height);
+    let offset_x = (width - viewport.width * scale) / 2.0;
+    let offset_y = (height - viewport.height * scale) / 2.0;
+    Matrix::translate(0.0, height)
+        * Matrix::scale(1.0, -1.0)
+        * Matrix::translate(offset_x, offset_y)
+        * Matrix::scale(scale, scale)
+        * Matrix::translate(-viewport.x, -viewport.y)
+        * viewport.get_view_matrix()
+}
+
+// the last view of the page, or all of its nodes
+pub fn page_viewport(data_model: &DataModel, page: &Page, width: u32, height: u32) -> Viewport {
+    let mut viewport = Viewport::new();
+    viewport.set_canvas_size(width as f64, height as f64);
+    if let Some(view) = page.get_view() {
+        viewport.set_view(view);
+    } else if let Some(extents) = data_model
+        .get_spatial_index(page.get_id())
+        .and_then(|index| index.extents())
+    {
+        viewport.zoom_extents(&extents);
+    }
+    viewport
+}
+
+// the nodes of the page on a white background
+pub fn rasterize(
+    data_model: &DataModel,
+    page: &Page,
+    viewport: &Viewport,
+    options: &RasterOptions,
+) -> Result<RasterImage, String> {
+    let mut surface = RasterSurface::new(options.width, options.height)?;
+    surface.fill(255, 255, 255);
+    let matrix = raster_matrix(viewport, options.width, options.height);
+    let line_width = options.pixels(options.line_width);
+    draw_page(&mut surface, data_model, page, &matrix, &|_| line_width);
+    Ok(surface.into_image())
+}
+
+pub fn render_png(
+    data_model: &DataModel,
+    page: &Page,
+    viewport: &Viewport,
+    options: &RasterOptions,
+) -> Result<Vec<u8>, String> {
+    rasterize(data_model, page, viewport, options)?.to_png(options.dpi)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datamodel::{Arc, Line, Node, Polyline, Text};
+
+    const REFERENCE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/samples/page.png");
+
+    fn sample() -> DataModel {
+        let mut dm = DataModel::default();
+        dm.insert_page(Page::new("p1", "sample".into(), String::new()));
+        let mut line = Line::new("1".to_string());
+        (line.x2, line.y2) = (100.0, 50.0);
+        let polyline = Polyline::new(
+            "2".to_string(),
+            vec![
+                Point2d::new(0.0, 0.0),
+                Point2d::new(100.0, 0.0),
+                Point2d::new(100.0, 60.0),
+                Point2d::new(0.0, 60.0),
+            ],
+            true,
+        );
+        let arc = Arc::new("3".to_string(), 70.0, 20.0, 15.0, 0.0, 270.0);
+        let text = Text::new(
+            "4".to_string(),
+            Point2d::new(5.0, 45.0),
+            8.0,
+            "Plan 1:50".into(),
+        );
+        let nodes: Vec<Box<dyn Node>> = vec![
+            Box::new(line),
+            Box::new(polyline),
+            Box::new(arc),
+            Box::new(text),
+        ];
+        for node in nodes {
+            let id = node.get_id().to_string();
+            dm.insert_node(node);
+            dm.add_node_to_page("p1", &id);
+        }
+        dm
+    }
+
+    #[test]
+    fn matrix() {
+        let mut viewport = Viewport::new();
+        viewport.set_canvas_size(200.0, 100.0);
+        // the view box gets the ratio of the canvas, 200 x 100
+        viewport.set_view(&algebra::View {
+            x: 0.0,
+            y: -100.0,
+            width: 100.0,
+            height: 100.0,
+            rotation: 0.0,
+        });
+        // and is centered in a square image
+        let matrix = raster_matrix(&viewport, 400, 400);
+        assert_eq!(
+            matrix.multiply(&Point2d::new(0.0, 0.0)),
+            Point2d::new(0.0, 100.0)
+        );
+        assert_eq!(
+            matrix.multiply(&Point2d::new(100.0, 100.0)),
+            Point2d::new(200.0, 300.0)
+        );
+    }
+
+    // set UPDATE_REFERENCE=1 to write the reference image after an intended change
+    #[test]
+    fn reference_image() {
+        let dm = sample();
+        let page = dm.get_page("p1").unwrap();
+        let options = RasterOptions {
+            width: 160,
+            height: 120,
+            ..Default::default()
+        };
+        let viewport = page_viewport(&dm, page, options.width, options.height);
+        let image = rasterize(&dm, page, &viewport, &options).unwrap();
+        let png = image.to_png(options.dpi).unwrap();
+        if std::env::var_os("UPDATE_REFERENCE").is_some() {
+            std::fs::write(REFERENCE, &png).unwrap();
+        }
+
+        let decoded = RasterImage::from_png(&png).unwrap();
+        assert_eq!(decoded, image);
+        let reference = RasterImage::from_png(&std::fs::read(REFERENCE).unwrap()).unwrap();
+        // anti-aliasing may differ slightly between platforms
+        assert!(image.diff(&reference, 16).unwrap() < 20);
+
+        // a dark pixel at the corner of the rectangle, white around the page
+        let pixel = |x: u32, y: u32| {
+            let i = ((y * image.width + x) * 4) as usize;
+            image.pixels[i..i + 4].to_vec()
+        };
+        let corner = raster_matrix(&viewport, 160, 120).multiply(&Point2d::new(100.0, 0.0));
+        let (x, y) = (corner.x as u32, 120 - corner.y as u32);
+        let darkest = (x - 1..=x + 1)
+            .flat_map(|x| (y - 1..=y + 1).map(move |y| (x, y)))
+            .map(|(x, y)| pixel(x, y)[0])
+            .min();
+        assert!(darkest.unwrap() < 128);
+        assert_eq!(pixel(0, 0), vec![255, 255, 255, 255]);
+        assert!(RasterSurface::new(0, 10).is_err());
+        assert!(RasterSurface::new(MAX_IMAGE_SIZE + 1, 10).is_err());
+    }
+}
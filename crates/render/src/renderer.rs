@@ -1,12 +1,14 @@
 //
 
-use algebra::Viewport;
+use std::collections::{HashMap, HashSet};
+
+use algebra::{Matrix, Viewport};
 use wasm_bindgen::prelude::*;
-use web_sys::{Document, Element, SvgElement, Window};
+use web_sys::{Document, Element, SvgElement};
 
-use datamodel::{Arc, DataModel, Line, Node, Page};
+use datamodel::{Arc, DataModel, Line, Node, Page, Polyline, Text};
 
-use crate::transform::{self, Transform};
+const SVG_NS: &str = "http://www.w3.org/2000/svg";
 
 #[wasm_bindgen]
 extern "C" {
@@ -21,12 +23,12 @@ pub fn round(x: f64) -> String {
 }
 
 trait Graphic: Node {
-    fn render(&self, document: &Document, svg_parent: &Element) -> Result<web_sys::Node, JsValue>;
+    fn create_element(&self, document: &Document) -> Result<Element, JsValue>;
 }
 
 impl Graphic for Line {
-    fn render(&self, document: &Document, svg_parent: &Element) -> Result<web_sys::Node, JsValue> {
-        let svg_line = document.create_element_ns(Some("http://www.w3.org/2000/svg"), "line")?;
+    fn create_element(&self, document: &Document) -> Result<Element, JsValue> {
+        let svg_line = document.create_element_ns(Some(SVG_NS), "line")?;
 
         svg_line.set_attribute("id", self.get_id())?;
         svg_line.set_attribute("x1", &round(self.get_x1()))?;
@@ -34,113 +36,330 @@ impl Graphic for Line {
         svg_line.set_attribute("x2", &round(self.get_x2()))?;
         svg_line.set_attribute("y2", &round(self.get_y2()))?;
 
-        svg_line.set_attribute("stroke", &"black")?;
+        svg_line.set_attribute("stroke", "black")?;
         svg_line.set_attribute("stroke-width", &round(1.0))?;
 
-        svg_parent.append_child(&svg_line)
+        Ok(svg_line)
     }
 }
 
 impl Graphic for Arc {
-    fn render(&self, document: &Document, svg_parent: &Element) -> Result<web_sys::Node, JsValue> {
-        let svg_arc = document.create_element_ns(Some("http://www.w3.org/2000/svg"), "path")?;
+    fn create_element(&self, document: &Document) -> Result<Element, JsValue> {
+        let svg_arc = document.create_element_ns(Some(SVG_NS), "path")?;
 
         //TODO: use the angle_start and angle_end and the values of the Arc struct
 
         svg_arc.set_attribute("id", "ABC")?;
         svg_arc.set_attribute("cx", "10")?;
         svg_arc.set_attribute("cy", "10")?;
-        svg_arc.set_attribute("r", &"20")?;
+        svg_arc.set_attribute("r", "20")?;
 
-        svg_parent.append_child(&svg_arc)
+        Ok(svg_arc)
     }
 }
 
-pub struct Renderer<'a> {
-    data_model: &'a DataModel,
-    viewport: &'a Viewport,
-    // window: Window,
-    document: Document,
+impl Graphic for Polyline {
+    fn create_element(&self, document: &Document) -> Result<Element, JsValue> {
+        let tag = if self.closed { "polygon" } else { "polyline" };
+        let svg_polyline = document.create_element_ns(Some(SVG_NS), tag)?;
+
+        let points: Vec<String> = self
+            .points
+            .iter()
+            .map(|pt| format!("{},{}", round(pt.x), round(pt.y)))
+            .collect();
+        svg_polyline.set_attribute("id", self.get_id())?;
+        svg_polyline.set_attribute("points", &points.join(" "))?;
+
+        svg_polyline.set_attribute("fill", "none")?;
+        svg_polyline.set_attribute("stroke", "black")?;
+        svg_polyline.set_attribute("stroke-width", &round(1.0))?;
+
+        Ok(svg_polyline)
+    }
 }
 
-impl<'a> Renderer<'a> {
-    pub fn new(data_model: &'a DataModel, viewport: &'a Viewport) -> Self {
-        let window: Window = web_sys::window().expect("no global `window` exists");
-        let document: Document = window.document().expect("should have a document on window");
+impl Graphic for Text {
+    fn create_element(&self, document: &Document) -> Result<Element, JsValue> {
+        let svg_text = document.create_element_ns(Some(SVG_NS), "text")?;
 
-        Self {
-            data_model,
-            viewport,
-            // window,
-            document,
-        }
+        // the root group flips y, the text is flipped back to stay readable
+        let transform = Matrix::translate(self.x, self.y)
+            * Matrix::rotate(self.rotation.to_radians())
+            * Matrix::scale(1.0, -1.0);
+        svg_text.set_attribute("id", self.get_id())?;
+        svg_text.set_attribute("transform", &transform.to_svg())?;
+        svg_text.set_attribute("font-size", &round(self.height))?;
+        svg_text.set_text_content(Some(&self.text));
+
+        Ok(svg_text)
     }
+}
 
-    pub fn render_page(&self, page: &Page) -> Result<(), JsValue> {
-        let svg_canvas: SvgElement = self.get_svg_element()?;
-        svg_canvas.set_inner_html(""); // Clear the canvas
+fn create_node_element(document: &Document, node: &dyn Node) -> Result<Option<Element>, JsValue> {
+    if let Some(line) = node.as_any().downcast_ref::<Line>() {
+        line.create_element(document).map(Some)
+    } else if let Some(arc) = node.as_any().downcast_ref::<Arc>() {
+        arc.create_element(document).map(Some)
+    } else if let Some(polyline) = node.as_any().downcast_ref::<Polyline>() {
+        polyline.create_element(document).map(Some)
+    } else if let Some(text) = node.as_any().downcast_ref::<Text>() {
+        text.create_element(document).map(Some)
+    } else {
+        Ok(None)
+    }
+}
 
-        let root_group = self.create_root_group()?;
-        svg_canvas.append_child(&root_group)?;
+// keeps the svg elements of the rendered page, so only changes
+// have to be applied to the DOM
+pub struct Renderer {
+    // looked up when the first page is rendered, so a renderer can be
+    // created without a DOM, e.g. in a worker or a test
+    document: Option<Document>,
+    page_id: Option<String>,
+    // carries the view transform
+    root_group: Option<SvgElement>,
+    grid_group: Option<Element>,
+    node_group: Option<Element>,
+    // node id -> svg element of the visible nodes
+    elements: HashMap<String, Element>,
+}
 
-        self.render_nodes(&root_group, &page.get_node_ids())?;
+impl Default for Renderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        Ok(())
+impl Renderer {
+    pub fn new() -> Self {
+        Self {
+            document: None,
+            page_id: None,
+            root_group: None,
+            grid_group: None,
+            node_group: None,
+            elements: HashMap::new(),
+        }
     }
 
-    // ----------------------
+    // rebuild the complete svg of the page
+    pub fn render_page(
+        &mut self,
+        data_model: &DataModel,
+        viewport: &Viewport,
+        page: &Page,
+    ) -> Result<(), JsValue> {
+        let document = self.document()?;
+        let svg_canvas: SvgElement = get_svg_element(&document, viewport)?;
+        svg_canvas.set_inner_html(""); // Clear the canvas
+        self.elements.clear();
 
-    fn get_svg_element(&self) -> Result<SvgElement, JsValue> {
-        let svg_canvas: SvgElement = self
-            .document
-            .get_element_by_id(self.viewport.get_canvas_id())
-            .expect("No element with your svg_canvas_id")
-            .dyn_into::<SvgElement>()
-            .map_err(|_| ())
-            .expect("The element with your_svg_id is not an SVGElement");
+        let root_group = create_root_group(&document, viewport)?;
+        svg_canvas.append_child(&root_group)?;
 
-        let viewport = format!(
-            "{} {} {} {}",
-            self.viewport.x, self.viewport.y, self.viewport.width, self.viewport.height,
-        );
+        let grid_group = document.create_element_ns(Some(SVG_NS), "g")?;
+        grid_group.set_attribute("id", "grid")?;
+        root_group.append_child(&grid_group)?;
+        let node_group = document.create_element_ns(Some(SVG_NS), "g")?;
+        node_group.set_attribute("id", "nodes")?;
+        root_group.append_child(&node_group)?;
 
-        svg_canvas.set_attribute("viewBox", &viewport)?;
+        self.page_id = Some(page.get_id().to_string());
+        self.root_group = Some(root_group);
+        self.grid_group = Some(grid_group);
+        self.node_group = Some(node_group);
 
-        Ok(svg_canvas)
+        self.render_grid(&document, data_model, viewport)?;
+        self.render_nodes(&document, data_model, viewport, page, &HashSet::new())
     }
 
-    fn create_root_group(&self) -> Result<SvgElement, JsValue> {
-        let svg_group = self
-            .document
-            .create_element_ns(Some("http://www.w3.org/2000/svg"), "g")
-            .expect("can't create svg group")
-            .dyn_into::<SvgElement>()
-            .map_err(|_| ())
-            .expect("The element with your_svg_id is not an SVGElement");
+    // pan and zoom: only the viewBox changes, nodes that became visible
+    // are added and nodes outside of the viewport are removed
+    pub fn update_viewport(
+        &mut self,
+        data_model: &DataModel,
+        viewport: &Viewport,
+        page: &Page,
+    ) -> Result<(), JsValue> {
+        if !self.is_rendered(page) {
+            return self.render_page(data_model, viewport, page);
+        }
+        let document = self.document()?;
+        get_svg_element(&document, viewport)?;
+        if let Some(root_group) = &self.root_group {
+            // the view may have been rotated
+            root_group.set_attribute("transform", &viewport.get_view_matrix().to_svg())?;
+        }
+        self.render_grid(&document, data_model, viewport)?;
+        self.render_nodes(&document, data_model, viewport, page, &HashSet::new())
+    }
 
-        svg_group.set_attribute("id", "root_group")?;
+    // added, removed or patched nodes
+    pub fn update_nodes(
+        &mut self,
+        data_model: &DataModel,
+        viewport: &Viewport,
+        page: &Page,
+        node_ids: &[String],
+    ) -> Result<(), JsValue> {
+        if !self.is_rendered(page) {
+            return self.render_page(data_model, viewport, page);
+        }
+        let document = self.document()?;
+        let changed: HashSet<&str> = node_ids.iter().map(|id| id.as_str()).collect();
+        self.render_nodes(&document, data_model, viewport, page, &changed)
+    }
 
-        // let transform = self.viewport.get_svg_transform_matrix();
-        svg_group.set_attribute("transform", "scale(1,-1)")?;
+    // ----------------------
 
-        Ok(svg_group)
+    fn document(&mut self) -> Result<Document, JsValue> {
+        if let Some(document) = &self.document {
+            return Ok(document.clone());
+        }
+        let document = web_sys::window()
+            .and_then(|window| window.document())
+            .ok_or_else(|| JsValue::from_str("No document to render the page to"))?;
+        self.document = Some(document.clone());
+        Ok(document)
+    }
 
-        // svg_group.set_attribute("transform", "transform(matrix(1,0,0,1,40,80))")?;
-        // svg_canvas.append_child(&svg_group)?;
+    fn is_rendered(&self, page: &Page) -> bool {
+        self.node_group.is_some() && self.page_id.as_deref() == Some(page.get_id())
+    }
 
-        // render_page(page, canvas_id)
+    // the grid depends on the viewport, so it is always drawn completely
+    fn render_grid(
+        &self,
+        document: &Document,
+        data_model: &DataModel,
+        viewport: &Viewport,
+    ) -> Result<(), JsValue> {
+        let Some(svg_grid) = &self.grid_group else {
+            return Ok(());
+        };
+        svg_grid.set_inner_html("");
+
+        let aids = data_model.get_drawing_aids();
+        if !aids.grid_on {
+            return Ok(());
+        }
+        for grid_line in aids.grid.lines(viewport) {
+            let svg_line = document.create_element_ns(Some(SVG_NS), "line")?;
+            svg_line.set_attribute("x1", &round(grid_line.from.x))?;
+            svg_line.set_attribute("y1", &round(grid_line.from.y))?;
+            svg_line.set_attribute("x2", &round(grid_line.to.x))?;
+            svg_line.set_attribute("y2", &round(grid_line.to.y))?;
+            let color = if grid_line.major {
+                "#c0c0c0"
+            } else {
+                "#e8e8e8"
+            };
+            svg_line.set_attribute("stroke", color)?;
+            svg_line.set_attribute("stroke-width", "1")?;
+            // keep the grid one pixel wide at every zoom level
+            svg_line.set_attribute("vector-effect", "non-scaling-stroke")?;
+            svg_grid.append_child(&svg_line)?;
+        }
+        Ok(())
     }
 
-    fn render_nodes(&self, svg_parent: &SvgElement, node_ids: &Vec<String>) -> Result<(), JsValue> {
-        for id in node_ids {
-            if let Some(node) = self.data_model.get_node(id) {
-                if let Some(line) = node.as_any().downcast_ref::<Line>() {
-                    line.render(&self.document, &svg_parent)?;
-                } else if let Some(arc) = node.as_any().downcast_ref::<Arc>() {
-                    arc.render(&self.document, &svg_parent)?;
+    // walk the visible nodes in draw order: existing elements are kept,
+    // changed ones replaced, missing ones inserted at their position and
+    // elements of nodes that are no longer visible are removed
+    fn render_nodes(
+        &mut self,
+        document: &Document,
+        data_model: &DataModel,
+        viewport: &Viewport,
+        page: &Page,
+        changed: &HashSet<&str>,
+    ) -> Result<(), JsValue> {
+        let Some(node_group) = self.node_group.clone() else {
+            return Ok(());
+        };
+
+        let visible_area = viewport.get_visible_area();
+        let visible_nodes = data_model.query_nodes(page, &visible_area);
+        let visible_ids: HashSet<&str> = visible_nodes
+            .iter()
+            .map(|node| node.get_id().as_str())
+            .collect();
+
+        let removed: Vec<String> = self
+            .elements
+            .keys()
+            .filter(|id| !visible_ids.contains(id.as_str()))
+            .cloned()
+            .collect();
+        for id in removed {
+            if let Some(element) = self.elements.remove(&id) {
+                element.remove();
+            }
+        }
+
+        let mut previous: Option<Element> = None;
+        for node in visible_nodes {
+            let id = node.get_id().as_str();
+            let existing = self.elements.get(id).cloned();
+            let element = match existing {
+                Some(element) if !changed.contains(id) => element,
+                _ => {
+                    let Some(new_element) = create_node_element(document, node)? else {
+                        continue;
+                    };
+                    if let Some(old_element) = existing {
+                        node_group.replace_child(&new_element, &old_element)?;
+                    }
+                    self.elements.insert(id.to_string(), new_element.clone());
+                    new_element
                 }
+            };
+            // new elements and, when the draw order changed, existing ones
+            // are moved behind the element drawn before them
+            let next = match &previous {
+                Some(previous) => previous.next_sibling(),
+                None => node_group.first_child(),
+            };
+            if !next
+                .as_ref()
+                .is_some_and(|next| next.is_same_node(Some(&element)))
+            {
+                node_group.insert_before(&element, next.as_ref())?;
             }
+            previous = Some(element);
         }
         Ok(())
     }
 }
+
+fn get_svg_element(document: &Document, viewport: &Viewport) -> Result<SvgElement, JsValue> {
+    let svg_canvas: SvgElement = document
+        .get_element_by_id(viewport.get_canvas_id())
+        .ok_or_else(|| JsValue::from_str("No element with the svg canvas id"))?
+        .dyn_into::<SvgElement>()
+        .map_err(|_| JsValue::from_str("The element with the svg canvas id is no svg element"))?;
+
+    let view_box = format!(
+        "{} {} {} {}",
+        viewport.x, viewport.y, viewport.width, viewport.height,
+    );
+
+    svg_canvas.set_attribute("viewBox", &view_box)?;
+
+    Ok(svg_canvas)
+}
+
+fn create_root_group(document: &Document, viewport: &Viewport) -> Result<SvgElement, JsValue> {
+    let svg_group = document
+        .create_element_ns(Some(SVG_NS), "g")?
+        .dyn_into::<SvgElement>()
+        .map_err(|_| JsValue::from_str("The root group is no svg element"))?;
+
+    svg_group.set_attribute("id", "root_group")?;
+
+    // model to view coordinates: y up and the rotation of the view
+    svg_group.set_attribute("transform", &viewport.get_view_matrix().to_svg())?;
+
+    Ok(svg_group)
+}
@@ -0,0 +1,230 @@
+//
+// a simple stroke font for the raster images, like the simplex fonts of cad
+// programs. the glyphs are drawn on a grid of 8 x 10 units with the
+// baseline at 0, x is halved so a character advances 0.6 of the height,
+// the width that Text::get_width assumes
+//
+
+use algebra::Point2d;
+
+type Glyph = &'static [&'static [(i8, i8)]];
+
+const ADVANCE: f64 = 12.0;
+const CAP_HEIGHT: f64 = 10.0;
+
+// the polylines of a text, height is the cap height
+pub fn text_strokes(
+    text: &str,
+    position: Point2d,
+    height: f64,
+    rotation: f64,
+) -> Vec<Vec<Point2d>> {
+    let scale = height / CAP_HEIGHT;
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    let mut strokes = vec![];
+    for (i, c) in text.chars().enumerate() {
+        let offset = i as f64 * ADVANCE;
+        let (glyph, accent) = glyph(c);
+        for stroke in glyph.iter().chain(accent) {
+            strokes.push(
+                stroke
+                    .iter()
+                    .map(|(x, y)| {
+                        // 2 units of space left and right of the 8 units of the glyph
+                        let (x, y) = ((offset + 2.0 + *x as f64) / 2.0 * scale, *y as f64 * scale);
+                        Point2d::new(
+                            position.x + x * cos - y * sin,
+                            position.y + x * sin + y * cos,
+                        )
+                    })
+                    .collect(),
+            );
+        }
+    }
+    strokes
+}
+
+// a few letters with diacritics are built from the base letter
+fn glyph(c: char) -> (Glyph, Glyph) {
+    const UMLAUT: Glyph = &[&[(2, 9), (2, 10)], &[(6, 9), (6, 10)]];
+    const UPPER_UMLAUT: Glyph = &[&[(2, 11), (2, 12)], &[(6, 11), (6, 12)]];
+    match c {
+        'ä' => (ascii('a'), UMLAUT),
+        'ö' => (ascii('o'), UMLAUT),
+        'ü' => (ascii('u'), UMLAUT),
+        'Ä' => (ascii('A'), UPPER_UMLAUT),
+        'Ö' => (ascii('O'), UPPER_UMLAUT),
+        'Ü' => (ascii('U'), UPPER_UMLAUT),
+        'ß' => (
+            &[&[
+                (0, -1),
+                (0, 8),
+                (2, 10),
+                (5, 10),
+                (7, 8),
+                (5, 6),
+                (8, 3),
+                (8, 1),
+                (7, 0),
+                (4, 0),
+            ]],
+            &[],
+        ),
+        '°' => (&[&[(4, 10), (3, 9), (4, 8), (5, 9), (4, 10)]], &[]),
+        '±' => (
+            &[&[(4, 9), (4, 3)], &[(1, 6), (7, 6)], &[(1, 1), (7, 1)]],
+            &[],
+        ),
+        '⌀' | 'ø' | 'Ø' => (ascii('o'), &[&[(0, -1), (8, 8)]]),
+        c => (ascii(c), &[]),
+    }
+}
+
+// characters without a glyph are shown as '?'
+#[rustfmt::skip]
+fn ascii(c: char) -> Glyph {
+    match c {
+        ' ' => &[],
+        '!' => &[&[(4, 10), (4, 3)], &[(4, 0), (4, 1)]],
+        '"' => &[&[(2, 10), (2, 7)], &[(6, 10), (6, 7)]],
+        '#' => &[&[(3, 10), (1, 0)], &[(7, 10), (5, 0)], &[(0, 7), (8, 7)], &[(0, 3), (8, 3)]],
+        '$' => &[
+            &[(8, 8), (6, 10), (2, 10), (0, 8), (0, 7), (2, 5), (6, 5), (8, 3), (8, 2), (6, 0), (2, 0), (0, 2)],
+            &[(4, 11), (4, -1)],
+        ],
+        '%' => &[
+            &[(0, 0), (8, 10)],
+            &[(1, 10), (0, 9), (1, 8), (2, 9), (1, 10)],
+            &[(7, 2), (6, 1), (7, 0), (8, 1), (7, 2)],
+        ],
+        '&' => &[&[(8, 0), (1, 7), (1, 9), (2, 10), (4, 10), (5, 9), (5, 7), (0, 3), (0, 1), (1, 0), (4, 0), (8, 4)]],
+        '\'' => &[&[(4, 10), (4, 7)]],
+        '(' => &[&[(6, 11), (4, 9), (3, 6), (3, 2), (4, -1), (6, -3)]],
+        ')' => &[&[(2, 11), (4, 9), (5, 6), (5, 2), (4, -1), (2, -3)]],
+        '*' => &[&[(4, 9), (4, 3)], &[(1, 8), (7, 4)], &[(1, 4), (7, 8)]],
+        '+' => &[&[(4, 8), (4, 2)], &[(1, 5), (7, 5)]],
+        ',' => &[&[(4, 1), (4, 0), (3, -2)]],
+        '-' => &[&[(1, 5), (7, 5)]],
+        '.' => &[&[(4, 0), (4, 1)]],
+        '/' => &[&[(0, 0), (8, 10)]],
+        '0' => &[&[(2, 0), (0, 2), (0, 8), (2, 10), (6, 10), (8, 8), (8, 2), (6, 0), (2, 0)], &[(1, 1), (7, 9)]],
+        '1' => &[&[(2, 8), (4, 10), (4, 0)], &[(2, 0), (6, 0)]],
+        '2' => &[&[(0, 8), (2, 10), (6, 10), (8, 8), (8, 6), (0, 0), (8, 0)]],
+        '3' => &[
+            &[(0, 8), (2, 10), (6, 10), (8, 8), (8, 7), (6, 5), (3, 5)],
+            &[(6, 5), (8, 3), (8, 2), (6, 0), (2, 0), (0, 2)],
+        ],
+        '4' => &[&[(6, 0), (6, 10), (0, 3), (8, 3)]],
+        '5' => &[&[(8, 10), (0, 10), (0, 5), (6, 5), (8, 3), (8, 2), (6, 0), (2, 0), (0, 2)]],
+        '6' => &[&[(7, 10), (3, 10), (0, 7), (0, 2), (2, 0), (6, 0), (8, 2), (8, 4), (6, 6), (2, 6), (0, 4)]],
+        '7' => &[&[(0, 10), (8, 10), (3, 0)]],
+        '8' => &[&[
+            (2, 5), (0, 7), (0, 8), (2, 10), (6, 10), (8, 8), (8, 7), (6, 5), (2, 5),
+            (0, 3), (0, 2), (2, 0), (6, 0), (8, 2), (8, 3), (6, 5),
+        ]],
+        '9' => &[&[(8, 6), (6, 4), (2, 4), (0, 6), (0, 8), (2, 10), (6, 10), (8, 8), (8, 3), (5, 0), (1, 0)]],
+        ':' => &[&[(4, 6), (4, 7)], &[(4, 0), (4, 1)]],
+        ';' => &[&[(4, 6), (4, 7)], &[(4, 1), (4, 0), (3, -2)]],
+        '<' => &[&[(8, 9), (0, 5), (8, 1)]],
+        '=' => &[&[(1, 7), (7, 7)], &[(1, 3), (7, 3)]],
+        '>' => &[&[(0, 9), (8, 5), (0, 1)]],
+        '?' => &[&[(0, 8), (2, 10), (6, 10), (8, 8), (8, 7), (4, 4), (4, 3)], &[(4, 0), (4, 1)]],
+        '@' => &[&[
+            (6, 3), (6, 6), (4, 7), (2, 5), (3, 3), (6, 3), (8, 5), (8, 8), (6, 10), (2, 10),
+            (0, 8), (0, 2), (2, 0), (7, 0),
+        ]],
+        'A' => &[&[(0, 0), (4, 10), (8, 0)], &[(2, 5), (6, 5)]],
+        'B' => &[
+            &[(0, 0), (0, 10), (5, 10), (7, 9), (7, 6), (5, 5), (0, 5)],
+            &[(5, 5), (8, 4), (8, 1), (6, 0), (0, 0)],
+        ],
+        'C' => &[&[(8, 8), (6, 10), (2, 10), (0, 8), (0, 2), (2, 0), (6, 0), (8, 2)]],
+        'D' => &[&[(0, 0), (0, 10), (5, 10), (8, 7), (8, 3), (5, 0), (0, 0)]],
+        'E' => &[&[(8, 10), (0, 10), (0, 0), (8, 0)], &[(0, 5), (6, 5)]],
+        'F' => &[&[(8, 10), (0, 10), (0, 0)], &[(0, 5), (6, 5)]],
+        'G' => &[&[(8, 8), (6, 10), (2, 10), (0, 8), (0, 2), (2, 0), (6, 0), (8, 2), (8, 5), (5, 5)]],
+        'H' => &[&[(0, 0), (0, 10)], &[(8, 0), (8, 10)], &[(0, 5), (8, 5)]],
+        'I' => &[&[(2, 10), (6, 10)], &[(4, 10), (4, 0)], &[(2, 0), (6, 0)]],
+        'J' => &[&[(8, 10), (8, 2), (6, 0), (2, 0), (0, 2)]],
+        'K' => &[&[(0, 0), (0, 10)], &[(8, 10), (0, 3)], &[(3, 6), (8, 0)]],
+        'L' => &[&[(0, 10), (0, 0), (8, 0)]],
+        'M' => &[&[(0, 0), (0, 10), (4, 4), (8, 10), (8, 0)]],
+        'N' => &[&[(0, 0), (0, 10), (8, 0), (8, 10)]],
+        'O' => &[&[(2, 0), (0, 2), (0, 8), (2, 10), (6, 10), (8, 8), (8, 2), (6, 0), (2, 0)]],
+        'P' => &[&[(0, 0), (0, 10), (6, 10), (8, 8), (8, 6), (6, 4), (0, 4)]],
+        'Q' => &[&[(2, 0), (0, 2), (0, 8), (2, 10), (6, 10), (8, 8), (8, 2), (6, 0), (2, 0)], &[(5, 3), (8, 0)]],
+        'R' => &[&[(0, 0), (0, 10), (6, 10), (8, 8), (8, 6), (6, 4), (0, 4)], &[(4, 4), (8, 0)]],
+        'S' => &[&[(8, 8), (6, 10), (2, 10), (0, 8), (0, 7), (2, 5), (6, 5), (8, 3), (8, 2), (6, 0), (2, 0), (0, 2)]],
+        'T' => &[&[(0, 10), (8, 10)], &[(4, 10), (4, 0)]],
+        'U' => &[&[(0, 10), (0, 2), (2, 0), (6, 0), (8, 2), (8, 10)]],
+        'V' => &[&[(0, 10), (4, 0), (8, 10)]],
+        'W' => &[&[(0, 10), (2, 0), (4, 6), (6, 0), (8, 10)]],
+        'X' => &[&[(0, 0), (8, 10)], &[(0, 10), (8, 0)]],
+        'Y' => &[&[(0, 10), (4, 5), (8, 10)], &[(4, 5), (4, 0)]],
+        'Z' => &[&[(0, 10), (8, 10), (0, 0), (8, 0)]],
+        '[' => &[&[(6, 11), (3, 11), (3, -3), (6, -3)]],
+        '\\' => &[&[(0, 10), (8, 0)]],
+        ']' => &[&[(2, 11), (5, 11), (5, -3), (2, -3)]],
+        '^' => &[&[(1, 7), (4, 10), (7, 7)]],
+        '_' => &[&[(0, -2), (8, -2)]],
+        '`' => &[&[(3, 10), (5, 8)]],
+        'a' => &[&[(8, 7), (8, 0)], &[(8, 5), (6, 7), (2, 7), (0, 5), (0, 2), (2, 0), (6, 0), (8, 2)]],
+        'b' => &[&[(0, 10), (0, 0)], &[(0, 5), (2, 7), (6, 7), (8, 5), (8, 2), (6, 0), (2, 0), (0, 2)]],
+        'c' => &[&[(8, 5), (6, 7), (2, 7), (0, 5), (0, 2), (2, 0), (6, 0), (8, 2)]],
+        'd' => &[&[(8, 10), (8, 0)], &[(8, 5), (6, 7), (2, 7), (0, 5), (0, 2), (2, 0), (6, 0), (8, 2)]],
+        'e' => &[&[(0, 4), (8, 4), (8, 5), (6, 7), (2, 7), (0, 5), (0, 2), (2, 0), (6, 0), (8, 1)]],
+        'f' => &[&[(7, 10), (5, 10), (3, 8), (3, 0)], &[(0, 7), (6, 7)]],
+        'g' => &[
+            &[(8, 7), (8, -1), (6, -3), (2, -3), (0, -2)],
+            &[(8, 5), (6, 7), (2, 7), (0, 5), (0, 2), (2, 0), (6, 0), (8, 2)],
+        ],
+        'h' => &[&[(0, 10), (0, 0)], &[(0, 5), (2, 7), (6, 7), (8, 5), (8, 0)]],
+        'i' => &[&[(4, 7), (4, 0)], &[(4, 9), (4, 10)]],
+        'j' => &[&[(5, 7), (5, -1), (3, -3), (1, -3)], &[(5, 9), (5, 10)]],
+        'k' => &[&[(0, 10), (0, 0)], &[(7, 7), (0, 2)], &[(3, 4), (8, 0)]],
+        'l' => &[&[(4, 10), (4, 0)]],
+        'm' => &[
+            &[(0, 7), (0, 0)],
+            &[(0, 5), (1, 7), (3, 7), (4, 5), (4, 0)],
+            &[(4, 5), (5, 7), (7, 7), (8, 5), (8, 0)],
+        ],
+        'n' => &[&[(0, 7), (0, 0)], &[(0, 5), (2, 7), (6, 7), (8, 5), (8, 0)]],
+        'o' => &[&[(2, 0), (0, 2), (0, 5), (2, 7), (6, 7), (8, 5), (8, 2), (6, 0), (2, 0)]],
+        'p' => &[&[(0, 7), (0, -3)], &[(0, 5), (2, 7), (6, 7), (8, 5), (8, 2), (6, 0), (2, 0), (0, 2)]],
+        'q' => &[&[(8, 7), (8, -3)], &[(8, 5), (6, 7), (2, 7), (0, 5), (0, 2), (2, 0), (6, 0), (8, 2)]],
+        'r' => &[&[(0, 7), (0, 0)], &[(0, 4), (3, 7), (7, 7)]],
+        's' => &[&[(8, 6), (6, 7), (2, 7), (0, 6), (0, 5), (2, 4), (6, 3), (8, 2), (8, 1), (6, 0), (2, 0), (0, 1)]],
+        't' => &[&[(3, 10), (3, 1), (4, 0), (6, 0)], &[(0, 7), (6, 7)]],
+        'u' => &[&[(0, 7), (0, 2), (2, 0), (6, 0), (8, 2)], &[(8, 7), (8, 0)]],
+        'v' => &[&[(0, 7), (4, 0), (8, 7)]],
+        'w' => &[&[(0, 7), (2, 0), (4, 5), (6, 0), (8, 7)]],
+        'x' => &[&[(0, 7), (8, 0)], &[(0, 0), (8, 7)]],
+        'y' => &[&[(0, 7), (4, 0)], &[(8, 7), (3, -3), (1, -3)]],
+        'z' => &[&[(0, 7), (8, 7), (0, 0), (8, 0)]],
+        '{' => &[&[(6, 11), (4, 10), (4, 6), (2, 4), (4, 2), (4, -2), (6, -3)]],
+        '|' => &[&[(4, 11), (4, -3)]],
+        '}' => &[&[(2, 11), (4, 10), (4, 6), (6, 4), (4, 2), (4, -2), (2, -3)]],
+        '~' => &[&[(0, 5), (2, 7), (6, 4), (8, 6)]],
+        _ => ascii('?'),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strokes() {
+        // the A of the second character, 2 units in, starts at 0.6 * height
+        let strokes = text_strokes("HA", Point2d::new(0.0, 0.0), 10.0, 0.0);
+        assert_eq!(strokes.len(), 5);
+        assert_eq!(strokes[3][0], Point2d::new(7.0, 0.0));
+        assert_eq!(strokes[3][1], Point2d::new(9.0, 10.0));
+
+        let strokes = text_strokes("ü", Point2d::new(0.0, 0.0), 10.0, 90.0);
+        assert_eq!(strokes.len(), 4);
+        assert!(strokes[0][0].distance(&Point2d::new(-7.0, 1.0)) < 1e-9);
+        assert!(text_strokes(" ", Point2d::new(0.0, 0.0), 1.0, 0.0).is_empty());
+        assert_eq!(text_strokes("€", Point2d::new(0.0, 0.0), 1.0, 0.0).len(), 2);
+    }
+}
@@ -0,0 +1,153 @@
+//
+// the nodes drawn to a backend without the DOM, e.g. a pdf page
+//
+
+use algebra::{Matrix, Point2d};
+use datamodel::{Arc, DataModel, Line, Node, Page, Polyline, Text};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathSegment {
+    MoveTo(Point2d),
+    LineTo(Point2d),
+    // two control points and the end point
+    CubicTo(Point2d, Point2d, Point2d),
+    Close,
+}
+
+// the coordinates are in the units of the surface with y up
+pub trait Surface {
+    fn stroke(&mut self, path: &[PathSegment], width: f64);
+
+    // the baseline starts at position, height is the font size and
+    // rotation the angle of the baseline in degrees
+    fn text(&mut self, text: &str, position: Point2d, height: f64, rotation: f64);
+}
+
+// the nodes of the page in draw order, matrix maps drawing coordinates to
+// the surface and width gives the line width of a layer on the surface
+pub fn draw_page(
+    surface: &mut dyn Surface,
+    data_model: &DataModel,
+    page: &Page,
+    matrix: &Matrix,
+    width: &dyn Fn(&str) -> f64,
+) {
+    for node in page
+        .get_node_ids()
+        .iter()
+        .filter_map(|id| data_model.get_node(id))
+    {
+        draw_node(surface, node, matrix, width(node.get_layer()));
+    }
+}
+
+pub fn draw_node(surface: &mut dyn Surface, node: &dyn Node, matrix: &Matrix, width: f64) {
+    let any = node.as_any();
+    let path = if let Some(line) = any.downcast_ref::<Line>() {
+        vec![
+            PathSegment::MoveTo(Point2d::new(line.x1, line.y1)),
+            PathSegment::LineTo(Point2d::new(line.x2, line.y2)),
+        ]
+    } else if let Some(arc) = any.downcast_ref::<Arc>() {
+        arc_path(
+            &arc.get_center(),
+            arc.get_r(),
+            arc.get_angle_start(),
+            arc.get_angle_end(),
+        )
+    } else if let Some(polyline) = any.downcast_ref::<Polyline>() {
+        let mut path: Vec<PathSegment> = polyline
+            .points
+            .iter()
+            .enumerate()
+            .map(|(i, pt)| match i {
+                0 => PathSegment::MoveTo(*pt),
+                _ => PathSegment::LineTo(*pt),
+            })
+            .collect();
+        if polyline.closed {
+            path.push(PathSegment::Close);
+        }
+        path
+    } else if let Some(text) = any.downcast_ref::<Text>() {
+        // texts keep their proportions, the scale is taken from the x axis
+        let (sin, cos) = text.rotation.to_radians().sin_cos();
+        let direction = matrix.multiply_vector(&Point2d::new(cos, sin));
+        let scale = direction.distance(&Point2d::new(0.0, 0.0));
+        surface.text(
+            &text.text,
+            matrix.multiply(&text.get_position()),
+            text.height * scale,
+            direction.y.atan2(direction.x).to_degrees(),
+        );
+        return;
+    } else {
+        return;
+    };
+    let path: Vec<PathSegment> = path
+        .iter()
+        .map(|segment| transform(segment, matrix))
+        .collect();
+    if !path.is_empty() {
+        surface.stroke(&path, width);
+    }
+}
+
+fn transform(segment: &PathSegment, matrix: &Matrix) -> PathSegment {
+    match segment {
+        PathSegment::MoveTo(pt) => PathSegment::MoveTo(matrix.multiply(pt)),
+        PathSegment::LineTo(pt) => PathSegment::LineTo(matrix.multiply(pt)),
+        PathSegment::CubicTo(c1, c2, pt) => PathSegment::CubicTo(
+            matrix.multiply(c1),
+            matrix.multiply(c2),
+            matrix.multiply(pt),
+        ),
+        PathSegment::Close => PathSegment::Close,
+    }
+}
+
+// counter clockwise from start to end, one bezier per quarter circle at most
+pub fn arc_path(center: &Point2d, r: f64, start: f64, end: f64) -> Vec<PathSegment> {
+    let sweep = algebra::arc_sweep(start, end);
+    let count = (sweep / 90.0).ceil().max(1.0) as usize;
+    let step = (sweep / count as f64).to_radians();
+    // the distance of the control points along the tangents
+    let k = 4.0 / 3.0 * (step / 4.0).tan() * r;
+
+    let mut path = vec![PathSegment::MoveTo(algebra::arc_point(center, r, start))];
+    for i in 0..count {
+        let a0 = start.to_radians() + step * i as f64;
+        let a1 = a0 + step;
+        let p0 = *center + Point2d::new(a0.cos(), a0.sin()) * r;
+        let p1 = *center + Point2d::new(a1.cos(), a1.sin()) * r;
+        path.push(PathSegment::CubicTo(
+            p0 + Point2d::new(-a0.sin(), a0.cos()) * k,
+            p1 - Point2d::new(-a1.sin(), a1.cos()) * k,
+            p1,
+        ));
+    }
+    if sweep == 360.0 {
+        path.push(PathSegment::Close);
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quarter_arcs() {
+        let path = arc_path(&Point2d::new(0.0, 0.0), 10.0, 0.0, 360.0);
+        assert_eq!(path.len(), 6);
+        assert_eq!(path[0], PathSegment::MoveTo(Point2d::new(10.0, 0.0)));
+        let PathSegment::CubicTo(c1, _, end) = path[1] else {
+            panic!("a bezier");
+        };
+        assert!(c1.distance(&Point2d::new(10.0, 5.5228475)) < 1e-6);
+        assert!(end.distance(&Point2d::new(0.0, 10.0)) < 1e-12);
+
+        // from 350 to 10 degrees
+        assert_eq!(arc_path(&Point2d::new(0.0, 0.0), 1.0, 350.0, 10.0).len(), 2);
+    }
+}
@@ -0,0 +1,172 @@
+//
+// a standalone svg document of a page, one svg unit is one drawing unit
+//
+
+use std::fmt::Write;
+
+use algebra::{BBox, Matrix, Point2d};
+use datamodel::{DataModel, Node, Page};
+
+use crate::pdf::number;
+use crate::surface::{draw_page, PathSegment, Surface};
+
+// the empty space around the nodes, relative to the larger side
+const MARGIN: f64 = 0.05;
+
+// the elements of an svg with y up, flipped at the height of the document
+#[derive(Debug)]
+pub struct SvgSurface {
+    height: f64,
+    body: String,
+}
+
+impl SvgSurface {
+    pub fn new(height: f64) -> Self {
+        Self {
+            height,
+            body: String::new(),
+        }
+    }
+
+    pub fn into_body(self) -> String {
+        self.body
+    }
+
+    fn point(&mut self, pt: &Point2d) {
+        let _ = write!(self.body, "{} {}", number(pt.x), number(self.height - pt.y));
+    }
+}
+
+impl Surface for SvgSurface {
+    fn stroke(&mut self, path: &[PathSegment], width: f64) {
+        self.body.push_str("<path d=\"");
+        for (i, segment) in path.iter().enumerate() {
+            if i > 0 {
+                self.body.push(' ');
+            }
+            match segment {
+                PathSegment::MoveTo(pt) => {
+                    self.body.push_str("M ");
+                    self.point(pt);
+                }
+                PathSegment::LineTo(pt) => {
+                    self.body.push_str("L ");
+                    self.point(pt);
+                }
+                PathSegment::CubicTo(c1, c2, pt) => {
+                    self.body.push_str("C ");
+                    self.point(c1);
+                    self.body.push(' ');
+                    self.point(c2);
+                    self.body.push(' ');
+                    self.point(pt);
+                }
+                PathSegment::Close => self.body.push('Z'),
+            }
+        }
+        let _ = writeln!(self.body, "\" stroke-width=\"{}\"/>", number(width));
+    }
+
+    fn text(&mut self, text: &str, position: Point2d, height: f64, rotation: f64) {
+        let (x, y) = (number(position.x), number(self.height - position.y));
+        let _ = write!(
+            self.body,
+            "<text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"black\" stroke=\"none\"",
+            x,
+            y,
+            number(height)
+        );
+        if rotation != 0.0 {
+            // svg angles are clockwise
+            let _ = write!(
+                self.body,
+                " transform=\"rotate({} {} {})\"",
+                number(-rotation),
+                x,
+                y
+            );
+        }
+        let _ = writeln!(self.body, ">{}</text>", escape(text));
+    }
+}
+
+// the nodes of the page with a margin, line_width in drawing units
+pub fn write_svg(data_model: &DataModel, page: &Page, line_width: f64) -> String {
+    let extents = data_model
+        .get_spatial_index(page.get_id())
+        .and_then(|index| index.extents())
+        .unwrap_or_else(|| BBox::new(Point2d::new(0.0, 0.0), Point2d::new(0.0, 0.0)));
+    let margin = extents.width().max(extents.height()) * MARGIN;
+    let mut area = extents.inflate(margin);
+    // a single point or no nodes at all
+    if area.width() <= 0.0 || area.height() <= 0.0 {
+        area = area.inflate(line_width.max(1.0));
+    }
+    let matrix = Matrix::translate(-area.min.x, -area.min.y);
+    let (width, height) = (number(area.width()), number(area.height()));
+
+    let mut surface = SvgSurface::new(area.height());
+    draw_page(&mut surface, data_model, page, &matrix, &|_| line_width);
+
+    let mut svg = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">",
+        width, height, width, height
+    );
+    let _ = writeln!(svg, "<title>{}</title>", escape(page.get_name()));
+    svg.push_str(
+        "<g fill=\"none\" stroke=\"black\" stroke-linecap=\"round\" stroke-linejoin=\"round\" \
+         font-family=\"sans-serif\">\n",
+    );
+    svg.push_str(&surface.into_body());
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datamodel::{Arc, Line, Text};
+
+    #[test]
+    fn document() {
+        let mut dm = DataModel::default();
+        dm.insert_page(Page::new("p1", "a & b".into(), String::new()));
+        let mut line = Line::new("1".to_string());
+        (line.x2, line.y2) = (100.0, 50.0);
+        dm.insert_node(Box::new(line));
+        dm.add_node_to_page("p1", "1");
+        let arc = Arc::new("2".to_string(), 50.0, 25.0, 10.0, 0.0, 90.0);
+        dm.insert_node(Box::new(arc));
+        dm.add_node_to_page("p1", "2");
+        let mut text = Text::new("3".to_string(), Point2d::new(50.0, 10.0), 5.0, "<1>".into());
+        text.rotation = 90.0;
+        dm.insert_node(Box::new(text));
+        dm.add_node_to_page("p1", "3");
+
+        let svg = write_svg(&dm, dm.get_page("p1").unwrap(), 0.5);
+        // 5 units margin, y points down
+        assert!(svg.contains("width=\"110\" height=\"60\" viewBox=\"0 0 110 60\""));
+        assert!(svg.contains("<title>a &amp; b</title>"));
+        assert!(svg.contains("<path d=\"M 5 55 L 105 5\" stroke-width=\"0.5\"/>"));
+        assert!(svg.contains("<path d=\"M 65 30 C 65 24.477 60.523 20 55 20\""));
+        assert!(svg.contains(
+            "<text x=\"55\" y=\"45\" font-size=\"5\" fill=\"black\" stroke=\"none\" \
+             transform=\"rotate(-90 55 45)\">&lt;1&gt;</text>"
+        ));
+        assert!(svg.ends_with("</g>\n</svg>\n"));
+
+        let empty = Page::new("p2", "empty".into(), String::new());
+        dm.insert_page(empty);
+        let svg = write_svg(&dm, dm.get_page("p2").unwrap(), 0.5);
+        assert!(svg.contains("viewBox=\"0 0 2 2\""));
+    }
+}
@@ -2,6 +2,8 @@
 // https://github.com/snapview/tokio-tungstenite/blob/master/examples/interval-server.rs
 //
 
+use command::CommandLine;
+use datamodel::DataModel;
 use futures_channel::mpsc::{unbounded, UnboundedSender};
 use futures_util::{future, pin_mut, StreamExt, TryStreamExt};
 use std::{
@@ -15,8 +17,69 @@ use tokio_tungstenite::tungstenite::Message;
 
 type Tx = UnboundedSender<Message>;
 type PeerMap = Arc<Mutex<HashMap<SocketAddr, Tx>>>;
+// the accepted command lines of all peers
+type History = Arc<Mutex<Vec<String>>>;
 
-async fn handle_connection(peer_map: PeerMap, raw_stream: TcpStream, addr: SocketAddr) {
+const THUMBNAIL_SIZE: u32 = 256;
+// command lines replayed for a thumbnail at most
+const THUMBNAIL_HISTORY: usize = 20_000;
+// the server does not read or write files for a thumbnail
+const FILE_COMMANDS: &[&str] = &["export", "import", "script"];
+
+// "thumbnail [width [height]]", a png of the current page after replaying
+// the history without the commands that use files
+fn thumbnail(history: &[String], text: &str) -> Result<Vec<u8>, String> {
+    let sizes = text
+        .split_whitespace()
+        .skip(1)
+        .map(|size| {
+            size.parse::<u32>()
+                .ok()
+                .filter(|size| *size > 0)
+                .ok_or_else(|| format!("Invalid size '{}'", size))
+        })
+        .collect::<Result<Vec<u32>, String>>()?;
+    let width = sizes.first().copied().unwrap_or(THUMBNAIL_SIZE);
+    let options = render::RasterOptions {
+        width,
+        height: sizes.get(1).copied().unwrap_or(width),
+        ..Default::default()
+    };
+    render::check_image_size(options.width, options.height)?;
+    if history.len() > THUMBNAIL_HISTORY {
+        return Err(format!(
+            "No thumbnail for more than {} command lines",
+            THUMBNAIL_HISTORY
+        ));
+    }
+
+    let mut data_model = DataModel::default();
+    let mut command_line = CommandLine::default();
+    for line in history {
+        let name = line.split_whitespace().next().unwrap_or_default();
+        let registry = command_line.get_registry();
+        if let Some(info) = registry.lookup(name) {
+            if FILE_COMMANDS.contains(&info.name) {
+                continue;
+            }
+        }
+        if let Ok(command) = command_line.parse(&mut data_model, line) {
+            command.execute(&mut data_model);
+        }
+    }
+    let page = data_model
+        .get_current_page()
+        .ok_or_else(|| "No page to show".to_string())?;
+    let viewport = render::page_viewport(&data_model, page, options.width, options.height);
+    render::render_png(&data_model, page, &viewport, &options)
+}
+
+async fn handle_connection(
+    peer_map: PeerMap,
+    history: History,
+    raw_stream: TcpStream,
+    addr: SocketAddr,
+) {
     println!("Incoming TCP connection from: {}", addr);
 
     let ws_stream = tokio_tungstenite::accept_async(raw_stream)
@@ -34,8 +97,43 @@ async fn handle_connection(peer_map: PeerMap, raw_stream: TcpStream, addr: Socke
             Message::Text(text) => {
                 println!("Text message: {}", text);
 
+                // a thumbnail goes back to the sender only, it is rendered
+                // on a blocking thread without holding the locks
+                if text.split_whitespace().next() == Some("thumbnail") {
+                    let lines = {
+                        let history = history.lock().unwrap();
+                        // too long, thumbnail() gives the error
+                        history[..history.len().min(THUMBNAIL_HISTORY + 1)].to_vec()
+                    };
+                    let text = text.to_string();
+                    let peer_map = peer_map.clone();
+                    tokio::spawn(async move {
+                        let rendered =
+                            tokio::task::spawn_blocking(move || thumbnail(&lines, &text)).await;
+                        let reply = match rendered {
+                            Ok(Ok(png)) => Message::binary(png),
+                            Ok(Err(err)) => Message::text(format!("error: {}", err)),
+                            Err(err) => Message::text(format!("error: {}", err)),
+                        };
+                        if let Some(sender) = peer_map.lock().unwrap().get(&addr) {
+                            let _ = sender.unbounded_send(reply);
+                        }
+                    });
+                    return future::ok(());
+                }
+
                 let peers = peer_map.lock().unwrap();
 
+                // invalid commands go back to the sender only
+                if let Err(err) = CommandLine::default().check_syntax(text) {
+                    if let Some(sender) = peers.get(&addr) {
+                        let error = format!("error: {}", err.show(text));
+                        sender.unbounded_send(Message::text(error)).unwrap();
+                    }
+                    return future::ok(());
+                }
+                history.lock().unwrap().push(text.to_string());
+
                 // We want to broadcast the message to everyone except ourselves.
                 let broadcast_recipients = peers
                     .iter()
@@ -78,6 +176,7 @@ async fn main() {
         .unwrap_or_else(|| "127.0.0.1:9002".to_string());
 
     let state = PeerMap::new(Mutex::new(HashMap::new()));
+    let history = History::default();
 
     // Create the event loop and TCP listener we'll accept connections on.
     let try_socket = TcpListener::bind(&addr).await;
@@ -85,6 +184,85 @@ async fn main() {
     println!("Listening on: {}", addr);
 
     while let Ok((stream, client_addr)) = listener.accept().await {
-        tokio::spawn(handle_connection(state.clone(), stream, client_addr));
+        tokio::spawn(handle_connection(
+            state.clone(),
+            history.clone(),
+            stream,
+            client_addr,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::SinkExt;
+    use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+    #[tokio::test]
+    async fn erase_is_sent_to_peers() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let state = PeerMap::default();
+        let history = History::default();
+        tokio::spawn(async move {
+            while let Ok((stream, client_addr)) = listener.accept().await {
+                tokio::spawn(handle_connection(
+                    state.clone(),
+                    history.clone(),
+                    stream,
+                    client_addr,
+                ));
+            }
+        });
+
+        let (mut sender, _) = connect_async(url.as_str()).await.unwrap();
+        let (mut peer, _) = connect_async(url.as_str()).await.unwrap();
+        // both are known to the server once the first message is through
+        peer.send(Message::text("page p2")).await.unwrap();
+        assert_eq!(next_text(&mut sender).await.as_deref(), Some("page p2"));
+        for line in ["page p1", "line 0,0 10,0", "erase 2"] {
+            sender.send(Message::text(line)).await.unwrap();
+        }
+
+        let mut received = vec![];
+        while received.last().map(String::as_str) != Some("erase 2") {
+            match next_text(&mut peer).await {
+                Some(text) => received.push(text),
+                None => break,
+            }
+        }
+        assert_eq!(received, ["page p1", "line 0,0 10,0", "erase 2"]);
+
+        // the thumbnail is rendered on another thread and sent when done
+        peer.send(Message::text("thumbnail 32")).await.unwrap();
+        match peer.next().await {
+            Some(Ok(Message::Binary(png))) => assert!(png.starts_with(b"\x89PNG")),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn thumbnail_size() {
+        let history = [
+            "page p1".to_string(),
+            "line 0,0 10,0".to_string(),
+            "script /no/such/file.scr".to_string(),
+            "export /no/such/dir/plan.svg".to_string(),
+        ];
+        let png = thumbnail(&history, "thumbnail 64 32").unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+        let err = thumbnail(&history, "thumbnail 100000").unwrap_err();
+        assert!(err.starts_with("Invalid image size 100000x100000"));
+        let long = vec!["line 0,0 10,0".to_string(); THUMBNAIL_HISTORY + 1];
+        let err = thumbnail(&long, "thumbnail").unwrap_err();
+        assert_eq!(err, "No thumbnail for more than 20000 command lines");
+    }
+
+    async fn next_text(stream: &mut WebSocketStream<MaybeTlsStream<TcpStream>>) -> Option<String> {
+        match stream.next().await? {
+            Ok(Message::Text(text)) => Some(text.to_string()),
+            _ => None,
+        }
     }
 }
@@ -0,0 +1,106 @@
+//
+// svg import of simple geometry
+//
+// line, polyline, polygon, circle, ellipse, rect and path become lines,
+// arcs and polylines. beziers, elliptical arcs and ellipses are approximated
+// by polylines. transforms of nested groups are applied and y is flipped,
+// one user unit of the svg is one drawing unit
+//
+
+mod path;
+mod reader;
+mod transform;
+
+pub use reader::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datamodel::{Arc, Line, Node, Polyline};
+
+    const SHAPES: &str = include_str!("../samples/shapes.svg");
+
+    fn read(text: &str) -> Result<SvgImport, SvgError> {
+        let mut counter = 0;
+        read_svg(text, &mut || {
+            counter += 1;
+            counter.to_string().into()
+        })
+    }
+
+    fn get<T: 'static>(node: &dyn Node) -> &T {
+        node.as_any().downcast_ref::<T>().unwrap()
+    }
+
+    #[test]
+    fn shapes() {
+        let import = read(SHAPES).unwrap();
+        let nodes = &import.nodes;
+        assert_eq!(nodes.len(), 11);
+        assert_eq!(import.skipped.get("text"), Some(&1));
+        assert_eq!(import.skipped.get("use"), Some(&1));
+
+        // y is flipped at the bottom of the viewBox
+        let line = get::<Line>(nodes[0].as_ref());
+        assert_eq!(
+            (line.x1, line.y1, line.x2, line.y2),
+            (0.0, 100.0, 100.0, 0.0)
+        );
+        let polyline = get::<Polyline>(nodes[1].as_ref());
+        assert!(!polyline.closed);
+        assert_eq!(polyline.points[1], algebra::Point2d::new(20.0, 20.0));
+        assert!(get::<Polyline>(nodes[2].as_ref()).closed);
+
+        // a circle in the translated layer, the scaled one is flattened
+        let arc = get::<Arc>(nodes[3].as_ref());
+        assert_eq!(arc.get_center(), algebra::Point2d::new(50.0, 50.0));
+        assert_eq!(
+            (arc.get_r(), arc.get_angle_start(), arc.get_angle_end()),
+            (10.0, 0.0, 360.0)
+        );
+        assert_eq!(arc.get_layer(), "Outline");
+        // within the tolerance of the flattening
+        for (node, (width, height)) in nodes[4..6].iter().zip([(20.0, 10.0), (40.0, 20.0)]) {
+            let polyline = get::<Polyline>(node.as_ref());
+            assert!(polyline.closed);
+            assert_eq!(polyline.get_layer(), "Outline");
+            let bbox = polyline.get_bbox().unwrap();
+            assert!((bbox.width() - width).abs() < 0.05 && (bbox.height() - height).abs() < 0.05);
+        }
+        assert_eq!(nodes[6].get_layer(), "");
+
+        let rect = get::<Polyline>(nodes[6].as_ref());
+        assert_eq!(rect.points.len(), 4);
+        assert_eq!(rect.points[0], algebra::Point2d::new(10.0, 90.0));
+        let rounded = get::<Polyline>(nodes[7].as_ref());
+        assert!(rounded.points.len() > 8);
+        let bbox = rounded.get_bbox().unwrap();
+        assert_eq!(
+            (algebra::round(bbox.width()), algebra::round(bbox.height())),
+            (20.0, 10.0)
+        );
+
+        // a path with an open line, a closed triangle and a closed curve
+        assert!(nodes[8].as_any().is::<Line>());
+        assert_eq!(get::<Polyline>(nodes[9].as_ref()).points.len(), 3);
+        let curve = get::<Polyline>(nodes[10].as_ref());
+        assert!(curve.closed);
+        let bbox = curve.get_bbox().unwrap();
+        assert_eq!(algebra::round(bbox.height()), 13.75);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(read("<svg").unwrap_err().line, 1);
+        let error = read("<html/>").unwrap_err();
+        assert_eq!(error.to_string(), "line 1: Not an svg document");
+        let error = read("<svg>\n<rect width=\"10mm\" height=\"1\"/></svg>").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2: Unsupported length '10mm' of width"
+        );
+        let error = read("<svg>\n\n<path d=\"M0 0 L\"/></svg>").unwrap_err();
+        assert_eq!(error.line, 3);
+        assert!(read("<svg><g transform=\"rotate(\"/></svg>").is_err());
+    }
+}
@@ -0,0 +1,497 @@
+//
+// the d attribute of a path and the flattening of curves
+//
+
+use std::f64::consts::{FRAC_PI_4, PI};
+
+use algebra::{Matrix, Point2d};
+
+// the maximal distance between a curve and its polyline in drawing units
+const TOLERANCE: f64 = 0.01;
+const MAX_SEGMENTS: usize = 1024;
+
+// numbers, flags and command letters of the path data or a points list
+pub(crate) struct Scanner<'a> {
+    text: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    pub(crate) fn new(text: &'a str) -> Self {
+        Self {
+            text: text.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn skip_separators(&mut self) {
+        while self
+            .text
+            .get(self.pos)
+            .is_some_and(|c| c.is_ascii_whitespace() || *c == b',')
+        {
+            self.pos += 1;
+        }
+    }
+
+    pub(crate) fn at_end(&mut self) -> bool {
+        self.skip_separators();
+        self.pos >= self.text.len()
+    }
+
+    fn command(&mut self) -> Option<u8> {
+        self.skip_separators();
+        let c = *self.text.get(self.pos)?;
+        if c.is_ascii_alphabetic() && c != b'e' && c != b'E' {
+            self.pos += 1;
+            return Some(c);
+        }
+        None
+    }
+
+    // "-1.5e2", ".5" and "1.5.5" is 1.5 followed by .5
+    pub(crate) fn number(&mut self) -> Result<f64, String> {
+        self.skip_separators();
+        let start = self.pos;
+        let digits = |scanner: &mut Self| {
+            let from = scanner.pos;
+            while scanner
+                .text
+                .get(scanner.pos)
+                .is_some_and(u8::is_ascii_digit)
+            {
+                scanner.pos += 1;
+            }
+            scanner.pos > from
+        };
+        if matches!(self.text.get(self.pos), Some(b'+' | b'-')) {
+            self.pos += 1;
+        }
+        let mut valid = digits(self);
+        if self.text.get(self.pos) == Some(&b'.') {
+            self.pos += 1;
+            valid |= digits(self);
+        }
+        if valid && matches!(self.text.get(self.pos), Some(b'e' | b'E')) {
+            let mantissa = self.pos;
+            self.pos += 1;
+            if matches!(self.text.get(self.pos), Some(b'+' | b'-')) {
+                self.pos += 1;
+            }
+            if !digits(self) {
+                self.pos = mantissa;
+            }
+        }
+        let text = std::str::from_utf8(&self.text[start..self.pos]).unwrap_or_default();
+        match text.parse() {
+            Ok(value) if valid => Ok(value),
+            _ => Err(format!("Expected a number at '{}'", self.rest())),
+        }
+    }
+
+    // the arc flags may be written without separators, e.g. "a5 5 0 016 0"
+    fn flag(&mut self) -> Result<bool, String> {
+        self.skip_separators();
+        let flag = match self.text.get(self.pos) {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(format!("Expected a flag at '{}'", self.rest())),
+        };
+        self.pos += 1;
+        Ok(flag)
+    }
+
+    fn point(&mut self) -> Result<Point2d, String> {
+        Ok(Point2d::new(self.number()?, self.number()?))
+    }
+
+    fn rest(&self) -> String {
+        String::from_utf8_lossy(&self.text[self.pos..])
+            .chars()
+            .take(10)
+            .collect()
+    }
+}
+
+// a list of coordinates, e.g. the points attribute of a polygon.
+// an odd number of coordinates ignores the last one
+pub(crate) fn parse_points(text: &str) -> Result<Vec<Point2d>, String> {
+    let mut scanner = Scanner::new(text);
+    let mut values = vec![];
+    while !scanner.at_end() {
+        values.push(scanner.number()?);
+    }
+    Ok(values
+        .chunks_exact(2)
+        .map(|xy| Point2d::new(xy[0], xy[1]))
+        .collect())
+}
+
+// the transformed points of a subpath, the closing segment is not repeated
+#[derive(Debug, PartialEq)]
+pub(crate) struct Subpath {
+    pub(crate) points: Vec<Point2d>,
+    pub(crate) closed: bool,
+}
+
+struct PathBuilder<'a> {
+    matrix: &'a Matrix,
+    subpaths: Vec<Subpath>,
+    points: Vec<Point2d>,
+    // untransformed
+    current: Point2d,
+    start: Point2d,
+}
+
+impl PathBuilder<'_> {
+    fn finish(&mut self, closed: bool) {
+        let mut points = std::mem::take(&mut self.points);
+        if closed && points.len() > 2 && points.first() == points.last() {
+            points.pop();
+        }
+        if points.len() > 1 {
+            self.subpaths.push(Subpath { points, closed });
+        }
+    }
+
+    fn move_to(&mut self, pt: Point2d) {
+        self.finish(false);
+        self.points.push(self.matrix.multiply(&pt));
+        (self.start, self.current) = (pt, pt);
+    }
+
+    // a segment after a close starts at the start of the closed subpath
+    fn started(&mut self) {
+        if self.points.is_empty() {
+            self.points.push(self.matrix.multiply(&self.start));
+        }
+    }
+
+    fn line_to(&mut self, pt: Point2d) {
+        self.started();
+        self.points.push(self.matrix.multiply(&pt));
+        self.current = pt;
+    }
+
+    fn close(&mut self) {
+        self.finish(true);
+        self.current = self.start;
+    }
+
+    // beziers stay beziers under an affine transform
+    fn bezier_to(&mut self, controls: &[Point2d]) {
+        self.started();
+        let mut transformed = vec![self.matrix.multiply(&self.current)];
+        transformed.extend(controls.iter().map(|pt| self.matrix.multiply(pt)));
+        self.points.extend(flatten_bezier(&transformed));
+        self.current = *controls.last().unwrap_or(&self.current);
+    }
+
+    fn arc_to(&mut self, rx: f64, ry: f64, phi: f64, large: bool, sweep: bool, pt: Point2d) {
+        self.started();
+        let p1 = self.current;
+        self.current = pt;
+        if p1 == pt {
+            return;
+        }
+        if rx == 0.0 || ry == 0.0 {
+            self.points.push(self.matrix.multiply(&pt));
+            return;
+        }
+        let arc = endpoint_to_center(p1, pt, rx.abs(), ry.abs(), phi.to_radians(), large, sweep);
+        let mut points = ellipse_points(&arc, self.matrix);
+        // exactly at the end point
+        points.pop();
+        points.push(self.matrix.multiply(&pt));
+        self.points.extend(points);
+    }
+}
+
+pub(crate) fn parse_path(d: &str, matrix: &Matrix) -> Result<Vec<Subpath>, String> {
+    let mut builder = PathBuilder {
+        matrix,
+        subpaths: vec![],
+        points: vec![],
+        current: Point2d::new(0.0, 0.0),
+        start: Point2d::new(0.0, 0.0),
+    };
+    let mut scanner = Scanner::new(d);
+    let mut command = None;
+    // the second control points for the reflection of S and T
+    let mut last_cubic: Option<Point2d> = None;
+    let mut last_quadratic: Option<Point2d> = None;
+
+    loop {
+        if let Some(c) = scanner.command() {
+            if command.is_none() && builder.subpaths.is_empty() && !matches!(c, b'M' | b'm') {
+                return Err("A path must start with a moveto".into());
+            }
+            command = Some(c);
+        } else if scanner.at_end() {
+            break;
+        }
+        let Some(c) = command else {
+            return Err(format!("Expected a path command at '{}'", scanner.rest()));
+        };
+        let relative = c.is_ascii_lowercase();
+        let base = if relative {
+            builder.current
+        } else {
+            Point2d::new(0.0, 0.0)
+        };
+        let current = builder.current;
+        let (mut cubic, mut quadratic) = (None, None);
+        match c.to_ascii_uppercase() {
+            b'M' => {
+                builder.move_to(base + scanner.point()?);
+                // further coordinate pairs are lines
+                command = Some(if relative { b'l' } else { b'L' });
+            }
+            b'L' => builder.line_to(base + scanner.point()?),
+            b'H' => builder.line_to(Point2d::new(base.x + scanner.number()?, current.y)),
+            b'V' => builder.line_to(Point2d::new(current.x, base.y + scanner.number()?)),
+            b'C' | b'S' => {
+                let c1 = if c.eq_ignore_ascii_case(&b'C') {
+                    base + scanner.point()?
+                } else {
+                    last_cubic.map_or(current, |c2| current * 2.0 - c2)
+                };
+                let c2 = base + scanner.point()?;
+                let pt = base + scanner.point()?;
+                builder.bezier_to(&[c1, c2, pt]);
+                cubic = Some(c2);
+            }
+            b'Q' | b'T' => {
+                let c1 = if c.eq_ignore_ascii_case(&b'Q') {
+                    base + scanner.point()?
+                } else {
+                    last_quadratic.map_or(current, |c1| current * 2.0 - c1)
+                };
+                let pt = base + scanner.point()?;
+                builder.bezier_to(&[c1, pt]);
+                quadratic = Some(c1);
+            }
+            b'A' => {
+                let (rx, ry, phi) = (scanner.number()?, scanner.number()?, scanner.number()?);
+                let (large, sweep) = (scanner.flag()?, scanner.flag()?);
+                builder.arc_to(rx, ry, phi, large, sweep, base + scanner.point()?);
+            }
+            b'Z' => {
+                builder.close();
+                command = None;
+            }
+            _ => return Err(format!("Unknown path command '{}'", c as char)),
+        }
+        (last_cubic, last_quadratic) = (cubic, quadratic);
+    }
+    builder.finish(false);
+    Ok(builder.subpaths)
+}
+
+// the points after the first one, the number of segments after Wang's formula
+fn flatten_bezier(points: &[Point2d]) -> Vec<Point2d> {
+    let degree = points.len() - 1;
+    let second_difference = points
+        .windows(3)
+        .map(|p| (p[0] - p[1] * 2.0 + p[2]).distance(&Point2d::new(0.0, 0.0)))
+        .fold(0.0, f64::max);
+    let factor = (degree * degree.saturating_sub(1)) as f64 / 8.0;
+    let n = segment_count((factor * second_difference / TOLERANCE).sqrt());
+    (1..=n)
+        .map(|i| de_casteljau(points, i as f64 / n as f64))
+        .collect()
+}
+
+fn de_casteljau(points: &[Point2d], t: f64) -> Point2d {
+    let mut points = points.to_vec();
+    while points.len() > 1 {
+        points = points
+            .windows(2)
+            .map(|p| p[0] * (1.0 - t) + p[1] * t)
+            .collect();
+    }
+    points[0]
+}
+
+fn segment_count(n: f64) -> usize {
+    (n.ceil() as usize).clamp(1, MAX_SEGMENTS)
+}
+
+// an elliptical arc before the transform, angles in radians
+pub(crate) struct EllipseArc {
+    pub(crate) center: Point2d,
+    pub(crate) rx: f64,
+    pub(crate) ry: f64,
+    // the rotation of the x axis
+    pub(crate) phi: f64,
+    pub(crate) start: f64,
+    // negative is clockwise in the untransformed coordinates
+    pub(crate) sweep: f64,
+}
+
+impl EllipseArc {
+    pub(crate) fn full(center: Point2d, rx: f64, ry: f64) -> Self {
+        Self {
+            center,
+            rx,
+            ry,
+            phi: 0.0,
+            start: 0.0,
+            sweep: 2.0 * PI,
+        }
+    }
+
+    pub(crate) fn point(&self, angle: f64) -> Point2d {
+        let (sin, cos) = angle.sin_cos();
+        let (sin_phi, cos_phi) = self.phi.sin_cos();
+        let (x, y) = (self.rx * cos, self.ry * sin);
+        self.center + Point2d::new(cos_phi * x - sin_phi * y, sin_phi * x + cos_phi * y)
+    }
+}
+
+// the transformed points after the start point
+pub(crate) fn ellipse_points(arc: &EllipseArc, matrix: &Matrix) -> Vec<Point2d> {
+    // the frobenius norm is not less than the largest stretch of the matrix
+    let stretch = matrix
+        .multiply_vector(&Point2d::new(1.0, 0.0))
+        .distance(&Point2d::new(0.0, 0.0))
+        .hypot(
+            matrix
+                .multiply_vector(&Point2d::new(0.0, 1.0))
+                .distance(&Point2d::new(0.0, 0.0)),
+        );
+    let r = arc.rx.max(arc.ry) * stretch;
+    let step = if TOLERANCE < r {
+        (2.0 * (1.0 - TOLERANCE / r).acos()).min(FRAC_PI_4)
+    } else {
+        FRAC_PI_4
+    };
+    let n = segment_count(arc.sweep.abs() / step);
+    (1..=n)
+        .map(|i| matrix.multiply(&arc.point(arc.start + arc.sweep * i as f64 / n as f64)))
+        .collect()
+}
+
+// the center parameterization of an svg arc, see the svg implementation notes
+fn endpoint_to_center(
+    p1: Point2d,
+    p2: Point2d,
+    mut rx: f64,
+    mut ry: f64,
+    phi: f64,
+    large: bool,
+    sweep: bool,
+) -> EllipseArc {
+    let (sin_phi, cos_phi) = phi.sin_cos();
+    let half = (p1 - p2) / 2.0;
+    let x1 = cos_phi * half.x + sin_phi * half.y;
+    let y1 = -sin_phi * half.x + cos_phi * half.y;
+
+    // the radii are too small for the end points
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+    let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let sign = if large != sweep { 1.0 } else { -1.0 };
+    let coefficient = sign * (numerator / denominator).max(0.0).sqrt();
+    let cx = coefficient * rx * y1 / ry;
+    let cy = -coefficient * ry * x1 / rx;
+
+    let middle = (p1 + p2) / 2.0;
+    let center = Point2d::new(
+        cos_phi * cx - sin_phi * cy + middle.x,
+        sin_phi * cx + cos_phi * cy + middle.y,
+    );
+    let angle = |ux: f64, uy: f64, vx: f64, vy: f64| (ux * vy - uy * vx).atan2(ux * vx + uy * vy);
+    let (ux, uy) = ((x1 - cx) / rx, (y1 - cy) / ry);
+    let (vx, vy) = ((-x1 - cx) / rx, (-y1 - cy) / ry);
+    let start = angle(1.0, 0.0, ux, uy);
+    let mut delta = angle(ux, uy, vx, vy);
+    if !sweep && delta > 0.0 {
+        delta -= 2.0 * PI;
+    } else if sweep && delta < 0.0 {
+        delta += 2.0 * PI;
+    }
+    EllipseArc {
+        center,
+        rx,
+        ry,
+        phi,
+        start,
+        sweep: delta,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round(points: &[Point2d]) -> Vec<(f64, f64)> {
+        points
+            .iter()
+            .map(|pt| (algebra::round(pt.x), algebra::round(pt.y)))
+            .collect()
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(
+            parse_points("1,2 -3-4.5.5 1e1,+2E-1").unwrap(),
+            vec![
+                Point2d::new(1.0, 2.0),
+                Point2d::new(-3.0, -4.5),
+                Point2d::new(0.5, 10.0),
+            ]
+        );
+        assert!(parse_points("1 x").is_err());
+    }
+
+    #[test]
+    fn lines() {
+        let subpaths =
+            parse_path("M0 0 10 0v10h-10z m20 0 l5 5 M40 0", &Matrix::identity()).unwrap();
+        assert_eq!(subpaths.len(), 2);
+        assert!(subpaths[0].closed);
+        assert_eq!(
+            round(&subpaths[0].points),
+            vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]
+        );
+        // relative to the start of the closed subpath
+        assert!(!subpaths[1].closed);
+        assert_eq!(round(&subpaths[1].points), vec![(20.0, 0.0), (25.0, 5.0)]);
+
+        assert!(parse_path("L10 10", &Matrix::identity()).is_err());
+        assert!(parse_path("M0 0 L10", &Matrix::identity()).is_err());
+        assert!(parse_path("M0 0 X", &Matrix::identity()).is_err());
+    }
+
+    #[test]
+    fn curves() {
+        // a quarter circle from (10,0) to (0,10) around the origin
+        let subpaths = parse_path("M10 0 A10 10 0 0 1 0 10", &Matrix::identity()).unwrap();
+        let points = &subpaths[0].points;
+        assert!(points.len() > 10);
+        assert_eq!(round(&[points[points.len() - 1]]), vec![(0.0, 10.0)]);
+        assert!(points
+            .iter()
+            .all(|pt| (pt.distance(&Point2d::new(0.0, 0.0)) - 10.0).abs() < 1e-9));
+
+        // the compact flags and a radius too small for the end points
+        let subpaths = parse_path("M0 0a1 1 0 1020 0", &Matrix::identity()).unwrap();
+        let middle = subpaths[0].points[subpaths[0].points.len() / 2];
+        assert_eq!(round(&[middle]), vec![(10.0, 10.0)]);
+
+        // a straight cubic stays on the line, S reflects the control point
+        let subpaths = parse_path("M0 0 C1 0 2 0 3 0 S9 0 6 0", &Matrix::identity()).unwrap();
+        assert!(subpaths[0].points.iter().all(|pt| pt.y == 0.0));
+        let subpaths = parse_path("M0 0 Q5 10 10 0 T20 0", &Matrix::identity()).unwrap();
+        let points = &subpaths[0].points;
+        let top = points.iter().map(|pt| pt.y).fold(0.0, f64::max);
+        let bottom = points.iter().map(|pt| pt.y).fold(0.0, f64::min);
+        assert!((top - 5.0).abs() < TOLERANCE && (bottom + 5.0).abs() < TOLERANCE);
+        assert_eq!(round(&[points[points.len() - 1]]), vec![(20.0, 0.0)]);
+    }
+}
@@ -0,0 +1,290 @@
+//
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use algebra::{Matrix, Point2d};
+use datamodel::{Arc, Line, Node, NodeId, Polyline};
+
+use crate::path::{ellipse_points, parse_path, parse_points, EllipseArc};
+use crate::transform::parse_transform;
+
+const INKSCAPE: &str = "http://www.inkscape.org/namespaces/inkscape";
+
+// elements that are not drawn where they are defined
+const NOT_RENDERED: [&str; 14] = [
+    "defs",
+    "symbol",
+    "clipPath",
+    "mask",
+    "pattern",
+    "marker",
+    "linearGradient",
+    "radialGradient",
+    "filter",
+    "style",
+    "script",
+    "title",
+    "desc",
+    "metadata",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgError {
+    pub message: String,
+    // line in the file, starting with 1
+    pub line: usize,
+}
+
+impl SvgError {
+    fn new(message: impl Into<String>, line: usize) -> Self {
+        SvgError {
+            message: message.into(),
+            line,
+        }
+    }
+}
+
+impl fmt::Display for SvgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for SvgError {}
+
+#[derive(Debug)]
+pub struct SvgImport {
+    pub nodes: Vec<Box<dyn Node>>,
+    // element name -> number of elements that are not supported
+    pub skipped: BTreeMap<String, usize>,
+}
+
+// next_id gives the ids of the new nodes
+pub fn read_svg(text: &str, next_id: &mut dyn FnMut() -> NodeId) -> Result<SvgImport, SvgError> {
+    let options = roxmltree::ParsingOptions {
+        allow_dtd: true,
+        ..Default::default()
+    };
+    let document = roxmltree::Document::parse_with_options(text, options)
+        .map_err(|err| SvgError::new(err.to_string(), err.pos().row as usize))?;
+    let root = document.root_element();
+    if root.tag_name().name() != "svg" {
+        return Err(SvgError::new("Not an svg document", line(&root)));
+    }
+
+    let mut reader = Reader {
+        import: SvgImport {
+            nodes: vec![],
+            skipped: BTreeMap::new(),
+        },
+        next_id,
+    };
+    reader.element(root, &flip(&root)?, "")?;
+    Ok(reader.import)
+}
+
+// the svg y axis points down. the bottom of the viewBox or of the
+// height ends up on the x axis
+fn flip(root: &roxmltree::Node) -> Result<Matrix, SvgError> {
+    let bottom = match root.attribute("viewBox") {
+        Some(view_box) => {
+            let values = parse_points(view_box).map_err(|err| SvgError::new(err, line(root)))?;
+            match values[..] {
+                [min, size] => min.y + size.y,
+                _ => return Err(SvgError::new("Invalid viewBox", line(root))),
+            }
+        }
+        None => length(root, "height")?,
+    };
+    Ok(Matrix::translate(0.0, bottom) * Matrix::scale(1.0, -1.0))
+}
+
+fn line(node: &roxmltree::Node) -> usize {
+    node.document().text_pos_at(node.range().start).row as usize
+}
+
+// only user units, "px" is the same
+fn length(node: &roxmltree::Node, name: &str) -> Result<f64, SvgError> {
+    let Some(value) = node.attribute(name) else {
+        return Ok(0.0);
+    };
+    let number = value.trim().trim_end_matches("px");
+    number.parse().map_err(|_| {
+        SvgError::new(
+            format!("Unsupported length '{}' of {}", value, name),
+            line(node),
+        )
+    })
+}
+
+fn hidden(node: &roxmltree::Node) -> bool {
+    node.attribute("display") == Some("none")
+        || node.attribute("style").is_some_and(|style| {
+            style
+                .split(';')
+                .any(|item| item.replace(' ', "") == "display:none")
+        })
+}
+
+struct Reader<'a> {
+    import: SvgImport,
+    next_id: &'a mut dyn FnMut() -> NodeId,
+}
+
+impl Reader<'_> {
+    // the layer of the nodes is the label of an enclosing inkscape layer
+    fn element(
+        &mut self,
+        node: roxmltree::Node,
+        parent: &Matrix,
+        layer: &str,
+    ) -> Result<(), SvgError> {
+        let name = node.tag_name().name();
+        if NOT_RENDERED.contains(&name) || hidden(&node) {
+            return Ok(());
+        }
+        let mut matrix = match node.attribute("transform") {
+            Some(text) => {
+                *parent * parse_transform(text).map_err(|err| SvgError::new(err, line(&node)))?
+            }
+            None => *parent,
+        };
+        let start = self.import.nodes.len();
+        match name {
+            "svg" | "g" | "a" | "switch" => {
+                // a nested svg is placed at x, y, its viewBox is ignored
+                if name == "svg" && node.parent_element().is_some() {
+                    matrix = matrix * Matrix::translate(length(&node, "x")?, length(&node, "y")?);
+                }
+                let layer = match node.attribute((INKSCAPE, "groupmode")) {
+                    Some("layer") => node.attribute((INKSCAPE, "label")).unwrap_or(layer),
+                    _ => layer,
+                };
+                for child in node.children().filter(|child| child.is_element()) {
+                    self.element(child, &matrix, layer)?;
+                }
+                return Ok(());
+            }
+            "line" => {
+                let p1 = Point2d::new(length(&node, "x1")?, length(&node, "y1")?);
+                let p2 = Point2d::new(length(&node, "x2")?, length(&node, "y2")?);
+                self.line(matrix.multiply(&p1), matrix.multiply(&p2));
+            }
+            "polyline" | "polygon" => {
+                let points = parse_points(node.attribute("points").unwrap_or_default())
+                    .map_err(|err| SvgError::new(err, line(&node)))?;
+                let points = points.iter().map(|pt| matrix.multiply(pt)).collect();
+                self.polyline(points, name == "polygon");
+            }
+            "circle" | "ellipse" => {
+                let center = Point2d::new(length(&node, "cx")?, length(&node, "cy")?);
+                let (rx, ry) = if name == "circle" {
+                    let r = length(&node, "r")?;
+                    (r, r)
+                } else {
+                    (length(&node, "rx")?, length(&node, "ry")?)
+                };
+                self.ellipse(&matrix, center, rx, ry);
+            }
+            "rect" => self.rect(&node, &matrix)?,
+            "path" => {
+                let subpaths = parse_path(node.attribute("d").unwrap_or_default(), &matrix)
+                    .map_err(|err| SvgError::new(err, line(&node)))?;
+                for subpath in subpaths {
+                    match subpath.points[..] {
+                        [p1, p2] if !subpath.closed => self.line(p1, p2),
+                        _ => self.polyline(subpath.points, subpath.closed),
+                    }
+                }
+            }
+            _ => {
+                *self.import.skipped.entry(name.to_string()).or_default() += 1;
+            }
+        }
+        for node in &mut self.import.nodes[start..] {
+            node.set_layer(layer);
+        }
+        Ok(())
+    }
+
+    fn line(&mut self, p1: Point2d, p2: Point2d) {
+        let mut line = Line::new((self.next_id)());
+        (line.x1, line.y1, line.x2, line.y2) = (p1.x, p1.y, p2.x, p2.y);
+        self.import.nodes.push(Box::new(line));
+    }
+
+    fn polyline(&mut self, points: Vec<Point2d>, closed: bool) {
+        if points.len() > 1 {
+            let polyline = Polyline::new((self.next_id)(), points, closed);
+            self.import.nodes.push(Box::new(polyline));
+        }
+    }
+
+    // a circle stays a circle unless it is skewed or scaled non-uniformly
+    fn ellipse(&mut self, matrix: &Matrix, center: Point2d, rx: f64, ry: f64) {
+        if rx <= 0.0 || ry <= 0.0 {
+            return;
+        }
+        match matrix.uniform_scale() {
+            Some(scale) if rx == ry => {
+                let center = matrix.multiply(&center);
+                let arc = Arc::new((self.next_id)(), center.x, center.y, rx * scale, 0.0, 360.0);
+                self.import.nodes.push(Box::new(arc));
+            }
+            _ => {
+                let points = ellipse_points(&EllipseArc::full(center, rx, ry), matrix);
+                self.polyline(points, true);
+            }
+        }
+    }
+
+    fn rect(&mut self, node: &roxmltree::Node, matrix: &Matrix) -> Result<(), SvgError> {
+        let (x, y) = (length(node, "x")?, length(node, "y")?);
+        let (width, height) = (length(node, "width")?, length(node, "height")?);
+        if width <= 0.0 || height <= 0.0 {
+            return Ok(());
+        }
+        // a missing radius is the other one
+        let (rx, ry) = match (node.attribute("rx"), node.attribute("ry")) {
+            (None, None) => (0.0, 0.0),
+            (Some(_), None) => (length(node, "rx")?, length(node, "rx")?),
+            (None, Some(_)) => (length(node, "ry")?, length(node, "ry")?),
+            _ => (length(node, "rx")?, length(node, "ry")?),
+        };
+        let (rx, ry) = (rx.clamp(0.0, width / 2.0), ry.clamp(0.0, height / 2.0));
+        let corners = [
+            Point2d::new(x, y),
+            Point2d::new(x + width, y),
+            Point2d::new(x + width, y + height),
+            Point2d::new(x, y + height),
+        ];
+        if rx == 0.0 || ry == 0.0 {
+            let points = corners.iter().map(|pt| matrix.multiply(pt)).collect();
+            self.polyline(points, true);
+            return Ok(());
+        }
+        // clockwise on the screen, each corner is a quarter of an ellipse
+        let mut points = vec![];
+        for (i, corner) in corners.iter().enumerate() {
+            let sx = if i == 1 || i == 2 { -1.0 } else { 1.0 };
+            let sy = if i >= 2 { -1.0 } else { 1.0 };
+            let center = Point2d::new(corner.x + sx * rx, corner.y + sy * ry);
+            let start = std::f64::consts::FRAC_PI_2 * (i as f64 + 2.0);
+            let arc = EllipseArc {
+                center,
+                rx,
+                ry,
+                phi: 0.0,
+                start,
+                sweep: std::f64::consts::FRAC_PI_2,
+            };
+            points.push(matrix.multiply(&arc.point(start)));
+            points.extend(ellipse_points(&arc, matrix));
+        }
+        // the end of the last corner is the first point
+        points.pop();
+        self.polyline(points, true);
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+//
+// the transform attribute, e.g. "translate(10 20) rotate(45) scale(2)"
+//
+
+use algebra::Matrix;
+
+use crate::path::Scanner;
+
+// the transforms are applied from right to left, like nested groups
+pub(crate) fn parse_transform(text: &str) -> Result<Matrix, String> {
+    let mut matrix = Matrix::identity();
+    let mut rest = text.trim_start_matches([' ', ',', '\t', '\n', '\r']);
+    while !rest.is_empty() {
+        let open = rest
+            .find('(')
+            .ok_or_else(|| format!("Invalid transform '{}'", text))?;
+        let close = rest
+            .find(')')
+            .filter(|close| *close > open)
+            .ok_or_else(|| format!("Invalid transform '{}'", text))?;
+        let name = rest[..open].trim();
+        let mut scanner = Scanner::new(&rest[open + 1..close]);
+        let mut values = vec![];
+        while !scanner.at_end() {
+            values.push(scanner.number()?);
+        }
+        matrix = matrix
+            * transform(name, &values)
+                .ok_or_else(|| format!("Invalid transform '{}'", rest[..=close].trim()))?;
+        rest = rest[close + 1..].trim_start_matches([' ', ',', '\t', '\n', '\r']);
+    }
+    Ok(matrix)
+}
+
+fn transform(name: &str, values: &[f64]) -> Option<Matrix> {
+    let matrix = match (name, values) {
+        ("matrix", [a, b, c, d, e, f]) => Matrix::from_svg(*a, *b, *c, *d, *e, *f),
+        ("translate", [tx]) => Matrix::translate(*tx, 0.0),
+        ("translate", [tx, ty]) => Matrix::translate(*tx, *ty),
+        ("scale", [s]) => Matrix::scale(*s, *s),
+        ("scale", [sx, sy]) => Matrix::scale(*sx, *sy),
+        ("rotate", [angle]) => Matrix::rotate(angle.to_radians()),
+        ("rotate", [angle, cx, cy]) => {
+            Matrix::translate(*cx, *cy)
+                * Matrix::rotate(angle.to_radians())
+                * Matrix::translate(-cx, -cy)
+        }
+        ("skewX", [angle]) => Matrix::from_svg(1.0, 0.0, angle.to_radians().tan(), 1.0, 0.0, 0.0),
+        ("skewY", [angle]) => Matrix::from_svg(1.0, angle.to_radians().tan(), 0.0, 1.0, 0.0, 0.0),
+        _ => return None,
+    };
+    Some(matrix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use algebra::Point2d;
+
+    fn apply(text: &str, x: f64, y: f64) -> (f64, f64) {
+        let pt = parse_transform(text).unwrap().multiply(&Point2d::new(x, y));
+        (algebra::round(pt.x), algebra::round(pt.y))
+    }
+
+    #[test]
+    fn transforms() {
+        assert_eq!(apply("translate(10)", 1.0, 1.0), (11.0, 1.0));
+        assert_eq!(apply("translate(10,20) scale(2)", 1.0, 1.0), (12.0, 22.0));
+        assert_eq!(apply("rotate(90 10 10)", 20.0, 10.0), (10.0, 20.0));
+        assert_eq!(apply("matrix(1 0 0 1 5 -5)", 0.0, 0.0), (5.0, -5.0));
+        assert_eq!(apply("skewX(45)", 0.0, 10.0), (10.0, 10.0));
+        assert_eq!(apply(" ", 3.0, 4.0), (3.0, 4.0));
+        assert!(parse_transform("rotate(1 2)").is_err());
+        assert!(parse_transform("shear(1)").is_err());
+        assert!(parse_transform("scale(2").is_err());
+    }
+}
@@ -2,14 +2,25 @@ use gloo_utils::format::JsValueSerdeExt;
 use serde::{Deserialize, Serialize};
 //
 
-use algebra::{Point2d, Viewport};
-use datamodel::{Line, Node};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use algebra::{BBox, DrawingAids, Point2d, Ucs, View, Viewport};
+use datamodel::{Change, Line};
 use wasm_bindgen::prelude::*;
 
-use command::{CommandHandler, CommandLine};
-use datamodel::DataModel;
+use command::{
+    replay, run_script, Command, CommandHandler, CommandSession, CurrentPageCommand,
+    DrawingAidsCommand, ImportCommand, Input, Inquiry, Journal, LineCommand, MemorySink,
+    NamedViewCommand, OnError, OutputFile, PageCommand, ParseError, Prompt, ReplaceNodeCommand,
+    UcsCommand, Zoom,
+};
+use datamodel::{node_from_json, read_binary, read_document, ClientId, DataModel, NodeId};
 use render::Renderer;
 
+// object snap tolerance in pixel
+const OSNAP_TOLERANCE: f64 = 8.0;
+
 #[derive(Debug, Serialize, Deserialize)]
 struct BaseNode {
     id: String,
@@ -45,6 +56,11 @@ extern "C" {
 
     #[wasm_bindgen(js_namespace = console)]
     fn log(s: &str);
+
+    // runs the callback after the current api call returned, in a window
+    // and in a worker
+    #[wasm_bindgen(js_name = queueMicrotask)]
+    fn queue_microtask(callback: &JsValue);
 }
 
 #[wasm_bindgen]
@@ -52,22 +68,109 @@ pub fn console_log(s: &str) {
     log(s);
 }
 
+#[derive(Serialize)]
+struct PromptInfo {
+    #[serde(flatten)]
+    prompt: Prompt,
+    text: String,
+}
+
+#[derive(Serialize)]
+struct InquiryInfo<'a> {
+    #[serde(flatten)]
+    inquiry: &'a Inquiry,
+    text: String,
+}
+
+// the changes of the data model are collected for process_changes,
+// new ids are those of the client
+fn observed_data_model(
+    mut data_model: DataModel,
+    client_id: &ClientId,
+    changes: &Rc<RefCell<Vec<Change>>>,
+) -> DataModel {
+    data_model.set_client_id(Some(client_id.clone()));
+    let observed = changes.clone();
+    data_model.add_observer(Box::new(move |batch: &[Change]| {
+        observed.borrow_mut().extend_from_slice(batch);
+    }));
+    data_model
+}
+
+// {filename, bytes} with the bytes as Uint8Array
+fn output_file_to_js(file: &OutputFile) -> JsValue {
+    let object = js_sys::Object::new();
+    let _ = js_sys::Reflect::set(&object, &"filename".into(), &file.filename.as_str().into());
+    let bytes = js_sys::Uint8Array::from(file.bytes.as_slice());
+    let _ = js_sys::Reflect::set(&object, &"bytes".into(), &bytes);
+    object.into()
+}
+
+// the journal is kept in memory, javascript takes the lines
+struct JournalBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl std::io::Write for JournalBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 #[wasm_bindgen]
 pub struct ECAPI {
     // Add fields here if needed
     data_model: DataModel,
+    // random, the prefix of the ids of this session
+    client_id: ClientId,
     command_handler: CommandHandler,
+    // interactive command and last point for relative coordinate input
+    command_session: CommandSession,
     viewport: Viewport,
+    renderer: Renderer,
+    // changes of the data model, not yet processed
+    changes: Rc<RefCell<Vec<Change>>>,
+    // journal lines not yet taken by javascript
+    journal: Rc<RefCell<Vec<u8>>>,
+    // files of exports not yet given to javascript
+    exports: MemorySink,
+    change_callback: Option<js_sys::Function>,
+    export_callback: Option<js_sys::Function>,
+}
+
+impl Default for ECAPI {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[wasm_bindgen]
 impl ECAPI {
     #[wasm_bindgen(constructor)]
     pub fn new() -> Self {
+        let changes = Rc::new(RefCell::new(vec![]));
+        let client_id = ClientId::random();
+        let data_model = observed_data_model(DataModel::default(), &client_id, &changes);
+        // there is no file system in the browser
+        let exports = MemorySink::default();
+        let mut command_handler = CommandHandler::default();
+        command_handler.set_sink(Box::new(exports.clone()));
+
         let ecapi = ECAPI {
-            data_model: DataModel::default(),
-            command_handler: CommandHandler::default(),
+            data_model,
+            client_id,
+            command_handler,
+            command_session: CommandSession::default(),
             viewport: Viewport::new(),
+            renderer: Renderer::new(),
+            changes,
+            journal: Rc::new(RefCell::new(vec![])),
+            exports,
+            change_callback: None,
+            export_callback: None,
         };
         log("WASM ECAPI initialized");
         ecapi
@@ -79,21 +182,41 @@ impl ECAPI {
         self.viewport.set_canvas_id(canvas_id);
     }
 
+    // brings the svg up to date with the current page,
+    // the page is only rebuilt completely if it is not rendered yet
     #[wasm_bindgen]
     pub fn render_current_page(&mut self) {
         // Find the page by ID
 
         if let Some(page) = self
             .data_model
-            .get_page(&self.data_model.get_current_page_id())
+            .get_page(self.data_model.get_current_page_id())
         {
-            let renderer = Renderer::new(&self.data_model, &self.viewport);
-            renderer.render_page(&page);
+            let result = self
+                .renderer
+                .update_viewport(&self.data_model, &self.viewport, page);
+            if let Err(err) = result {
+                log(&format!("Error rendering page: {:?}", err));
+            }
+            self.store_page_view();
         } else {
             log("Page not found");
         }
     }
 
+    // rebuild all svg elements of the current page
+    pub fn redraw_current_page(&mut self) {
+        if let Some(page) = self.data_model.get_current_page() {
+            let result = self
+                .renderer
+                .render_page(&self.data_model, &self.viewport, page);
+            if let Err(err) = result {
+                log(&format!("Error rendering page: {:?}", err));
+            }
+            self.store_page_view();
+        }
+    }
+
     pub fn zoom_viewport(&mut self, delta_y: f64, center_x: f64, center_y: f64) {
         // Zoom the viewport based on the mouse wheel event
         self.viewport.zoom_viewport(delta_y, center_x, center_y);
@@ -108,12 +231,119 @@ impl ECAPI {
         self.render_current_page();
     }
 
+    #[wasm_bindgen]
+    // false if the current page has no nodes
+    pub fn zoom_extents(&mut self) -> bool {
+        self.zoom(&Zoom::Extents)
+    }
+
+    #[wasm_bindgen]
+    pub fn zoom_window(&mut self, p1: Point2d, p2: Point2d) {
+        self.zoom(&Zoom::Window(p1, p2));
+    }
+
+    #[wasm_bindgen]
+    // false if there is no previous view
+    pub fn zoom_previous(&mut self) -> bool {
+        self.zoom(&Zoom::Previous)
+    }
+
+    #[wasm_bindgen]
+    pub fn zoom_scale(&mut self, factor: f64) {
+        self.zoom(&Zoom::Scale(factor));
+    }
+
+    #[wasm_bindgen]
+    pub fn zoom_center(&mut self, center: Point2d, height: Option<f64>) {
+        self.zoom(&Zoom::Center { center, height });
+    }
+
+    #[wasm_bindgen]
+    // moves the drawing by delta in model coordinates
+    pub fn pan(&mut self, delta: Point2d) {
+        self.zoom(&Zoom::Pan(delta));
+    }
+
+    #[wasm_bindgen]
+    // the last view of the page is restored
+    pub fn set_current_page(&mut self, page_id: String) -> Result<(), JsValue> {
+        if self.data_model.get_page(&page_id).is_none() {
+            return Err(JsValue::from_str(&format!("Unknown page '{}'", page_id)));
+        }
+        let cmd = CurrentPageCommand::new(&self.data_model, page_id.into());
+        self.execute(Box::new(cmd));
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    // the current view of the current page under a name
+    pub fn save_view(&mut self, name: String) {
+        let page_id = self.data_model.get_current_page_id().clone();
+        let view = Some(self.viewport.get_view());
+        let cmd = NamedViewCommand::new(&self.data_model, page_id, name, view);
+        self.execute(Box::new(cmd));
+    }
+
+    #[wasm_bindgen]
+    // false if the current page has no view of that name
+    pub fn restore_view(&mut self, name: &str) -> bool {
+        match self.get_named_view(name) {
+            Some(view) => self.zoom(&Zoom::View(view)),
+            None => false,
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn delete_view(&mut self, name: String) -> bool {
+        if self.get_named_view(&name).is_none() {
+            return false;
+        }
+        let page_id = self.data_model.get_current_page_id().clone();
+        let cmd = NamedViewCommand::new(&self.data_model, page_id, name, None);
+        self.execute(Box::new(cmd));
+        true
+    }
+
+    #[wasm_bindgen]
+    // names of the views of the current page
+    pub fn get_view_names(&self) -> Vec<String> {
+        self.data_model
+            .get_current_page()
+            .map(|page| page.get_named_views().keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    #[wasm_bindgen]
+    // degrees, the drawing turns clockwise around the center of the view
+    pub fn set_view_rotation(&mut self, angle: f64) {
+        self.viewport.set_rotation(angle);
+        self.render_current_page();
+    }
+
+    #[wasm_bindgen]
+    pub fn get_view_rotation(&self) -> f64 {
+        self.viewport.rotation
+    }
+
+    #[wasm_bindgen]
+    // typed points are given relative to origin with the x axis at angle
+    pub fn set_ucs(&mut self, origin: Point2d, angle: f64) {
+        let old = *self.data_model.get_ucs();
+        self.execute(Box::new(UcsCommand::new(old, Ucs::new(origin, angle))));
+    }
+
+    #[wasm_bindgen]
+    pub fn get_ucs(&self) -> Result<JsValue, JsValue> {
+        Ok(serde_wasm_bindgen::to_value(self.data_model.get_ucs())?)
+    }
+
     #[wasm_bindgen]
     pub fn resize_canvas(&mut self, width: f64, height: f64) {
         // Set the viewport dimensions
         self.viewport.set_canvas_size(width, height);
 
         log("resize canvas");
+        self.render_current_page();
     }
 
     #[wasm_bindgen]
@@ -121,38 +351,59 @@ impl ECAPI {
         if let Some(page) = self.data_model.get_current_page() {
             // Get the selected node IDs from the page
             let selected_ids = page.get_selected_ids();
-            let result = selected_ids.clone();
-            // log(&format!("Selected IDs: {:?}", result));
-            return result;
+            // log(&format!("Selected IDs: {:?}", selected_ids));
+            selected_ids.iter().map(|id| id.to_string()).collect()
         } else {
             log("No page found");
-            return vec![];
+            vec![]
         }
     }
 
     pub fn set_selection(&mut self, ids: Vec<String>) {
         // Set the selected node IDs in the current page
-        if let Some(page) = self.data_model.get_current_page_mut() {
-            page.set_selected_ids(ids.clone());
+        if self.data_model.get_current_page().is_some() {
+            let ids = ids.into_iter().map(NodeId::from).collect();
+            self.data_model.set_selection(ids);
+            self.process_changes();
         } else {
             log("No page found");
         }
     }
 
+    // the callback gets an array of changes ({type, id}) after each
+    // api call or command that modified the data model
+    pub fn set_change_callback(&mut self, callback: js_sys::Function) {
+        self.change_callback = Some(callback);
+    }
+
+    // the callback gets {filename, bytes} (bytes a Uint8Array) for every file
+    // of a command, e.g. "export plan.pdf", to offer a download or store it
+    pub fn set_export_callback(&mut self, callback: js_sys::Function) {
+        self.export_callback = Some(callback);
+        self.notify_export_callback();
+    }
+
+    #[wasm_bindgen]
+    // without an export callback the files are kept until they are taken,
+    // an array of {filename, bytes}
+    pub fn take_exports(&mut self) -> js_sys::Array {
+        self.exports.take().iter().map(output_file_to_js).collect()
+    }
+
     #[wasm_bindgen]
     pub fn create_page(&mut self, name: String) -> String {
         // Create a new page in the data model
         let id = self.data_model.next_id();
-        let page = datamodel::Page::new(id.clone(), name.clone(), "page description".to_string());
-        self.data_model.insert_page(page);
-        id
+        let cmd = PageCommand::new(id.clone(), name, "page description".to_string());
+        self.execute(Box::new(cmd));
+        id.into()
     }
 
     #[wasm_bindgen]
     pub fn create_line(&mut self) -> Result<JsValue, JsValue> {
         // Create a new line in the data model
         // and add it to the current page
-        if let None = self.data_model.get_current_page() {
+        if self.data_model.get_current_page().is_none() {
             log("No current page found");
             return Err(JsValue::from_str("No current page found"));
         }
@@ -160,37 +411,58 @@ impl ECAPI {
         let id = self.data_model.next_id();
         let line = datamodel::Line::new(id.clone());
 
-        let a = JsValue::from_serde(&line).unwrap();
-
         let result = serde_wasm_bindgen::to_value(&line)?;
 
-        self.data_model.insert_node(Box::new(line));
-        if let Some(page) = self.data_model.get_current_page_mut() {
-            page.add_node_id(id.clone());
-        } else {
-            log("No page found");
-        }
+        let cmd = LineCommand::new(id, line.x1, line.y1, line.x2, line.y2);
+        self.execute(Box::new(cmd));
 
         Ok(result)
     }
 
+    #[wasm_bindgen]
+    // the prefix of the ids this session gives out
+    pub fn get_client_id(&self) -> String {
+        self.client_id.to_string()
+    }
+
+    #[wasm_bindgen]
+    // the selected nodes as a json array, e.g. for the clipboard
+    pub fn copy_selection(&self) -> Result<String, JsValue> {
+        let nodes: Vec<_> = self
+            .get_selection()
+            .iter()
+            .filter_map(|id| self.data_model.get_node(id))
+            .collect();
+        serde_json::to_string(&nodes).map_err(|err| JsValue::from_str(&err.to_string()))
+    }
+
+    #[wasm_bindgen]
+    // nodes of copy_selection, maybe of another document, are added to the
+    // current page with new ids, which are returned
+    pub fn paste_nodes(&mut self, json: &str) -> Result<Vec<String>, JsValue> {
+        let error = |err: String| JsValue::from_str(&err);
+        let values: Vec<serde_json::Value> =
+            serde_json::from_str(json).map_err(|err| error(err.to_string()))?;
+        let nodes = values
+            .into_iter()
+            .map(node_from_json)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(error)?;
+        let cmd = ImportCommand::paste(&mut self.data_model, nodes).map_err(error)?;
+        let ids = cmd.get_node_ids();
+        self.try_execute(Box::new(cmd)).map_err(error)?;
+        Ok(ids.into_iter().map(String::from).collect())
+    }
+
     pub fn patch_node(&mut self, patch: JsValue) -> Result<JsValue, JsValue> {
         let base_node = patch.into_serde::<BaseNode>().unwrap();
         match base_node.node_type.as_str() {
             "Line" => {
                 let patch_line: Line = serde_wasm_bindgen::from_value(patch)?;
-                if let Some(node) = self.data_model.get_node_mut(&patch_line.get_id()) {
-                    if let Some(line) = node.as_any_mut().downcast_mut::<Line>() {
-                        // Update the line properties
-                        line.x1 = patch_line.x1;
-                        line.y1 = patch_line.y1;
-                        line.x2 = patch_line.x2;
-                        line.y2 = patch_line.y2;
-                    } else {
-                        log("Failed to downcast node to Line");
-                    }
-                } else {
-                    log("Node not found");
+                // a command, so the patch can be undone and is journaled
+                match ReplaceNodeCommand::new(&self.data_model, Box::new(patch_line)) {
+                    Ok(cmd) => self.execute(Box::new(cmd)),
+                    Err(err) => log(&err),
                 }
             }
             _ => {
@@ -201,11 +473,110 @@ impl ECAPI {
         Ok(JsValue::NULL)
     }
 
+    // F7
+    pub fn toggle_grid(&mut self) -> bool {
+        self.update_drawing_aids(|aids| aids.grid_on = !aids.grid_on)
+            .grid_on
+    }
+
+    // F9
+    pub fn toggle_snap(&mut self) -> bool {
+        self.update_drawing_aids(|aids| aids.snap_on = !aids.snap_on)
+            .snap_on
+    }
+
+    // F8
+    pub fn toggle_ortho(&mut self) -> bool {
+        self.update_drawing_aids(|aids| {
+            aids.ortho_on = !aids.ortho_on;
+            if aids.ortho_on {
+                aids.polar_on = false;
+            }
+        })
+        .ortho_on
+    }
+
+    // F10
+    pub fn toggle_polar(&mut self) -> bool {
+        self.update_drawing_aids(|aids| {
+            aids.polar_on = !aids.polar_on;
+            if aids.polar_on {
+                aids.ortho_on = false;
+            }
+        })
+        .polar_on
+    }
+
+    // F3
+    pub fn toggle_osnap(&mut self) -> bool {
+        self.update_drawing_aids(|aids| aids.osnap_on = !aids.osnap_on)
+            .osnap_on
+    }
+
+    pub fn set_grid(&mut self, spacing: f64, major_every: u32, origin: Point2d) {
+        self.update_drawing_aids(|aids| {
+            aids.grid.spacing = spacing;
+            aids.grid.major_every = major_every;
+            aids.grid.origin = origin;
+        });
+    }
+
+    pub fn set_polar_angle(&mut self, angle: f64) {
+        self.update_drawing_aids(|aids| aids.polar_angle = angle);
+    }
+
+    // apply object snap, snap, ortho and polar tracking to a canvas point
+    pub fn snap_point(&self, pt: Point2d, base: Option<Point2d>) -> Point2d {
+        let aids = self.data_model.get_drawing_aids();
+        if aids.osnap_on {
+            if let Some(page) = self.data_model.get_current_page() {
+                let tolerance = OSNAP_TOLERANCE / self.viewport.scale;
+                if let Some(snap_pt) = self.data_model.snap_to_node(page, &pt, tolerance) {
+                    return snap_pt;
+                }
+            }
+        }
+        aids.apply(base.as_ref(), &pt)
+    }
+
+    // pick the topmost node at a canvas point, tolerance is given in pixel
+    pub fn pick_node(&self, pt: Point2d, tolerance: f64) -> Option<String> {
+        let page = self.data_model.get_current_page()?;
+        self.data_model
+            .pick_node(page, &pt, tolerance / self.viewport.scale)
+            .map(String::from)
+    }
+
+    // nodes completely inside the rectangle p1, p2 (canvas coordinates)
+    pub fn select_window(&self, p1: Point2d, p2: Point2d) -> Vec<String> {
+        match self.data_model.get_current_page() {
+            Some(page) => self
+                .data_model
+                .select_window(page, &BBox::from_points(&p1, &p2))
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    // nodes inside or crossing the rectangle p1, p2 (canvas coordinates)
+    pub fn select_crossing(&self, p1: Point2d, p2: Point2d) -> Vec<String> {
+        match self.data_model.get_current_page() {
+            Some(page) => self
+                .data_model
+                .select_crossing(page, &BBox::from_points(&p1, &p2))
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            None => vec![],
+        }
+    }
+
     #[wasm_bindgen]
     pub fn client_to_canvas(&self, client_pt: Point2d) -> Point2d {
         // let client_pos = Point2D::from(point);
-        let canvas_pt = self.viewport.client_to_canvas(client_pt);
-        canvas_pt
+        self.viewport.client_to_canvas(client_pt)
 
         // let client_pos = point.into_serde::<Point2D>().unwrap();
 
@@ -243,15 +614,186 @@ impl ECAPI {
     // }
 
     #[wasm_bindgen]
-    pub fn run_command(&mut self, command_line: String) {
-        CommandLine::parse(&mut self.data_model, command_line.as_str())
-            .map(|cmd| {
-                self.command_handler.execute(&mut self.data_model, cmd);
-            })
-            .unwrap_or_else(|err| {
-                eprintln!("Error: {}", err);
-                log(&format!("Error: {}", err));
-            });
+    // a complete command line, a command name to start an interactive
+    // command or the answer to the current prompt
+    // throws {message, position} if the input can not be parsed
+    // returns the result of inquiry commands, e.g.
+    // {inquiry: "dist", distance, angle, delta, text}, otherwise undefined
+    pub fn run_command(&mut self, command_line: String) -> Result<JsValue, JsValue> {
+        self.command_input(Input::Text(command_line))
+    }
+
+    #[wasm_bindgen]
+    // a point clicked on the canvas while a command is prompting
+    pub fn command_point(&mut self, pt: Point2d) -> Result<JsValue, JsValue> {
+        let base = self.get_prompt_base();
+        let pt = self.snap_point(pt, base);
+        self.command_input(Input::Point(pt))
+    }
+
+    #[wasm_bindgen]
+    pub fn command_enter(&mut self) -> Result<JsValue, JsValue> {
+        self.command_input(Input::Enter)
+    }
+
+    #[wasm_bindgen]
+    pub fn command_escape(&mut self) -> Result<JsValue, JsValue> {
+        self.command_input(Input::Escape)
+    }
+
+    #[wasm_bindgen]
+    pub fn is_command_active(&self) -> bool {
+        self.command_session.is_active()
+    }
+
+    #[wasm_bindgen]
+    // {message, kind, keywords, default, text} or null
+    pub fn get_prompt(&self) -> Result<JsValue, JsValue> {
+        match self.command_session.get_prompt() {
+            Some(prompt) => Ok(serde_wasm_bindgen::to_value(&PromptInfo {
+                text: prompt.text(),
+                prompt,
+            })?),
+            None => Ok(JsValue::NULL),
+        }
+    }
+
+    #[wasm_bindgen]
+    // start of the rubber band
+    pub fn get_prompt_base(&self) -> Option<Point2d> {
+        self.command_session
+            .get_prompt()
+            .and_then(|prompt| prompt.base)
+    }
+
+    #[wasm_bindgen]
+    // x,y after the first point of a command is relative, #x,y absolute
+    pub fn set_dynamic_input(&mut self, on: bool) {
+        self.command_session
+            .get_command_line_mut()
+            .set_dynamic_input(on);
+    }
+
+    #[wasm_bindgen]
+    // @ in the next command refers to this point, e.g. a clicked point
+    pub fn set_last_point(&mut self, pt: Point2d) {
+        self.command_session
+            .get_command_line_mut()
+            .set_last_point(pt);
+    }
+
+    #[wasm_bindgen]
+    pub fn get_last_point(&self) -> Option<Point2d> {
+        self.command_session.get_command_line().get_last_point()
+    }
+
+    #[wasm_bindgen]
+    // the text of a script file, every command is one undo step
+    // returns {executed, errors: [{line_nr, line, error: {message, position}}]}
+    pub fn run_script(&mut self, text: &str, continue_on_error: bool) -> Result<JsValue, JsValue> {
+        let on_error = if continue_on_error {
+            OnError::Continue
+        } else {
+            OnError::Stop
+        };
+        let command_handler = &mut self.command_handler;
+        let result = run_script(
+            &mut self.command_session,
+            &mut self.data_model,
+            text,
+            on_error,
+            |dm, cmd| command_handler.execute(dm, cmd),
+        );
+        self.process_changes();
+        self.notify_export_callback();
+        Ok(serde_wasm_bindgen::to_value(&result)?)
+    }
+
+    #[wasm_bindgen]
+    // from now on executed commands are written to the journal
+    pub fn start_journal(&mut self) {
+        let writer = JournalBuffer(self.journal.clone());
+        self.command_handler
+            .set_journal(Journal::new(Box::new(writer)));
+    }
+
+    #[wasm_bindgen]
+    // the json lines written since the last call, e.g. to store them
+    pub fn take_journal(&mut self) -> String {
+        let bytes: Vec<u8> = self.journal.borrow_mut().drain(..).collect();
+        String::from_utf8_lossy(&bytes).to_string()
+    }
+
+    #[wasm_bindgen]
+    // a new data model from the journal, e.g. after a crash
+    pub fn replay_journal(&mut self, text: &str) -> Result<usize, JsValue> {
+        let mut data_model =
+            observed_data_model(DataModel::default(), &self.client_id, &self.changes);
+        let mut command_handler = CommandHandler::default();
+        command_handler.set_sink(Box::new(self.exports.clone()));
+        let count = replay(text.as_bytes(), &mut data_model, &mut command_handler)
+            .map_err(|err| JsValue::from_str(&err.to_string()))?;
+
+        // the journal goes on after the replayed commands
+        if let Some(journal) = self.command_handler.take_journal() {
+            command_handler.set_journal(journal);
+        }
+        self.data_model = data_model;
+        self.command_handler = command_handler;
+        self.changes.borrow_mut().clear();
+        self.redraw_current_page();
+        Ok(count)
+    }
+
+    #[wasm_bindgen]
+    // a saved datamodel.json of any format version replaces the drawing,
+    // undo and the journal start again from it
+    pub fn load_document(&mut self, json: &str) -> Result<(), JsValue> {
+        self.load(read_document(json))
+    }
+
+    #[wasm_bindgen]
+    // the same for a binary document, e.g. the bytes of an exported .dmb
+    pub fn load_binary_document(&mut self, bytes: &[u8]) -> Result<(), JsValue> {
+        self.load(read_binary(bytes))
+    }
+
+    fn load(&mut self, data_model: Result<DataModel, String>) -> Result<(), JsValue> {
+        let data_model = data_model.map_err(|err| JsValue::from_str(&err))?;
+        let mut command_handler = CommandHandler::default();
+        command_handler.set_sink(Box::new(self.exports.clone()));
+        self.data_model = observed_data_model(data_model, &self.client_id, &self.changes);
+        self.command_handler = command_handler;
+        self.changes.borrow_mut().clear();
+        self.redraw_current_page();
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    // command names for the auto-completion of the command line
+    pub fn complete_command(&self, prefix: &str) -> Vec<String> {
+        self.command_session
+            .get_command_line()
+            .get_registry()
+            .complete(prefix)
+    }
+
+    #[wasm_bindgen]
+    pub fn get_command_help(&self, name: &str) -> Option<String> {
+        self.command_session
+            .get_command_line()
+            .get_registry()
+            .help(name)
+    }
+
+    #[wasm_bindgen]
+    // aliases in the format of a PGP file, returns the number of aliases
+    pub fn load_aliases(&mut self, text: &str) -> Result<usize, JsValue> {
+        self.command_session
+            .get_command_line_mut()
+            .get_registry_mut()
+            .load_aliases(text)
+            .map_err(|err| JsValue::from_str(&err))
     }
 
     #[wasm_bindgen]
@@ -259,3 +801,185 @@ impl ECAPI {
         "Hi DataModel, wasm from Rust!".to_string()
     }
 }
+
+impl ECAPI {
+    fn command_input(&mut self, input: Input) -> Result<JsValue, JsValue> {
+        let text = match &input {
+            Input::Text(text) => text.clone(),
+            _ => String::new(),
+        };
+        match self.command_session.input(&mut self.data_model, input) {
+            Ok(Some(cmd)) => {
+                let result = match cmd.get_inquiry() {
+                    Some(inquiry) => {
+                        let text = inquiry.to_string();
+                        log(&text);
+                        serde_wasm_bindgen::to_value(&InquiryInfo { inquiry, text })?
+                    }
+                    None => JsValue::UNDEFINED,
+                };
+                // e.g. an export that failed, given like a parse error
+                if let Err(message) = self.try_execute(cmd) {
+                    log(&format!("Error: {}", message));
+                    return Err(serde_wasm_bindgen::to_value(&ParseError::new(message, 0))?);
+                }
+                Ok(result)
+            }
+            Ok(None) => Ok(JsValue::UNDEFINED),
+            Err(err) => {
+                log(&format!("Error: {}", err.show(&text)));
+                Err(serde_wasm_bindgen::to_value(&err)?)
+            }
+        }
+    }
+
+    fn execute(&mut self, cmd: Box<dyn Command>) {
+        if let Err(message) = self.try_execute(cmd) {
+            log(&format!("Error: {}", message));
+        }
+    }
+
+    fn try_execute(&mut self, cmd: Box<dyn Command>) -> Result<(), String> {
+        let zoom = cmd.get_zoom().cloned();
+        let result = self.command_handler.execute(&mut self.data_model, cmd);
+        self.process_changes();
+        self.notify_export_callback();
+        if let Some(zoom) = zoom {
+            self.zoom(&zoom);
+        }
+        result
+    }
+
+    fn get_named_view(&self, name: &str) -> Option<View> {
+        self.data_model
+            .get_current_page()
+            .and_then(|page| page.get_named_view(name))
+            .copied()
+    }
+
+    // the page remembers where it was looked at
+    fn store_page_view(&mut self) {
+        let page_id = self.data_model.get_current_page_id().clone();
+        self.data_model
+            .set_page_view(&page_id, self.viewport.get_view());
+    }
+
+    // the view the new current page was left with
+    fn restore_page_view(&mut self) {
+        let view = self
+            .data_model
+            .get_current_page()
+            .and_then(|page| page.get_view())
+            .copied();
+        if let Some(view) = view {
+            self.viewport.set_view(&view);
+        }
+        self.viewport.clear_history();
+    }
+
+    fn zoom(&mut self, zoom: &Zoom) -> bool {
+        let changed = zoom.apply(&mut self.viewport, &self.data_model);
+        if changed {
+            self.render_current_page();
+        }
+        changed
+    }
+
+    fn update_drawing_aids<F>(&mut self, f: F) -> DrawingAids
+    where
+        F: FnOnce(&mut DrawingAids),
+    {
+        let old = *self.data_model.get_drawing_aids();
+        let mut aids = old;
+        f(&mut aids);
+        self.execute(Box::new(DrawingAidsCommand::new(old, aids)));
+        aids
+    }
+
+    // update the svg and forward the changes to javascript
+    fn process_changes(&mut self) {
+        let changes: Vec<Change> = self.changes.borrow_mut().drain(..).collect();
+        if changes.is_empty() {
+            return;
+        }
+
+        let page_changed = changes.iter().any(|change| {
+            matches!(
+                change,
+                Change::CurrentPageChanged(_) | Change::PageRemoved(_)
+            )
+        });
+        if page_changed {
+            self.restore_page_view();
+            self.redraw_current_page();
+        } else {
+            let node_ids: Vec<String> = changes
+                .iter()
+                .filter_map(|change| match change {
+                    Change::NodeAdded(id) | Change::NodeRemoved(id) | Change::NodeModified(id) => {
+                        Some(id.to_string())
+                    }
+                    _ => None,
+                })
+                .collect();
+            if !node_ids.is_empty() {
+                self.update_rendered_nodes(&node_ids);
+            }
+            if changes.contains(&Change::DrawingAidsChanged) {
+                self.render_current_page();
+            }
+        }
+
+        self.notify_change_callback(&changes);
+    }
+
+    fn notify_change_callback(&self, changes: &[Change]) {
+        let Some(callback) = self.change_callback.clone() else {
+            return;
+        };
+        let value = match serde_wasm_bindgen::to_value(changes) {
+            Ok(value) => value,
+            Err(err) => {
+                log(&format!("Error converting changes: {:?}", err));
+                return;
+            }
+        };
+        // call after the current api call returned, so the callback may use the api again,
+        // the closure is freed when it was called
+        queue_microtask(&Closure::once_into_js(move || {
+            if let Err(err) = callback.call1(&JsValue::NULL, &value) {
+                log(&format!("Error calling change callback: {:?}", err));
+            }
+        }));
+    }
+
+    fn notify_export_callback(&self) {
+        let Some(callback) = self.export_callback.clone() else {
+            return;
+        };
+        let files: Vec<JsValue> = self.exports.take().iter().map(output_file_to_js).collect();
+        if files.is_empty() {
+            return;
+        }
+        // like the change callback after the current api call returned
+        queue_microtask(&Closure::once_into_js(move || {
+            for file in &files {
+                if let Err(err) = callback.call1(&JsValue::NULL, file) {
+                    log(&format!("Error calling export callback: {:?}", err));
+                }
+            }
+        }));
+    }
+
+    // apply changed nodes to the svg of the current page
+    fn update_rendered_nodes(&mut self, node_ids: &[String]) {
+        if let Some(page) = self.data_model.get_current_page() {
+            let result =
+                self.renderer
+                    .update_nodes(&self.data_model, &self.viewport, page, node_ids);
+            if let Err(err) = result {
+                log(&format!("Error rendering nodes: {:?}", err));
+            }
+        }
+    }
+}
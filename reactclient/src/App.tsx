@@ -1,19 +1,28 @@
 import "./App.css";
 
 import { APIContext } from "./APIContext";
-import { useContext } from "react";
+import { useContext, useState } from "react";
 import CommandLine from "./CommandLine";
 import { Statusbar } from "./Statusbar";
 import { Canvas } from "./Canvas";
 
 function App() {
   const api = useContext(APIContext);
+  // text of the last inquiry, e.g. dist or list
+  const [output, setOutput] = useState("");
 
   const onCommandEntered = (command: string) => {
     // console.log("Command entered:", command);
 
-    api?.run_command(command);
-    api?.render_current_page();
+    // the svg is updated by the api when the data model changes
+    try {
+      const result = api?.run_command(command);
+      if (result?.text) {
+        setOutput(result.text);
+      }
+    } catch (err: any) {
+      console.error(`${err.message} at position ${err.position}`);
+    }
     // api.
 
     // api.render();
@@ -24,6 +33,7 @@ function App() {
       <Canvas></Canvas>
 
       <Statusbar></Statusbar>
+      {output && <pre className="command-output">{output}</pre>}
       <div className="commandline">
         <CommandLine onChanged={onCommandEntered} />
       </div>
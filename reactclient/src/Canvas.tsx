@@ -48,7 +48,10 @@ export const Canvas = () => {
 
   const onMouseDown = (event: React.MouseEvent<SVGSVGElement>) => {
     event.preventDefault();
-    if (event.buttons === 4) {
+    if (event.buttons === 4 && event.detail === 2) {
+      // double click with the wheel shows all nodes
+      api.zoom_extents();
+    } else if (event.buttons === 4) {
       panningStart(event);
     } else {
       let pt_client = new Point2d(event.clientX, event.clientY);
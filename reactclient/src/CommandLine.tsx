@@ -2,6 +2,7 @@ import React, { useContext, useState } from "react";
 
 import "./CommandLine.css";
 import { EditorContext } from "./EditorContext";
+import { APIContext } from "./APIContext";
 
 interface CommandLineProps {
   onChanged: (input: string) => void;
@@ -9,16 +10,42 @@ interface CommandLineProps {
 
 const CommandLine: React.FC<CommandLineProps> = ({ onChanged }) => {
   const editor = useContext(EditorContext);
+  const api = useContext(APIContext);
   const [input, setInput] = useState("");
+  const [prompt, setPrompt] = useState<string | null>(null);
 
   const handleKeyPress = (event: React.KeyboardEvent<HTMLInputElement>) => {
     if (event.key === "Enter") {
+      const wasActive = api.is_command_active();
       onChanged(input);
       setInput(""); // Clear the input field after calling the handler
-      editor.dispatchEvent({
-        type: "command",
-        command: input,
-      });
+      // answers to a prompt are no tool commands
+      if (!wasActive && !api.is_command_active()) {
+        editor.dispatchEvent({
+          type: "command",
+          command: input,
+        });
+      }
+      setPrompt(api.get_prompt()?.text ?? null);
+    } else if (event.key === "Tab") {
+      event.preventDefault();
+      // complete to the common start of all matching command names
+      const names = api.complete_command(input.trim());
+      if (names.length > 0) {
+        let common = names[0];
+        for (const name of names) {
+          while (!name.startsWith(common)) {
+            common = common.slice(0, -1);
+          }
+        }
+        setInput(names.length === 1 ? common + " " : common);
+      }
+      if (names.length > 1) {
+        console.log(names.join("  "));
+      }
+    } else if (event.key === "Escape") {
+      api.command_escape();
+      setPrompt(null);
     }
   };
 
@@ -31,7 +58,7 @@ const CommandLine: React.FC<CommandLineProps> = ({ onChanged }) => {
         value={input}
         onChange={(e) => setInput(e.target.value)}
         onKeyDown={handleKeyPress}
-        placeholder="Type a command and press Enter..."
+        placeholder={prompt ?? "Type a command and press Enter..."}
       />
     </div>
   );
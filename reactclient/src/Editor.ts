@@ -4,7 +4,7 @@ import { CommandTool } from "./tool/CommandTool";
 import { ECEvent } from "./tool/Event";
 import { BaseTool } from "./tool/BaseTool";
 import { createNewTool } from "./tool/ToolFactory";
-import { ECAPI } from "wasm";
+import { ECAPI, Point2d } from "wasm";
 
 export class Editor {
   private activeTool: BaseTool | null = null;
@@ -33,6 +33,18 @@ export class Editor {
     if (event.type === "command") {
       const cmdTool = new CommandTool(this);
       cmdTool.handleEvent(event);
+    } else if (event.type === "mouse_down" && this.api.is_command_active()) {
+      // a prompting command gets the clicked point
+      try {
+        const result = this.api.command_point(
+          new Point2d(event.canvasX, event.canvasY)
+        );
+        if (result?.text) {
+          console.log(result.text);
+        }
+      } catch (err: any) {
+        console.error(err.message);
+      }
     } else {
       if (this.activeTool) {
         this.activeTool.handleEvent(event);
@@ -16,6 +16,12 @@ init()
     let page_id = api?.create_page("new page") ?? "";
     console.log("created PageId:", page_id);
 
+    // user aliases are optional
+    fetch("/dmapp.pgp")
+      .then((response) => (response.ok ? response.text() : ""))
+      .then((text) => console.log("aliases loaded:", api.load_aliases(text)))
+      .catch((err) => console.error("loading aliases failed", err));
+
     let editor = new Editor(api);
 
     createRoot(document.getElementById("root")!).render(
@@ -62,7 +62,6 @@ export class LineTool extends BaseTool {
             this.line.x2 = ev.canvasX;
             this.line.y2 = ev.canvasY;
             this.editor.api.patch_node(this.line);
-            this.editor.api.render_current_page();
           },
           target: "idle",
         },
@@ -71,7 +70,6 @@ export class LineTool extends BaseTool {
             this.line.x2 = ev.canvasX;
             this.line.y2 = ev.canvasY;
             this.editor.api.patch_node(this.line);
-            this.editor.api.render_current_page();
           },
         },
       },
@@ -2,32 +2,19 @@
 
 import { ECEvent } from "./Event";
 import { BaseTool } from "./BaseTool";
+import { Point2d } from "wasm";
 
-export class SelectTool extends BaseTool {
-  nr: number = 0;
+// pick tolerance in pixel
+const PICK_TOLERANCE = 4;
 
+export class SelectTool extends BaseTool {
   handleEvent(event: ECEvent): void {
     switch (event.type) {
       case "mouse_down":
-        // console.log("Mouse down at:", event.clientX, event.clientY);
-
-        const selection = this.editor.api.get_selection();
-        // console.log("Current selection:", selection);
-        selection.push(`${++this.nr}`);
-        this.editor.api.set_selection(selection);
-
-        let line = this.editor.api.create_line();
-        // console.log("Line created:", line);
-
-        line.x1 = event.canvasX;
-        line.y1 = event.canvasY;
-
-        line.x2 = event.canvasX + 100;
-        line.y2 = event.canvasY + 100;
-
-        this.editor.api.patch_node(line);
+        const pt = new Point2d(event.canvasX, event.canvasY);
+        const id = this.editor.api.pick_node(pt, PICK_TOLERANCE);
 
-        this.editor.api.render_current_page();
+        this.editor.api.set_selection(id ? [id] : []);
 
         break;
       // Add more event types as needed